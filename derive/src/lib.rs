@@ -0,0 +1,103 @@
+// Apache License
+
+// Copyright (c) 2021 Adam Quigley
+
+//! The FromSsm derive macro for aws-ssm-parameter-cache. Enable the `derive` feature of
+//! aws-ssm-parameter-cache and use it from there rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, LitStr, Token};
+
+/// Implements the FromSsm trait, for ParameterCache::load_bundle, and generates an async
+/// `load(&mut ParameterCache)` constructor that fetches every field's parameter with batched
+/// GetParameters calls.
+///
+/// Each field is marked with the name of its parameter, `#[ssm("/svc/db_url")]`, and is a
+/// String, or with `#[ssm("/svc/pool", parse)]` any type implementing FromStr.
+#[proc_macro_derive(FromSsm, attributes(ssm))]
+pub fn derive_from_ssm(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input,
+                    "FromSsm needs named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input,
+                "FromSsm only supports structs",
+            ))
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut initializers = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have idents");
+        let attr = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("ssm"))
+            .ok_or_else(|| syn::Error::new_spanned(field, "missing #[ssm(\"parameter name\")]"))?;
+        let (name, parse) = attr.parse_args_with(parse_ssm_args)?;
+        let value = match parse {
+            true => quote!(::aws_ssm_parameter_cache::__private::parse(values, #name)),
+            false => quote!(::aws_ssm_parameter_cache::__private::value(values, #name)),
+        };
+        let field_name = ident.to_string();
+        initializers.push(
+            quote!(#ident: ::aws_ssm_parameter_cache::__private::field(#field_name, #value)?),
+        );
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::aws_ssm_parameter_cache::FromSsm for #ident #type_generics #where_clause {
+            const PARAMETER_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn from_parameters(
+                values: &::std::collections::HashMap<::std::string::String, ::std::string::String>,
+            ) -> ::core::result::Result<Self, ::aws_ssm_parameter_cache::CacheError> {
+                ::core::result::Result::Ok(Self { #(#initializers,)* })
+            }
+        }
+
+        impl #impl_generics #ident #type_generics #where_clause {
+            /// Loads every field from its AWS SSM parameter, using the cache where possible.
+            pub async fn load(
+                parameter_cache: &mut ::aws_ssm_parameter_cache::ParameterCache,
+            ) -> ::core::result::Result<Self, ::aws_ssm_parameter_cache::CacheError> {
+                parameter_cache.load_bundle().await
+            }
+        }
+    })
+}
+
+// Parses `"name"` or `"name", parse`
+fn parse_ssm_args(input: ParseStream) -> syn::Result<(LitStr, bool)> {
+    let name: LitStr = input.parse()?;
+    if input.is_empty() {
+        return Ok((name, false));
+    }
+    input.parse::<Token![,]>()?;
+    let option: Ident = input.parse()?;
+    if option != "parse" {
+        return Err(syn::Error::new_spanned(option, "expected `parse`"));
+    }
+    Ok((name, true))
+}
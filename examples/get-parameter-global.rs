@@ -1,31 +1,15 @@
-use async_once::AsyncOnce;
 use aws_sdk_ssm::Client;
-use aws_ssm_parameter_cache::ParameterCache;
-use lazy_static::lazy_static;
-use std::sync::Mutex;
-
-// store the cache in the global scope - useful for runtime environments like AWS Lambda
-lazy_static! {
-    static ref CACHE: AsyncOnce<Mutex<ParameterCache>> = AsyncOnce::new(async {
-        Mutex::new(ParameterCache::new(Client::new(
-            &aws_config::from_env().load().await,
-        )))
-    });
-}
+use aws_ssm_parameter_cache::{global, CacheConfig};
 
 #[tokio::main]
 async fn main() {
+    // initialize the cache in the global scope - useful for runtime environments like AWS Lambda
+    let client = Client::new(&aws_config::from_env().load().await);
+    global::init(client, CacheConfig::new()).expect("the global cache is initialized once");
+
     let parameter_name = "service/parameter";
 
-    match CACHE
-        .get() // get cache from the global scope
-        .await
-        .lock() // acquire cache lock
-        .unwrap()
-        .get_parameter(parameter_name)
-        .send()
-        .await
-    {
+    match global::get_parameter(parameter_name).await {
         Ok(parameter_value) => {
             println!(
                 "Successfully retrieved parameter {}: {}",
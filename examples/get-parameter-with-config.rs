@@ -1,5 +1,5 @@
 use aws_sdk_ssm::Client;
-use aws_ssm_parameter_cache::{CacheConfig, ParameterCache};
+use aws_ssm_parameter_cache::{CacheConfig, Consistency, ParameterCache};
 use std::time;
 
 #[tokio::main]
@@ -16,7 +16,7 @@ async fn main() {
 
     match cache
         .get_parameter(parameter_name)
-        .force_refresh() // force the value to be fetched from AWS and updated in the cache
+        .consistency(Consistency::Fresh) // force the value to be fetched from AWS and updated in the cache
         .send()
         .await
     {
@@ -0,0 +1,186 @@
+use aws_sdk_ssm::types::ParameterStringFilter;
+use tokio::task::JoinSet;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+use super::trace::Instrument;
+
+/// Whether a parameter could be read, as reported by check_access.
+#[derive(Debug)]
+pub enum AccessResult {
+    /// The parameter exists and could be read.
+    Ok,
+
+    /// Reading the parameter was denied, for example by IAM or KMS.
+    Denied,
+
+    /// The parameter doesn't exist.
+    NotFound,
+
+    /// Reading the parameter failed for another reason, such as throttling.
+    Failed(CacheError),
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Checks that every parameter a service needs can be read, e.g. as a startup preflight.
+    ///
+    /// Each parameter is read with GetParameter, decrypted if default_with_decryption is set
+    /// in the CacheConfig so that KMS permissions are checked too. The reads run concurrently,
+    /// at most max_concurrent_requests at a time. Nothing is stored in the cache.
+    ///
+    /// Returns the result for each name, in the order given.
+    pub async fn check_access(&self, names: &[&str]) -> Vec<(String, AccessResult)> {
+        let mut reads = JoinSet::new();
+        for (index, &name) in names.iter().enumerate() {
+            let client = self.client.clone();
+            let semaphore = self.request_limit.clone();
+            let fetch_rate = self.fetch_rate.clone();
+            let with_decryption = self.config.default_with_decryption;
+            let refused = self.dry_run_refusal(name, "GetParameter");
+            let name = name.to_string();
+            reads.spawn(
+                async move {
+                    if let Some(e) = refused {
+                        return (index, name, AccessResult::Failed(e));
+                    }
+                    let _permit = semaphore.acquire_owned().await;
+                    fetch_rate.acquire().await;
+                    let result = client
+                        .get_parameter()
+                        .name(&name)
+                        .with_decryption(with_decryption)
+                        .send()
+                        .await;
+                    let access = match result.map_err(|e| CacheError::from_sdk_error(&name, e)) {
+                        Ok(_) => AccessResult::Ok,
+                        Err(CacheError::AccessDenied { .. }) => AccessResult::Denied,
+                        Err(e) if e.is_not_found() => AccessResult::NotFound,
+                        Err(e) => AccessResult::Failed(e),
+                    };
+                    (index, name, access)
+                }
+                .in_current_span(),
+            );
+        }
+
+        let mut results = Vec::with_capacity(names.len());
+        while let Some(read) = reads.join_next().await {
+            results.push(read.expect("check_access task panicked"));
+        }
+        results.sort_unstable_by_key(|(index, _, _)| *index);
+        results
+            .into_iter()
+            .map(|(_, name, access)| (name, access))
+            .collect()
+    }
+
+    /// Checks whether a parameter exists, without fetching its value.
+    ///
+    /// Returns true straight away if the parameter has an unexpired cached value. Otherwise the
+    /// parameter is looked up with DescribeParameters, which needs the ssm:DescribeParameters
+    /// permission but neither transfers the value nor needs permission to decrypt it. The
+    /// answer isn't cached, so a later call checks again.
+    pub async fn exists(&self, name: &str) -> Result<bool, CacheError> {
+        if self.peek_unexpired(name).is_some() {
+            return Ok(true);
+        }
+        if let Some(e) = self.dry_run_refusal(name, "DescribeParameters") {
+            return Err(e);
+        }
+        let filter = ParameterStringFilter::builder()
+            .key("Name")
+            .option("Equals")
+            .values(name)
+            .build()
+            .expect("ParameterStringFilter key is set");
+        self.fetch_rate.acquire().await;
+        let output = self
+            .client
+            .describe_parameters()
+            .parameter_filters(filter)
+            .send()
+            .await
+            .map_err(|e| CacheError::from_sdk_error(name, e))?;
+        Ok(output
+            .parameters()
+            .iter()
+            .any(|metadata| metadata.name() == Some(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{error_response, mock_ssm_client, parameter_response};
+
+    #[tokio::test]
+    async fn access_checks_refused_in_dry_run() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| panic!("called {}", operation));
+        let config = CacheConfig::new().dry_run(true);
+        let parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let results = parameter_cache.check_access(&["a"]).await;
+        assert!(matches!(
+            results[0].1,
+            AccessResult::Failed(CacheError::DryRun { .. })
+        ));
+        let exists = parameter_cache.exists("a").await;
+        assert!(matches!(exists, Err(CacheError::DryRun { .. })));
+    }
+
+    #[tokio::test]
+    async fn exists_checks_without_fetching() {
+        let mock_ssm_client = mock_ssm_client(|operation, body| {
+            assert_eq!(operation, "DescribeParameters");
+            match body.contains("present") {
+                true => (200, r#"{"Parameters":[{"Name":"present"}]}"#.to_string()),
+                false => (200, r#"{"Parameters":[]}"#.to_string()),
+            }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache.insert(
+            CacheKey::new("cached", false),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+
+        assert!(parameter_cache.exists("present").await.unwrap());
+        assert!(!parameter_cache.exists("absent").await.unwrap());
+        assert!(parameter_cache.exists("cached").await.unwrap());
+        assert_eq!(parameter_cache.cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn check_access_reports_each_name() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            if body.contains("denied") {
+                error_response("AccessDeniedException")
+            } else if body.contains("missing") {
+                error_response("ParameterNotFound")
+            } else if body.contains("throttled") {
+                error_response("ThrottlingException")
+            } else {
+                (200, parameter_response("readable", "value"))
+            }
+        });
+        let parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let results = parameter_cache
+            .check_access(&["readable", "denied", "missing", "throttled"])
+            .await;
+
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["readable", "denied", "missing", "throttled"]);
+        assert!(matches!(results[0].1, AccessResult::Ok));
+        assert!(matches!(results[1].1, AccessResult::Denied));
+        assert!(matches!(results[2].1, AccessResult::NotFound));
+        assert!(matches!(
+            results[3].1,
+            AccessResult::Failed(CacheError::Throttled { .. })
+        ));
+        assert!(parameter_cache.cache.is_empty());
+    }
+}
@@ -0,0 +1,633 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use tokio::task::JoinSet;
+
+use super::cache::{lookup, Consistency, ParameterCache};
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+use super::trace::Instrument;
+use aws_sdk_ssm::operation::get_parameters::GetParametersOutput;
+use aws_sdk_ssm::types::Parameter;
+
+// The most names GetParameters accepts in one call
+pub(crate) const MAX_NAMES_PER_CALL: usize = 10;
+
+/// The result of a get_parameters request.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GetParametersResult {
+    /// The values of the parameters that were found, keyed by the requested name.
+    pub parameters: HashMap<String, String>,
+
+    /// The requested names that AWS SSM reported as invalid, for example because
+    /// the parameter doesn't exist, and those with empty values when empty_as_not_found is set.
+    pub invalid_parameters: Vec<String>,
+}
+
+/// A builder for the get_parameters method.
+pub struct GetParametersBuilder<'a, 'b, S> {
+    parameter_cache: &'a mut ParameterCache<S>,
+    parameter_names: &'b [&'b str],
+    force_refresh: bool,
+    cache_batch: bool,
+    with_decryption: bool,
+}
+
+impl<'a, 'b, S: CacheStore<CacheKey, CacheItem<String>>> GetParametersBuilder<'a, 'b, S> {
+    pub fn new(parameter_cache: &'a mut ParameterCache<S>, parameter_names: &'b [&'b str]) -> Self {
+        let with_decryption = parameter_cache.config.default_with_decryption;
+        GetParametersBuilder {
+            parameter_cache,
+            parameter_names,
+            force_refresh: false,
+            cache_batch: false,
+            with_decryption,
+        }
+    }
+
+    /// Forces all of the parameters to be fetched from AWS, updating the cache with the fresh values.
+    pub fn force_refresh(mut self) -> Self {
+        self.force_refresh = true;
+        self
+    }
+
+    /// Also caches the whole result under a key made from the sorted parameter names.
+    ///
+    /// A later request for the same set of names, in any order, is then answered from that one
+    /// entry without looking up each parameter. The batch entry expires with the cache_item_ttl
+    /// from the CacheConfig independently of the individual entries, so refreshing one of the
+    /// parameters on its own doesn't update the batch entry.
+    ///
+    /// Batch entries are held in addition to the individual entries, so each value is stored
+    /// twice. Batch entries are kept in a separate LRU bounded by max_cache_size.
+    pub fn cache_batch(mut self) -> Self {
+        self.cache_batch = true;
+        self
+    }
+
+    /// Sets whether SecureString values are fetched decrypted.
+    ///
+    /// Overrides default_with_decryption from the CacheConfig for this call.
+    pub fn with_decryption(mut self, with_decryption: bool) -> Self {
+        self.with_decryption = with_decryption;
+        self
+    }
+
+    /// Fetches the parameter values, using the cache where possible.
+    ///
+    /// Parameters that are cached and haven't expired are served from the cache. The remaining
+    /// parameters are fetched from AWS SSM with GetParameters, which accepts at most 10 names, so
+    /// larger batches are split into concurrent calls of up to 10 names, at most
+    /// max_concurrent_requests at a time, and their results merged. Each value found is stored in
+    /// the cache with the cache_item_ttl from the CacheConfig. Names that AWS SSM reports as
+    /// invalid are returned in invalid_parameters and are not cached. Throttled calls are retried
+    /// as set by max_retries. If any call fails its error is returned, though values from the
+    /// calls that succeeded are still cached.
+    pub async fn send(mut self) -> Result<GetParametersResult, CacheError> {
+        let batch_key = self.batch_key();
+        let caching_enabled = self.parameter_cache.config.caching_enabled;
+        let cache_batch = self.cache_batch && caching_enabled;
+        let update_recency_on_hit = self.parameter_cache.config.update_recency_on_hit;
+        if cache_batch && !self.force_refresh {
+            let batch_cache = &mut self.parameter_cache.batch_cache;
+            if let Some(cache_item) = lookup(batch_cache, update_recency_on_hit, &batch_key) {
+                if !cache_item.is_expired() {
+                    let result = cache_item.value.clone();
+                    for parameter_name in self.unique_names() {
+                        self.parameter_cache.record_lookup(parameter_name, true);
+                    }
+                    return Ok(result);
+                }
+            }
+        }
+
+        let mut result = GetParametersResult::default();
+        let mut misses = Vec::new();
+        for &parameter_name in self.parameter_names {
+            if result.parameters.contains_key(parameter_name) || misses.contains(&parameter_name) {
+                continue;
+            }
+            let cached = match self.force_refresh || !caching_enabled {
+                true => None,
+                false => lookup(
+                    &mut self.parameter_cache.cache,
+                    update_recency_on_hit,
+                    &CacheKey::new(parameter_name, self.with_decryption),
+                )
+                .filter(|cache_item| !cache_item.is_expired())
+                .map(|cache_item| cache_item.value.clone()),
+            };
+            self.parameter_cache
+                .record_lookup(parameter_name, cached.is_some());
+            match cached {
+                Some(value) => {
+                    result.parameters.insert(parameter_name.to_string(), value);
+                }
+                None => misses.push(parameter_name),
+            }
+        }
+
+        if self.parameter_cache.config.dry_run && !misses.is_empty() {
+            let output = self.dry_run_output(&misses)?;
+            self.store_output(output, &mut result);
+            misses.clear();
+        }
+
+        let mut fetches = JoinSet::new();
+        for chunk in misses.chunks(MAX_NAMES_PER_CALL) {
+            let names: Vec<String> = chunk.iter().map(|name| name.to_string()).collect();
+            let request = self
+                .parameter_cache
+                .client
+                .get_parameters()
+                .set_names(Some(names.clone()))
+                .with_decryption(self.with_decryption);
+            let semaphore = self.parameter_cache.request_limit.clone();
+            let retry = self.parameter_cache.retry_policy();
+            fetches.spawn(
+                async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    let started = Instant::now();
+                    let output = retry
+                        .send(|| request.clone().send())
+                        .await
+                        .map_err(|e| CacheError::from_sdk_error(&names.join(", "), e));
+                    (names, started, output)
+                }
+                .in_current_span(),
+            );
+        }
+        let mut failure = None;
+        while let Some(fetch) = fetches.join_next().await {
+            let (names, started, output) = fetch.expect("get_parameters task panicked");
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            self.parameter_cache.record_fetch(&names, started, &output);
+            match output {
+                Ok(output) => self.store_output(output, &mut result),
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        if cache_batch {
+            let ttl = self.parameter_cache.config.cache_item_ttl;
+            let clock = self.parameter_cache.clock.clone();
+            self.parameter_cache.batch_cache.put(
+                batch_key,
+                CacheItem::new_with_clock(result.clone(), ttl, clock),
+            );
+        }
+        Ok(result)
+    }
+
+    // The answer to GetParameters calls in dry run mode, made without calling AWS: each name
+    // gets the dry_run_placeholder, as get_parameter answers it
+    fn dry_run_output(&self, names: &[&str]) -> Result<GetParametersOutput, CacheError> {
+        let mut parameters = Vec::with_capacity(names.len());
+        for name in names {
+            let output = self
+                .parameter_cache
+                .dry_run_output(name, self.with_decryption, None)?;
+            parameters.extend(output.parameter);
+        }
+        Ok(GetParametersOutput::builder()
+            .set_parameters(Some(parameters))
+            .build())
+    }
+
+    // Caches the values of a GetParameters call and adds them to the result
+    fn store_output(&mut self, output: GetParametersOutput, result: &mut GetParametersResult) {
+        let ttl = self.parameter_cache.config.cache_item_ttl;
+        let empty_as_not_found = self.parameter_cache.config.empty_as_not_found;
+        result
+            .invalid_parameters
+            .extend_from_slice(output.invalid_parameters());
+        for parameter in output.parameters() {
+            if let (Some(parameter_name), Some(value)) =
+                (requested_name(parameter), parameter.value())
+            {
+                if value.is_empty() && empty_as_not_found {
+                    result.invalid_parameters.push(parameter_name);
+                    continue;
+                }
+                let mut cache_item = CacheItem::new_with_clock(
+                    value.to_string(),
+                    ttl,
+                    self.parameter_cache.clock.clone(),
+                );
+                cache_item.set_metadata(parameter);
+                self.parameter_cache.insert(
+                    CacheKey::new(parameter_name.clone(), self.with_decryption),
+                    cache_item,
+                );
+                result.parameters.insert(parameter_name, value.to_string());
+            }
+        }
+    }
+
+    fn batch_key(&self) -> String {
+        format!(
+            "{}\n{}",
+            self.with_decryption,
+            self.unique_names().join("\n")
+        )
+    }
+
+    fn unique_names(&self) -> Vec<&'b str> {
+        let mut names = self.parameter_names.to_vec();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Refetches only the cached parameters whose values have expired, e.g. as periodic
+    /// maintenance that is cheaper than refreshing everything. Pairs with expired_keys.
+    ///
+    /// Expired values are fetched with GetParameters, ten names per call, keeping each value's
+    /// decryption setting; values cached for a registered account or a tenant are fetched one
+    /// at a time, with any account's client. If a GetParameters call fails, its names are
+    /// fetched one at a time so each gets its own result. A parameter that no longer exists is reported as a
+    /// CacheError::NotFound, and its expired value stays cached until it is evicted.
+    ///
+    /// Returns the result for each refreshed name.
+    pub async fn refresh_expired(&mut self) -> Vec<(String, Result<(), CacheError>)> {
+        let expired: Vec<CacheKey> = self
+            .cache
+            .iter()
+            .filter(|(_, cache_item)| cache_item.is_expired())
+            .map(|(key, _)| key.clone())
+            .collect();
+        self.refresh_keys(expired).await
+    }
+
+    /// Refetches every cached parameter whose name matches a predicate, expired or not, e.g. to
+    /// refresh everything under "/secrets/" on a rotation event.
+    ///
+    /// The matching entries are refetched as refresh_expired refetches expired ones, in
+    /// GetParameters calls of up to ten names where possible. Returns the result for each
+    /// refreshed name, so partial failures can be told apart.
+    pub async fn refresh_matching(
+        &mut self,
+        predicate: impl Fn(&str) -> bool,
+    ) -> Vec<(String, Result<(), CacheError>)> {
+        let matching: Vec<CacheKey> = self
+            .cache
+            .iter()
+            .filter(|(key, _)| predicate(&key.name))
+            .map(|(key, _)| key.clone())
+            .collect();
+        self.refresh_keys(matching).await
+    }
+
+    // Refetches the cached entries with these keys, batching those for the default client
+    async fn refresh_keys(&mut self, keys: Vec<CacheKey>) -> Vec<(String, Result<(), CacheError>)> {
+        let mut results = Vec::new();
+        let mut batches: [Vec<String>; 2] = Default::default();
+        for key in keys {
+            if key.account.is_none() && key.tenant.is_none() {
+                batches[usize::from(key.with_decryption)].push(key.name);
+                continue;
+            }
+            let mut request = self
+                .get_parameter(&key.name)
+                .with_decryption(key.with_decryption)
+                .consistency(Consistency::Fresh);
+            if let Some(account) = key.account {
+                request = request.account(account);
+            }
+            if let Some(tenant) = key.tenant {
+                request = request.tenant(tenant);
+            }
+            let result = request.send().await.map(|_| ());
+            results.push((key.name, result));
+        }
+
+        for (with_decryption, names) in [false, true].into_iter().zip(batches) {
+            for chunk in names.chunks(MAX_NAMES_PER_CALL) {
+                let chunk: Vec<&str> = chunk.iter().map(String::as_str).collect();
+                let fetched = self
+                    .get_parameters(&chunk)
+                    .with_decryption(with_decryption)
+                    .force_refresh()
+                    .send()
+                    .await;
+                for name in chunk {
+                    let result = match &fetched {
+                        Ok(fetched) if fetched.parameters.contains_key(name) => Ok(()),
+                        Ok(_) => Err(CacheError::not_found(name, "Parameter no longer exists")),
+                        Err(_) => self
+                            .get_parameter(name)
+                            .with_decryption(with_decryption)
+                            .consistency(Consistency::Fresh)
+                            .send()
+                            .await
+                            .map(|_| ()),
+                    };
+                    results.push((name.to_string(), result));
+                }
+            }
+        }
+        results
+    }
+
+    /// Returns whether two parameters have the same value, e.g. to check that a primary and a
+    /// replica configuration match.
+    ///
+    /// Both values are fetched from AWS SSM in one GetParameters call, bypassing the cache, so
+    /// they are read at nearly the same moment; this isn't transactional, but avoids comparing a
+    /// fresh value with one cached long ago. The fetched values are cached as get_parameters
+    /// does. Returns a CacheError::NotFound if either parameter doesn't exist.
+    pub async fn get_and_compare(
+        &mut self,
+        name_a: &str,
+        name_b: &str,
+    ) -> Result<bool, CacheError> {
+        let mut result = self
+            .get_parameters(&[name_a, name_b])
+            .force_refresh()
+            .send()
+            .await?;
+        let mut value = |name: &str| {
+            result
+                .parameters
+                .remove(name)
+                .ok_or_else(|| CacheError::not_found(name, "Parameter not found by GetParameters"))
+        };
+        if name_a == name_b {
+            return value(name_a).map(|_| true);
+        }
+        Ok(value(name_a)? == value(name_b)?)
+    }
+}
+
+// The name a returned parameter was requested by, including any version or label selector
+fn requested_name(parameter: &Parameter) -> Option<String> {
+    parameter
+        .name()
+        .map(|name| format!("{}{}", name, parameter.selector().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{batch_response, error_response, mock_ssm_client};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const GET_PARAMETERS_RESPONSE: &str = r#"{
+        "Parameters": [
+            {"Name": "a", "Type": "String", "Value": "value-a", "Version": 1},
+            {"Name": "b", "Type": "String", "Value": "value-b", "Version": 1}
+        ],
+        "InvalidParameters": ["missing"]
+    }"#;
+
+    #[tokio::test]
+    async fn get_and_compare_fetches_both_in_one_call() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, _| {
+            assert_eq!(operation, "GetParameters");
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, GET_PARAMETERS_RESPONSE.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache.insert(
+            CacheKey::new("a", false),
+            CacheItem::new("value-b".to_string(), 3600000000000),
+        );
+
+        assert!(!parameter_cache.get_and_compare("a", "b").await.unwrap());
+        assert!(parameter_cache.get_and_compare("a", "a").await.unwrap());
+        let missing = parameter_cache.get_and_compare("a", "missing").await;
+        assert!(missing.unwrap_err().is_not_found());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn get_parameters_without_recency_updates() {
+        for (update_recency_on_hit, candidate) in [(true, "b"), (false, "a")] {
+            let mock_ssm_client = mock_ssm_client(|operation, _| panic!("called {}", operation));
+            let config = CacheConfig::new().update_recency_on_hit(update_recency_on_hit);
+            let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+            for name in ["a", "b"] {
+                parameter_cache.insert(
+                    CacheKey::new(name, false),
+                    CacheItem::new("value".to_string(), 3600000000000),
+                );
+            }
+
+            let result = parameter_cache.get_parameters(&["a"]).send().await.unwrap();
+            assert_eq!(result.parameters["a"], "value");
+            assert_eq!(
+                parameter_cache.next_eviction_candidate().unwrap(),
+                candidate
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn get_parameters_dry_run() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| panic!("called {}", operation));
+        let config = CacheConfig::new()
+            .dry_run(true)
+            .dry_run_placeholder("placeholder");
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let result = parameter_cache.get_parameters(&["a", "b"]).send().await;
+        let parameters = result.unwrap().parameters;
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters["b"], "placeholder");
+    }
+
+    #[tokio::test]
+    async fn get_parameters_retries_kms_throttling() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(
+                move |_, body| match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => error_response("KMSThrottlingException"),
+                    _ => (200, batch_response(body)),
+                },
+            );
+        let config = CacheConfig::new()
+            .max_retries(1)
+            .base_backoff(Duration::from_millis(1));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let result = parameter_cache
+            .get_parameters(&["a", "b"])
+            .with_decryption(true)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(result.parameters.len(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn get_parameters_splits_large_batches() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, body| {
+            assert_eq!(operation, "GetParameters");
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, batch_response(body))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let mut names: Vec<String> = (0..23).map(|i| format!("p{}", i)).collect();
+        names.extend(["missing-a".to_string(), "missing-b".to_string()]);
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        let result = parameter_cache.get_parameters(&names).send().await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(result.parameters.len(), 23);
+        assert_eq!(result.parameters["p17"], "value-p17");
+        let mut invalid = result.invalid_parameters.clone();
+        invalid.sort();
+        assert_eq!(invalid, vec!["missing-a", "missing-b"]);
+    }
+
+    #[tokio::test]
+    async fn refresh_expired_fetches_only_expired() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, body| {
+            assert_eq!(operation, "GetParameters");
+            assert!(!body.contains("fresh"));
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, GET_PARAMETERS_RESPONSE.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        for name in ["a", "b", "missing"] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new("old".to_string(), 0),
+            );
+        }
+        parameter_cache.insert(
+            CacheKey::new("fresh", false),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+
+        let mut results = parameter_cache.refresh_expired().await;
+        results.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].1.is_ok() && results[1].1.is_ok());
+        assert!(results[2].1.as_ref().unwrap_err().is_not_found());
+        assert_eq!(parameter_cache.expired_keys(), vec!["missing".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn refresh_matching_fetches_matching_names() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, body| {
+            assert_eq!(operation, "GetParameters");
+            assert!(!body.contains("/config/"), "{}", body);
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, batch_response(body))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let mut names: Vec<String> = (0..12).map(|i| format!("/secrets/s{}", i)).collect();
+        names.extend(["/secrets/missing".to_string(), "/config/c".to_string()]);
+        for name in &names {
+            parameter_cache.insert(
+                CacheKey::new(name.as_str(), false),
+                CacheItem::new("old".to_string(), 3600000000000),
+            );
+        }
+
+        let results = parameter_cache
+            .refresh_matching(|name| name.starts_with("/secrets/"))
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 13);
+        for (name, result) in &results {
+            assert_eq!(result.is_err(), name == "/secrets/missing", "{}", name);
+        }
+        let value = |name: &str| {
+            &parameter_cache
+                .cache
+                .peek(&CacheKey::new(name, false))
+                .unwrap()
+                .value
+        };
+        assert_eq!(value("/secrets/s3"), "value-/secrets/s3");
+        assert_eq!(value("/config/c"), "old");
+    }
+
+    #[tokio::test]
+    async fn get_parameters_populates_individual_entries() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, _| {
+            assert_eq!(operation, "GetParameters");
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, GET_PARAMETERS_RESPONSE.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let result = parameter_cache
+            .get_parameters(&["a", "b", "missing"])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            result.parameters.get("a").map(String::as_str),
+            Some("value-a")
+        );
+        assert_eq!(
+            result.parameters.get("b").map(String::as_str),
+            Some("value-b")
+        );
+        assert_eq!(result.invalid_parameters, vec!["missing".to_string()]);
+
+        // the individual entries are now cache hits
+        let value = parameter_cache.get_parameter("a").send().await.unwrap();
+        assert_eq!(value, "value-a");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn get_parameters_cache_batch() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, GET_PARAMETERS_RESPONSE.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let first = parameter_cache
+            .get_parameters(&["a", "b", "missing"])
+            .cache_batch()
+            .send()
+            .await
+            .unwrap();
+        // the same set of names in a different order resolves from the batch entry,
+        // including the invalid name that would otherwise be fetched again
+        let second = parameter_cache
+            .get_parameters(&["missing", "b", "a"])
+            .cache_batch()
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::{Duration, SystemTime};
+
+/// The state of the circuit breaker guarding fetches from AWS SSM, reported by
+/// ParameterCache::stats.
+///
+/// - Closed: fetches go to AWS. breaker_threshold consecutive failures open the breaker.
+/// - Open: fetches fail fast with a CacheError::CircuitOpen, without calling AWS, until
+///   breaker_cooldown has passed since the breaker opened.
+/// - HalfOpen: the cooldown has passed and the next fetch goes to AWS as a trial. Its success
+///   closes the breaker; its failure opens it again for another cooldown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Fetches go to AWS SSM.
+    #[default]
+    Closed,
+    /// Fetches fail fast without calling AWS SSM.
+    Open,
+    /// The next fetch goes to AWS SSM to test whether it has recovered.
+    HalfOpen,
+}
+
+/// Counts consecutive fetch failures to decide the BreakerState.
+#[derive(Debug, Default)]
+pub(crate) struct CircuitBreaker {
+    failures: usize,
+    opened_at: Option<SystemTime>,
+}
+
+impl CircuitBreaker {
+    /// Returns the state of the breaker at the time now, read from the cache's clock. It is
+    /// always Closed without a threshold.
+    pub(crate) fn state(&self, cooldown: Duration, now: SystemTime) -> BreakerState {
+        match self.opened_at {
+            None => BreakerState::Closed,
+            Some(opened_at) if now.duration_since(opened_at).unwrap_or_default() < cooldown => {
+                BreakerState::Open
+            }
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Counts the outcome of a fetch completed at the time now, opening the breaker once
+    /// threshold failures in a row have been seen, or on any failure while half open.
+    pub(crate) fn record(&mut self, threshold: Option<usize>, success: bool, now: SystemTime) {
+        let Some(threshold) = threshold else {
+            return;
+        };
+        match success {
+            true => *self = CircuitBreaker::default(),
+            false => {
+                self.failures += 1;
+                if self.failures >= threshold.max(1) {
+                    self.opened_at = Some(now);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let cooldown = Duration::from_secs(60);
+        let now = SystemTime::now();
+        let mut breaker = CircuitBreaker::default();
+        breaker.record(Some(2), false, now);
+        breaker.record(Some(2), true, now);
+        breaker.record(Some(2), false, now);
+        assert_eq!(breaker.state(cooldown, now), BreakerState::Closed);
+        breaker.record(Some(2), false, now);
+        assert_eq!(breaker.state(cooldown, now), BreakerState::Open);
+    }
+
+    #[test]
+    fn half_open_trial_decides() {
+        let cooldown = Duration::from_secs(60);
+        let now = SystemTime::now();
+        let later = now + cooldown;
+        let mut breaker = CircuitBreaker::default();
+        breaker.record(Some(1), false, now);
+        assert_eq!(breaker.state(cooldown, now), BreakerState::Open);
+        assert_eq!(breaker.state(cooldown, later), BreakerState::HalfOpen);
+        breaker.record(Some(1), false, later);
+        assert_eq!(breaker.state(cooldown, later), BreakerState::Open);
+        breaker.record(Some(1), true, later);
+        assert_eq!(breaker.state(cooldown, later), BreakerState::Closed);
+        breaker.record(None, false, later);
+        assert_eq!(breaker.state(cooldown, later), BreakerState::Closed);
+    }
+}
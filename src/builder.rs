@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aws_sdk_ssm::Client as SSMClient;
+use lru::LruCache;
+
+use super::cache::{new_lru, ParameterCache};
+use super::cache_item::CacheItem;
+use super::clock::Clock;
+use super::config::CacheConfig;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::secondary::SecondaryStore;
+use super::store::CacheStore;
+
+// Creates the store for a cache once its configuration is known
+type MakeStore<S> = Box<dyn FnOnce(&CacheConfig) -> S + Send>;
+
+/// A builder for a ParameterCache, started with ParameterCache::builder.
+///
+/// Gathers every construction option and checks the configuration when the cache is built.
+/// ParameterCache::new remains a shortcut for a cache with the default configuration.
+pub struct ParameterCacheBuilder<S = LruCache<CacheKey, CacheItem<String>>> {
+    client: SSMClient,
+    config: CacheConfig,
+    account_clients: HashMap<String, SSMClient>,
+    make_store: MakeStore<S>,
+    secondary: Option<Box<dyn SecondaryStore>>,
+    endpoint_url: Option<String>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl ParameterCache {
+    /// Returns a builder for a ParameterCache using a client for AWS SSM.
+    pub fn builder(client: SSMClient) -> ParameterCacheBuilder {
+        ParameterCacheBuilder {
+            client,
+            config: CacheConfig::new(),
+            account_clients: HashMap::new(),
+            make_store: Box::new(new_lru),
+            secondary: None,
+            endpoint_url: None,
+            clock: None,
+        }
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCacheBuilder<S> {
+    /// Sets the Cache Configuration, replacing any options set on the builder before.
+    pub fn config(mut self, config: CacheConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the environment substituted for {env} in parameter names.
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.config.environment = Some(environment.into());
+        self
+    }
+
+    /// Registers a client for reading parameters from another account, as
+    /// ParameterCache::with_account_client does.
+    pub fn account_client(mut self, account: impl Into<String>, client: SSMClient) -> Self {
+        self.account_clients.insert(account.into(), client);
+        self
+    }
+
+    /// Backs the cache with a custom CacheStore, as ParameterCache::new_with_store does.
+    pub fn store<T: CacheStore<CacheKey, CacheItem<String>> + Send + 'static>(
+        self,
+        store: T,
+    ) -> ParameterCacheBuilder<T> {
+        ParameterCacheBuilder {
+            client: self.client,
+            config: self.config,
+            account_clients: self.account_clients,
+            make_store: Box::new(move |_| store),
+            secondary: self.secondary,
+            endpoint_url: self.endpoint_url,
+            clock: self.clock,
+        }
+    }
+
+    /// Adds a second-level cache below the in-process cache, as
+    /// ParameterCache::with_secondary_store does.
+    pub fn secondary_store(mut self, secondary: impl SecondaryStore + 'static) -> Self {
+        self.secondary = Some(Box::new(secondary));
+        self
+    }
+
+    /// Sends requests to AWS SSM through a custom endpoint, e.g. the interface VPC endpoint of
+    /// a service in a VPC without internet access.
+    ///
+    /// The builder's client is rebuilt from its own configuration with the endpoint URL
+    /// overridden. Clients registered with account_client are used as given, so set their
+    /// endpoints on the clients themselves.
+    pub fn endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(endpoint_url.into());
+        self
+    }
+
+    /// Reads the current time from a clock to decide when cached values expire, e.g. a fake
+    /// clock advanced by hand in tests. Defaults to SystemClock.
+    ///
+    /// Items stored in the cache, including ones created with CacheItem::new, are moved onto
+    /// the clock with their TTL left as it was. The error_cache_ttl and negative_ttl windows and
+    /// the circuit breaker cooldown are measured on the clock too.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Builds the ParameterCache.
+    ///
+    /// Returns a CacheError::InvalidConfig if the configuration can't work: a max_cache_size or
+    /// max_concurrent_requests of zero, a max_fetch_rate that isn't a positive number, a
+    /// capacity_warn_fraction outside 0 to 1, a max_inflight_groups of zero, or an empty
+    /// environment.
+    pub fn build(self) -> Result<ParameterCache<S>, CacheError> {
+        validate_config(&self.config)?;
+        let store = (self.make_store)(&self.config);
+        let client = match self.endpoint_url {
+            Some(endpoint_url) => {
+                let config = self.client.config().to_builder().endpoint_url(endpoint_url);
+                SSMClient::from_conf(config.build())
+            }
+            None => self.client,
+        };
+        let mut parameter_cache = ParameterCache::new_with_store(client, self.config, store);
+        parameter_cache.account_clients = self.account_clients;
+        parameter_cache.secondary = self.secondary.map(Arc::from);
+        if let Some(clock) = self.clock {
+            parameter_cache.clock = clock;
+        }
+        Ok(parameter_cache)
+    }
+}
+
+pub(crate) fn validate_config(config: &CacheConfig) -> Result<(), CacheError> {
+    let invalid = |message: &str| {
+        Err(CacheError::InvalidConfig {
+            message: message.to_string(),
+        })
+    };
+    if config.max_cache_size == 0 {
+        return invalid("max_cache_size must be at least 1");
+    }
+    if config.max_concurrent_requests == 0 {
+        return invalid("max_concurrent_requests must be at least 1");
+    }
+    if matches!(config.max_fetch_rate, Some(rate) if !(rate.is_finite() && rate > 0.0)) {
+        return invalid("max_fetch_rate must be a positive number of requests a second");
+    }
+    if matches!(config.capacity_warn_fraction, Some(fraction) if !(fraction > 0.0 && fraction <= 1.0))
+    {
+        return invalid("capacity_warn_fraction must be above 0 and at most 1");
+    }
+    if config.max_inflight_groups == Some(0) {
+        return invalid("max_inflight_groups must be at least 1");
+    }
+    if matches!(&config.environment, Some(environment) if environment.is_empty()) {
+        return invalid("environment must not be empty");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{client_with_http_client, mock_ssm_client, parameter_response};
+    use aws_smithy_runtime::client::http::test_util::infallible_client_fn;
+    use std::num::NonZeroUsize;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn builder_applies_options() {
+        let client = mock_ssm_client(|_, body| {
+            assert!(body.contains("/service/prod/db"), "{}", body);
+            (200, parameter_response("/service/prod/db", "value"))
+        });
+        let mut parameter_cache = ParameterCache::builder(client.clone())
+            .config(CacheConfig::new().max_cache_size(10))
+            .environment("prod")
+            .account_client("other", client)
+            .build()
+            .unwrap();
+
+        assert_eq!(parameter_cache.config().max_cache_size, 10);
+        assert!(parameter_cache.client_for(Some("other")).is_ok());
+        let value = parameter_cache.get_parameter("/service/{env}/db").send();
+        assert_eq!(value.await.unwrap(), "value");
+    }
+
+    #[tokio::test]
+    async fn builder_endpoint_url() {
+        let uris = Arc::new(Mutex::new(Vec::new()));
+        let requested = uris.clone();
+        let http_client = infallible_client_fn(move |request: http::Request<_>| {
+            requested.lock().unwrap().push(request.uri().to_string());
+            let response = parameter_response("service/parameter", "value");
+            http::Response::builder()
+                .status(200)
+                .body(response)
+                .unwrap()
+        });
+        let mut parameter_cache = ParameterCache::builder(client_with_http_client(http_client))
+            .endpoint_url("https://vpce-0123.ssm.ap-southeast-2.vpce.amazonaws.com")
+            .build()
+            .unwrap();
+
+        let value = parameter_cache.get_parameter("service/parameter").send();
+        assert_eq!(value.await.unwrap(), "value");
+        let uris = uris.lock().unwrap();
+        assert!(uris[0].starts_with("https://vpce-0123.ssm."), "{:?}", uris);
+    }
+
+    #[test]
+    fn builder_custom_store() {
+        let client = mock_ssm_client(|_, _| panic!("building never calls AWS"));
+        let store = LruCache::new(NonZeroUsize::new(3).unwrap());
+        let parameter_cache = ParameterCache::builder(client)
+            .config(CacheConfig::new().max_cache_size(10))
+            .store(store)
+            .build()
+            .unwrap();
+
+        assert_eq!(parameter_cache.cache.cap().get(), 3);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_config() {
+        let configs = [
+            CacheConfig::new().max_cache_size(0),
+            CacheConfig::new().max_concurrent_requests(0),
+            CacheConfig::new().max_fetch_rate(0.0),
+            CacheConfig::new().capacity_warn_fraction(1.5),
+            CacheConfig::new().environment(""),
+            CacheConfig::new().max_inflight_groups(0),
+        ];
+        for config in configs {
+            let client = mock_ssm_client(|_, _| panic!("building never calls AWS"));
+            let result = ParameterCache::builder(client).config(config).build();
+            assert!(matches!(result, Err(CacheError::InvalidConfig { .. })));
+        }
+    }
+}
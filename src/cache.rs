@@ -1,20 +1,73 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
 use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
 
+use super::batch::{GetParametersBuilder, GetParametersResult};
+use super::breaker::{BreakerState, CircuitBreaker};
 use super::cache_item::CacheItem;
+use super::clock::{system_clock, Clock};
 use super::config::CacheConfig;
-use aws_sdk_config::error::SdkError;
-use aws_sdk_ssm::operation::get_parameter::GetParameterError;
+use super::describe::ParameterDescription;
+use super::error::CacheError;
+use super::events::{CacheEvent, EventHook, EVENT_CHANNEL_CAPACITY};
+use super::eviction::EvictionReason;
+use super::history::GetParameterHistoryBuilder;
+use super::key::{normalize_name, validate_name, CacheKey};
+use super::parse;
+use super::path::GetParametersByPathBuilder;
+use super::rate::RateLimiter;
+use super::retry::RetryPolicy;
+use super::secondary::SecondaryStore;
+use super::stats::CacheStats;
+use super::store::CacheStore;
+use super::trace::{self, Instrument};
+use aws_sdk_ssm::operation::get_parameter::builders::GetParameterFluentBuilder;
+use aws_sdk_ssm::operation::get_parameter::GetParameterOutput;
+use aws_sdk_ssm::types::{
+    Parameter, ParameterMetadata, ParameterStringFilter, ParameterTier, ParameterType,
+};
 use aws_sdk_ssm::Client as SSMClient;
 use lru::LruCache;
+use tokio::sync::{broadcast, Semaphore};
+
+// The placeholder in parameter names replaced by CacheConfig::environment
+const ENV_PLACEHOLDER: &str = "{env}";
+const TENANT_PLACEHOLDER: &str = "{tenant}";
 
 /// Client for in-process caching of parameter values from AWS SSM.
 ///
 /// An LRU (least-recently used) caching scheme is used that provides
 /// O(1) insertions and O(1) lookups for cached values.
-pub struct ParameterCache {
-    client: SSMClient,
-    config: CacheConfig,
-    cache: LruCache<String, CacheItem<String>>,
+///
+/// The store holding cached values can be replaced with any CacheStore implementation
+/// using new_with_store.
+pub struct ParameterCache<S = LruCache<CacheKey, CacheItem<String>>> {
+    pub(crate) client: SSMClient,
+    pub(crate) account_clients: HashMap<String, SSMClient>,
+    pub(crate) config: CacheConfig,
+    pub(crate) cache: S,
+    pub(crate) batch_cache: LruCache<String, CacheItem<GetParametersResult>>,
+    pub(crate) descriptions: LruCache<String, CacheItem<ParameterDescription>>,
+    pub(crate) degraded: bool,
+    pub(crate) memory_bytes: usize,
+    pub(crate) request_limit: Arc<Semaphore>,
+    pub(crate) fetch_rate: Arc<RateLimiter>,
+    // locked, so hits served through a shared reference can be counted
+    pub(crate) stats: Mutex<CacheStats>,
+    pub(crate) stats_by_name: Mutex<HashMap<String, CacheStats>>,
+    pub(crate) aliases: HashMap<String, Vec<String>>,
+    pub(crate) events: broadcast::Sender<CacheEvent>,
+    pub(crate) event_hook: Option<EventHook>,
+    pub(crate) secondary: Option<Arc<dyn SecondaryStore>>,
+    pub(crate) jitter_source: JitterSource,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) recent_failures: HashMap<CacheKey, (SystemTime, String)>,
+    pub(crate) not_found: HashMap<CacheKey, (SystemTime, String)>,
+    pub(crate) breaker: CircuitBreaker,
 }
 
 impl ParameterCache {
@@ -28,126 +81,4551 @@ impl ParameterCache {
         ParameterCache::new_cache(client, config)
     }
 
+    /// Returns a new ParameterCache using a Cache Configuration read from an SSM parameter.
+    ///
+    /// The parameter value must be a JSON CacheConfig, for example `{"max_cache_size": 100}`;
+    /// fields that are left out take their default values. The parameter is read with a direct
+    /// GetParameter call and is not itself cached.
+    ///
+    /// If the parameter doesn't exist the default Cache Configuration is used. Any other failure
+    /// to read it is returned as an error, and a value that isn't a valid JSON CacheConfig is
+    /// returned as a CacheError::Parse.
+    #[cfg(feature = "serde")]
+    pub async fn new_with_config_from_ssm(
+        client: SSMClient,
+        config_parameter_name: &str,
+    ) -> Result<Self, CacheError> {
+        use aws_sdk_ssm::error::ProvideErrorMetadata;
+
+        let config = match client
+            .get_parameter()
+            .name(config_parameter_name)
+            .send()
+            .await
+        {
+            Ok(output) => {
+                let value = output
+                    .parameter
+                    .and_then(|parameter| parameter.value)
+                    .unwrap_or_default();
+                serde_json::from_str(&value).map_err(|e| CacheError::Parse {
+                    name: config_parameter_name.to_string(),
+                    message: format!("value is not a valid CacheConfig: {}", e),
+                })?
+            }
+            Err(e) if e.code() == Some("ParameterNotFound") => CacheConfig::new(),
+            Err(e) => return Err(CacheError::from_sdk_error(config_parameter_name, e)),
+        };
+        Ok(ParameterCache::new_cache(client, config))
+    }
+
     fn new_cache(client: SSMClient, config: CacheConfig) -> Self {
-        let cache = LruCache::new(
-            NonZeroUsize::new(config.max_cache_size)
-                .unwrap_or(NonZeroUsize::new(1).expect("Default max_cache_size must be non-zero")),
-        );
+        let cache = new_lru(&config);
+        ParameterCache::new_with_store(client, config, cache)
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Returns a new ParameterCache backed by a custom CacheStore.
+    ///
+    /// The store is responsible for its own capacity, so max_cache_size from the
+    /// Cache Configuration is not applied.
+    pub fn new_with_store(client: SSMClient, config: CacheConfig, store: S) -> Self {
+        warn_zero_capacity(&config);
+        let batch_cache = new_lru(&config);
+        let descriptions = new_lru(&config);
+        let request_limit = request_limit(&config);
+        let fetch_rate = Arc::new(RateLimiter::new(config.max_fetch_rate));
         Self {
             client,
+            account_clients: HashMap::new(),
             config,
-            cache,
+            cache: store,
+            batch_cache,
+            descriptions,
+            degraded: false,
+            memory_bytes: 0,
+            request_limit,
+            fetch_rate,
+            stats: Mutex::default(),
+            stats_by_name: Mutex::default(),
+            aliases: HashMap::new(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            event_hook: None,
+            secondary: None,
+            jitter_source: Arc::new(random_fraction),
+            clock: system_clock(),
+            recent_failures: HashMap::new(),
+            not_found: HashMap::new(),
+            breaker: CircuitBreaker::default(),
         }
     }
 
-    /// Returns a builder for getting parameter strings.
+    /// Sets the source of the random offsets applied to entry expiries with jitter, and to the
+    /// waits between retries of throttled requests, e.g. to make them deterministic in tests.
     ///
-    /// Retrieve the parameter value with send()
-    pub fn get_parameter<'a,'b>(&'a mut self, parameter_name: &'b str) -> GetParameterStringBuilder<'a,'b> {
-        GetParameterStringBuilder::new(self, parameter_name)
+    /// Each call returns a number from 0 to 1, spread evenly: 0 moves an entry's expiry jitter
+    /// earlier, 0.5 leaves it unchanged and 1 moves it jitter later. A retry waits half its
+    /// backoff for 0 and all of it for 1. Defaults to a source seeded randomly for each call.
+    pub fn with_jitter_source(mut self, source: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+        self.jitter_source = Arc::new(source);
+        self
     }
-}
 
-/// A builder for the get_parameter method.
-pub struct GetParameterStringBuilder<'a,'b> {
-    parameter_cache: &'a mut ParameterCache,
-    parameter_name: &'b str,
-    force_refresh: bool,
-}
+    /// Registers a client for reading parameters from another account.
+    ///
+    /// The client would typically be built with the credentials of a role assumed in that
+    /// account. Requests select it with the account builder option, and their values are
+    /// cached separately from those fetched with other clients. Registering a client under an
+    /// existing name replaces it, without affecting values already cached.
+    pub fn with_account_client(mut self, account: impl Into<String>, client: SSMClient) -> Self {
+        self.account_clients.insert(account.into(), client);
+        self
+    }
 
-impl<'a,'b> GetParameterStringBuilder<'a,'b> {
-    pub fn new(parameter_cache: &'a mut ParameterCache, parameter_name: &'b str) -> Self {
-        GetParameterStringBuilder {
-            parameter_cache,
+    /// Returns the client for an account, or the default client for None.
+    pub(crate) fn client_for(&self, account: Option<&str>) -> Result<&SSMClient, CacheError> {
+        match account {
+            None => Ok(&self.client),
+            Some(account) => {
+                self.account_clients
+                    .get(account)
+                    .ok_or_else(|| CacheError::UnknownAccount {
+                        account: account.to_string(),
+                    })
+            }
+        }
+    }
+
+    /// Returns the Cache Configuration the cache is running with.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Replaces the Cache Configuration of a running cache, keeping the cached entries.
+    ///
+    /// Entries keep the expiry they were stored with; a new cache_item_ttl applies to values
+    /// stored from now on. If max_cache_size shrinks, the least recently used entries are evicted
+    /// to fit (stores from new_with_store manage their own capacity), and a smaller
+    /// max_memory_bytes is enforced immediately.
+    pub fn set_config(&mut self, config: CacheConfig) {
+        if config.max_cache_size != self.config.max_cache_size {
+            warn_zero_capacity(&config);
+            for (key, evicted) in self.cache.resize(capacity(&config)) {
+                self.evicted(&key, &evicted, EvictionReason::Resized);
+            }
+            self.batch_cache.resize(capacity(&config));
+            self.descriptions.resize(capacity(&config));
+        }
+        if config.max_concurrent_requests != self.config.max_concurrent_requests {
+            self.request_limit = request_limit(&config);
+        }
+        if config.max_fetch_rate != self.config.max_fetch_rate {
+            self.fetch_rate = Arc::new(RateLimiter::new(config.max_fetch_rate));
+        }
+        if !config.stats_by_name {
+            locked(&self.stats_by_name).clear();
+        }
+        self.config = config;
+        self.enforce_memory_budget();
+    }
+
+    /// Returns true if the last fetch from AWS SSM was denied access.
+    ///
+    /// Access denied errors indicate a misconfigured IAM role, so they are returned
+    /// immediately as CacheError::AccessDenied and never retried. The cache stays degraded
+    /// until a later fetch succeeds.
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Returns the hit, miss and fetch counts for all parameters since the cache was created.
+    ///
+    /// With caching disabled every request is a miss and a fetch.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            breaker: self.breaker_state(),
+            ..*locked(&self.stats)
+        }
+    }
+
+    // How requests to AWS SSM are retried while they are throttled
+    pub(crate) fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.config.max_retries,
+            base_backoff: self.config.base_backoff,
+            jitter_source: self.jitter_source.clone(),
+            fetch_rate: self.fetch_rate.clone(),
+        }
+    }
+
+    pub(crate) fn breaker_state(&self) -> BreakerState {
+        self.breaker
+            .state(self.config.breaker_cooldown, self.clock.now())
+    }
+
+    // How long ago a time read from the cache's clock was
+    fn elapsed_since(&self, then: SystemTime) -> Duration {
+        self.clock.now().duration_since(then).unwrap_or_default()
+    }
+
+    /// Zeroes the stats, including the fetch latency histogram and the counts for each name.
+    pub fn reset_stats(&mut self) {
+        *locked(&self.stats) = CacheStats::default();
+        locked(&self.stats_by_name).clear();
+    }
+
+    /// Returns the hit, miss and fetch counts for each parameter name.
+    ///
+    /// Counts are only kept while stats_by_name is enabled in the CacheConfig; otherwise this
+    /// is empty. Names include any version or label selector, and GetParametersByPath fetches
+    /// are counted under the path.
+    pub fn stats_by_name(&self) -> HashMap<String, CacheStats> {
+        locked(&self.stats_by_name).clone()
+    }
+
+    /// Records whether a request for a parameter was answered from the cache.
+    pub(crate) fn record_lookup(&self, parameter_name: &str, hit: bool) {
+        self.emit(|| {
+            let name = parameter_name.to_string();
+            match hit {
+                true => CacheEvent::Hit { name },
+                false => CacheEvent::Miss { name },
+            }
+        });
+        locked(&self.stats).record_lookup(hit);
+        if self.config.stats_by_name {
+            locked(&self.stats_by_name)
+                .entry(parameter_name.to_string())
+                .or_default()
+                .record_lookup(hit);
+        }
+    }
+
+    /// Records that a stale value was served for a parameter.
+    pub(crate) fn record_stale_served(&self, parameter_name: &str) {
+        self.record_stat(parameter_name, CacheStats::record_stale_served);
+    }
+
+    /// Records a fetch of a parameter answered by its fallback_name.
+    pub(crate) fn record_fallback_used(&self, parameter_name: &str) {
+        self.record_stat(parameter_name, CacheStats::record_fallback_used);
+    }
+
+    /// Records a miss for a parameter whose cached value had expired.
+    pub(crate) fn record_expired(&self, parameter_name: &str) {
+        self.record_stat(parameter_name, CacheStats::record_expired);
+    }
+
+    /// Records a miss because a refresh of a parameter was forced.
+    pub(crate) fn record_forced_refresh(&self, parameter_name: &str) {
+        self.record_stat(parameter_name, CacheStats::record_forced_refresh);
+    }
+
+    // Records a count in the totals and, with stats_by_name, the counts for the name
+    fn record_stat(&self, parameter_name: &str, record: fn(&mut CacheStats)) {
+        record(&mut locked(&self.stats));
+        if self.config.stats_by_name {
+            record(
+                locked(&self.stats_by_name)
+                    .entry(parameter_name.to_string())
+                    .or_default(),
+            );
+        }
+    }
+
+    /// Records the outcome of a fetch from AWS SSM for the named parameters, started at started.
+    pub(crate) fn record_fetch<T>(
+        &mut self,
+        parameter_names: &[&str],
+        started: Instant,
+        result: &Result<T, CacheError>,
+    ) {
+        let mut stats = locked(&self.stats);
+        stats.record_fetch();
+        stats.fetch_latency.record(started.elapsed());
+        drop(stats);
+        if self.config.stats_by_name {
+            let mut stats_by_name = locked(&self.stats_by_name);
+            for &parameter_name in parameter_names {
+                stats_by_name
+                    .entry(parameter_name.to_string())
+                    .or_default()
+                    .record_fetch();
+            }
+        }
+        self.record_outcome(parameter_names, started, result);
+    }
+
+    /// Records the outcome of a synthetic fetch, counted apart from the other stats.
+    pub(crate) fn record_synthetic_fetch<T>(
+        &mut self,
+        parameter_name: &str,
+        started: Instant,
+        result: &Result<T, CacheError>,
+    ) {
+        locked(&self.stats).record_synthetic();
+        if self.config.stats_by_name {
+            locked(&self.stats_by_name)
+                .entry(parameter_name.to_string())
+                .or_default()
+                .record_synthetic();
+        }
+        self.record_outcome(&[parameter_name], started, result);
+    }
+
+    fn record_outcome<T>(
+        &mut self,
+        parameter_names: &[&str],
+        started: Instant,
+        result: &Result<T, CacheError>,
+    ) {
+        for &parameter_name in parameter_names {
+            self.emit(|| {
+                let name = parameter_name.to_string();
+                match result {
+                    Ok(_) => CacheEvent::Fetched {
+                        name,
+                        elapsed: started.elapsed(),
+                    },
+                    Err(e) => CacheEvent::Error {
+                        name,
+                        message: e.to_string(),
+                    },
+                }
+            });
+        }
+        let success = match result {
+            Ok(_) => true,
+            Err(e) => e.is_not_found(),
+        };
+        let now = self.clock.now();
+        self.breaker
+            .record(self.config.breaker_threshold, success, now);
+        match result {
+            Ok(_) => self.degraded = false,
+            Err(CacheError::AccessDenied { .. }) => self.degraded = true,
+            Err(_) => {}
+        }
+    }
+
+    /// Stores a value in the cache, keeping within the max_memory_bytes budget.
+    ///
+    /// The least recently used entries are evicted until the cached values fit the budget,
+    /// which can include the new entry itself if its value alone exceeds it.
+    /// Nothing is stored if caching is disabled in the Cache Configuration, or if the value is
+    /// larger than max_value_bytes.
+    pub(crate) fn insert(&mut self, key: CacheKey, cache_item: CacheItem<String>) {
+        self.insert_value(key, cache_item, false);
+    }
+
+    /// Stores a value in the cache as insert does, ignoring max_value_bytes if allow_large is set.
+    pub(crate) fn insert_value(
+        &mut self,
+        key: CacheKey,
+        mut cache_item: CacheItem<String>,
+        allow_large: bool,
+    ) {
+        if !self.config.caching_enabled {
+            return;
+        }
+        cache_item.set_clock(self.clock.clone());
+        if let Some(max_value_bytes) = self.config.max_value_bytes {
+            if cache_item.value.len() > max_value_bytes && !allow_large {
+                trace::debug!(
+                    parameter_name = %key.name,
+                    value_bytes = cache_item.value.len(),
+                    max_value_bytes,
+                    "parameter value too large to cache"
+                );
+                self.remove(&key);
+                return;
+            }
+        }
+        if !cache_item.is_expired() {
+            if let Some(expiry_grace) = self.config.expiry_grace {
+                cache_item.expiry_grace = expiry_grace.as_nanos();
+            }
+            if let Some(jitter) = self.config.jitter {
+                let fraction = (self.jitter_source)().clamp(0.0, 1.0) * 2.0 - 1.0;
+                cache_item.shift_expiry((jitter.as_nanos() as f64 * fraction) as i128);
+            }
+        }
+        if let Some(cached) = self.cache.peek(&key) {
+            cache_item.generation = cached.generation + u64::from(cached.value != cache_item.value);
+        }
+        self.not_found.remove(&key);
+        let was_near_capacity = self.is_near_capacity();
+        self.memory_bytes += cache_item.value.len();
+        match self.cache.put(key.clone(), cache_item) {
+            Some((displaced_key, displaced)) if displaced_key != key => {
+                self.evicted(&displaced_key, &displaced, EvictionReason::Capacity)
+            }
+            Some((_, replaced)) => self.memory_bytes -= replaced.value.len(),
+            None => {}
+        }
+        self.enforce_memory_budget();
+        if !was_near_capacity && self.is_near_capacity() {
+            trace::warn!(
+                entries = self.cache.len(),
+                max_cache_size = self.config.max_cache_size,
+                "parameter cache is nearly full"
+            );
+        }
+    }
+
+    /// Returns true if the cache holds at least capacity_warn_fraction of max_cache_size entries.
+    ///
+    /// Always false if capacity_warn_fraction isn't set in the CacheConfig.
+    pub fn is_near_capacity(&self) -> bool {
+        match self.config.capacity_warn_fraction {
+            Some(fraction) => {
+                self.cache.len() as f64 >= fraction * self.config.max_cache_size as f64
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of entries in the cache, including expired entries not yet evicted.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Returns the most entries the cache holds, as reported by its store.
+    ///
+    /// For the default LruCache this is the max_cache_size from the CacheConfig, or 1 for a
+    /// max_cache_size of 0. Returns None for stores from new_with_store that don't report a
+    /// fixed capacity.
+    pub fn capacity(&self) -> Option<usize> {
+        self.cache.capacity()
+    }
+
+    /// Returns the name of the parameter whose entry would be evicted next to make room, e.g. to
+    /// check that the LRU order matches the expected access pattern.
+    ///
+    /// This doesn't affect which entry is evicted next. Returns None if the cache is empty or
+    /// its store doesn't keep a usage order.
+    pub fn next_eviction_candidate(&self) -> Option<String> {
+        self.cache.peek_lru().map(|(key, _)| key.name.clone())
+    }
+
+    /// Removes an entry from the cache, returning it.
+    pub(crate) fn remove(&mut self, key: &CacheKey) -> Option<CacheItem<String>> {
+        let cache_item = self.cache.pop(key)?;
+        self.memory_bytes -= cache_item.value.len();
+        Some(cache_item)
+    }
+
+    // Accounts for an entry that has been removed from the store
+    fn evicted(&mut self, key: &CacheKey, cache_item: &CacheItem<String>, reason: EvictionReason) {
+        self.memory_bytes -= cache_item.value.len();
+        if reason != EvictionReason::Invalidated {
+            self.record_stat(&key.name, CacheStats::record_eviction);
+        }
+        self.emit(|| CacheEvent::Evicted {
+            name: key.name.clone(),
+            reason,
+        });
+        trace::debug!(
+            parameter_name = %key.name,
+            with_decryption = key.with_decryption,
+            %reason,
+            "evicted parameter from cache"
+        );
+    }
+
+    /// Remembers the outcome of a fetch for error_cache_ttl.
+    ///
+    /// A failure is kept for later reads to return, a success forgets any earlier failure. Not
+    /// found isn't a failure here, so that optional and fallback_name still see it; negative_ttl
+    /// remembers it instead.
+    pub(crate) fn record_failure<T>(&mut self, key: &CacheKey, result: &Result<T, CacheError>) {
+        let Some(error_cache_ttl) = self.config.error_cache_ttl else {
+            return;
+        };
+        match result {
+            Ok(_) => {
+                self.recent_failures.remove(key);
+            }
+            Err(e) if e.is_not_found() => {
+                self.recent_failures.remove(key);
+            }
+            Err(e) => {
+                let now = self.clock.now();
+                self.recent_failures.retain(|_, (failed_at, _)| {
+                    now.duration_since(*failed_at).unwrap_or_default() < error_cache_ttl
+                });
+                self.recent_failures
+                    .insert(key.clone(), (now, e.to_string()));
+            }
+        }
+    }
+
+    /// Returns a CacheError::RecentFailure if a fetch of the key failed within error_cache_ttl.
+    pub(crate) fn recent_failure(&self, key: &CacheKey) -> Option<CacheError> {
+        let error_cache_ttl = self.config.error_cache_ttl?;
+        self.recent_failures
+            .get(key)
+            .filter(|(failed_at, _)| self.elapsed_since(*failed_at) < error_cache_ttl)
+            .map(|(_, message)| CacheError::RecentFailure {
+                name: key.name.clone(),
+                message: message.clone(),
+            })
+    }
+
+    /// Remembers for negative_ttl that a fetch found no parameter; a success forgets it.
+    pub(crate) fn record_not_found<T>(&mut self, key: &CacheKey, result: &Result<T, CacheError>) {
+        let Some(negative_ttl) = self.config.negative_ttl else {
+            return;
+        };
+        match result {
+            Ok(_) => {
+                self.not_found.remove(key);
+            }
+            Err(e) if e.is_not_found() => {
+                let now = self.clock.now();
+                self.not_found.retain(|_, (found_at, _)| {
+                    now.duration_since(*found_at).unwrap_or_default() < negative_ttl
+                });
+                self.not_found.insert(key.clone(), (now, e.to_string()));
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Returns a CacheError::NotFound if a fetch of the key found no parameter within
+    /// negative_ttl.
+    pub(crate) fn cached_not_found(&self, key: &CacheKey) -> Option<CacheError> {
+        let negative_ttl = self.config.negative_ttl?;
+        self.not_found
+            .get(key)
+            .filter(|(found_at, _)| self.elapsed_since(*found_at) < negative_ttl)
+            .map(|(_, message)| CacheError::not_found(&key.name, message.clone()))
+    }
+
+    /// Returns the error a fetch of the key fails with before calling AWS: a failure remembered
+    /// for error_cache_ttl or a not found remembered for negative_ttl, unless the fetch is
+    /// forced, or CacheError::CircuitOpen while the circuit breaker is open.
+    pub(crate) fn fetch_refused(&self, key: &CacheKey, force_refresh: bool) -> Option<CacheError> {
+        if !force_refresh {
+            if let Some(e) = self.recent_failure(key) {
+                return Some(e);
+            }
+            if let Some(e) = self.cached_not_found(key) {
+                return Some(e);
+            }
+        }
+        (self.breaker_state() == BreakerState::Open).then(|| CacheError::CircuitOpen {
+            name: key.name.clone(),
+        })
+    }
+
+    /// Records the outcome of a GetParameter call for the key, started at started: in the stats,
+    /// events and circuit breaker, and for error_cache_ttl and negative_ttl.
+    pub(crate) fn record_get_parameter<T>(
+        &mut self,
+        key: &CacheKey,
+        started: Instant,
+        result: &Result<T, CacheError>,
+        synthetic: bool,
+    ) {
+        match synthetic {
+            true => self.record_synthetic_fetch(&key.name, started, result),
+            false => self.record_fetch(&[&key.name], started, result),
+        }
+        self.record_failure(key, result);
+        self.record_not_found(key, result);
+    }
+
+    /// Returns the answer to a GetParameter call in dry run mode, made without calling AWS: the
+    /// dry_run_placeholder, or a CacheError::DryRun if there is none.
+    pub(crate) fn dry_run_output(
+        &self,
+        parameter_name: &str,
+        with_decryption: bool,
+        account: Option<&str>,
+    ) -> Result<GetParameterOutput, CacheError> {
+        trace::info!(
             parameter_name,
-            force_refresh: false,
+            with_decryption,
+            account,
+            "dry run: would fetch parameter"
+        );
+        let placeholder =
+            self.config
+                .dry_run_placeholder
+                .clone()
+                .ok_or_else(|| CacheError::DryRun {
+                    name: parameter_name.to_string(),
+                })?;
+        let parameter = Parameter::builder()
+            .name(parameter_name)
+            .value(placeholder)
+            .build();
+        Ok(GetParameterOutput::builder().parameter(parameter).build())
+    }
+
+    /// Returns the CacheError::DryRun a call to AWS is refused with in dry run mode, logging the
+    /// call that would have been made, or None if the cache isn't in dry run mode.
+    pub(crate) fn dry_run_refusal(&self, name: &str, operation: &str) -> Option<CacheError> {
+        if !self.config.dry_run {
+            return None;
         }
+        trace::info!(name, operation, "dry run: would call AWS");
+        Some(CacheError::DryRun {
+            name: name.to_string(),
+        })
     }
 
-    /// Forces a refresh of the parameter.
+    /// Takes the value out of a GetParameter response, returning it with the rest of the
+    /// parameter.
     ///
-    /// Forces the parameter to be fetched from AWS and updates the cache with the fresh value.
-    /// This is required when the cached parameter is out of date but not expired, for example due to rotation.
-    pub fn force_refresh(mut self) -> Self {
-        self.force_refresh = true;
-        self
+    /// Returns a CacheError::MissingValue if there is no value, and a CacheError::NotFound for an
+    /// empty value when empty_as_not_found is set.
+    pub(crate) fn take_value(
+        &self,
+        parameter_name: &str,
+        output: GetParameterOutput,
+    ) -> Result<(String, Parameter), CacheError> {
+        let mut parameter = output
+            .parameter
+            .unwrap_or_else(|| Parameter::builder().build());
+        let parameter_value = parameter
+            .value
+            .take()
+            .ok_or_else(|| CacheError::MissingValue {
+                name: parameter_name.to_string(),
+            })?;
+        if parameter_value.is_empty() && self.config.empty_as_not_found {
+            return Err(CacheError::not_found(
+                parameter_name,
+                "Parameter value is empty",
+            ));
+        }
+        Ok((parameter_value, parameter))
     }
 
-    /// Fetches the parameter value from the cache.
+    /// Counts a failed attempt to refresh an expired entry.
     ///
-    /// If the parameter value exists in the cache and hasn't expired it will be immediately returned.
-    /// The parameter will be fetched by calling AWS SSM and updated in the cache if:
-    /// - the parameter value hasn't been stored in the cache
-    /// - the parameter stored in the cache but has expired
-    /// - the force_refresh option was provided
-    ///
-    /// Values are stored in the cache with the cache_item_ttl from the CacheConfig.
-    pub async fn send(&mut self) -> Result<String, SdkError<GetParameterError>> {
-        if !self.force_refresh {
-            if let Some(cache_item) = self.parameter_cache.cache.get(self.parameter_name) {
-                if !cache_item.is_expired() {
-                    return Ok(cache_item.value.clone());
+    /// Once evict_after_failed_refreshes consecutive attempts have failed, the entry is
+    /// evicted so later reads are cold misses. Unexpired entries are left alone.
+    pub(crate) fn record_failed_refresh(&mut self, key: &CacheKey) {
+        let Some(max_failed_refreshes) = self.config.evict_after_failed_refreshes else {
+            return;
+        };
+        let Some(mut cache_item) = self.cache.pop(key) else {
+            return;
+        };
+        if !cache_item.is_expired() {
+            self.cache.put(key.clone(), cache_item);
+            return;
+        }
+        cache_item.failed_refreshes += 1;
+        match cache_item.failed_refreshes >= max_failed_refreshes {
+            true => self.evicted(key, &cache_item, EvictionReason::FailedRefreshes),
+            false => {
+                self.cache.put(key.clone(), cache_item);
+            }
+        }
+    }
+
+    fn enforce_memory_budget(&mut self) {
+        if let Some(max_memory_bytes) = self.config.max_memory_bytes {
+            while self.memory_bytes > max_memory_bytes {
+                match self.cache.pop_lru() {
+                    Some((key, evicted)) => {
+                        self.evicted(&key, &evicted, EvictionReason::MemoryBudget)
+                    }
+                    None => break,
                 }
             }
         }
+    }
 
-        match self.fetch_parameter().await {
-            Ok(parameter_value) => {
-                let cache_item = CacheItem::new(
-                    parameter_value.clone(),
-                    self.parameter_cache.config.cache_item_ttl,
-                );
-                self.parameter_cache
-                    .cache
-                    .put(self.parameter_name.to_string(), cache_item);
-                Ok(parameter_value)
+    /// Returns a builder for getting parameter strings.
+    ///
+    /// Retrieve the parameter value with send()
+    pub fn get_parameter<'a, 'b>(
+        &'a mut self,
+        parameter_name: &'b str,
+    ) -> GetParameterStringBuilder<'a, 'b, S> {
+        GetParameterStringBuilder::new(self, parameter_name)
+    }
+
+    /// Returns a builder for getting a parameter string, refreshing the cached value first if
+    /// it was fetched more than max_age ago.
+    ///
+    /// Shorthand for get_parameter(name).max_age(max_age); retrieve the value with send().
+    pub fn get_fresher_than<'a, 'b>(
+        &'a mut self,
+        parameter_name: &'b str,
+        max_age: Duration,
+    ) -> GetParameterStringBuilder<'a, 'b, S> {
+        self.get_parameter(parameter_name).max_age(max_age)
+    }
+
+    /// Returns a builder for getting several parameter strings at once.
+    ///
+    /// Retrieve the parameter values with send()
+    pub fn get_parameters<'a, 'b>(
+        &'a mut self,
+        parameter_names: &'b [&'b str],
+    ) -> GetParametersBuilder<'a, 'b, S> {
+        GetParametersBuilder::new(self, parameter_names)
+    }
+
+    /// Returns a builder for getting all of the parameters under a path.
+    ///
+    /// Retrieve the parameter values with send()
+    pub fn get_parameters_by_path<'a, 'b>(
+        &'a mut self,
+        path: &'b str,
+    ) -> GetParametersByPathBuilder<'a, 'b, S> {
+        GetParametersByPathBuilder::new(self, path)
+    }
+
+    /// Returns a builder for getting the version history of a parameter.
+    ///
+    /// Retrieve the history with send(). History is never cached.
+    pub fn get_parameter_history<'a, 'b>(
+        &'a self,
+        parameter_name: &'b str,
+    ) -> GetParameterHistoryBuilder<'a, 'b, S> {
+        GetParameterHistoryBuilder::new(self, parameter_name)
+    }
+
+    /// Returns the tier of a cached parameter, without calling AWS.
+    ///
+    /// The tier is only known for parameters fetched with the with_tier option. Returns None
+    /// if the parameter isn't cached, has expired, or its tier wasn't recorded.
+    pub fn get_parameter_tier(&self, parameter_name: &str) -> Option<ParameterTier> {
+        self.peek_unexpired(parameter_name)
+            .and_then(|cache_item| cache_item.tier.clone())
+    }
+
+    /// Returns the ID of the KMS key encrypting a cached SecureString parameter, without calling AWS.
+    ///
+    /// The key ID is only known for parameters fetched with the with_key_id option. Returns None
+    /// if the parameter isn't cached, has expired, isn't a SecureString, or its key wasn't recorded.
+    pub fn get_parameter_key_id(&self, parameter_name: &str) -> Option<String> {
+        self.peek_unexpired(parameter_name)
+            .and_then(|cache_item| cache_item.key_id.clone())
+    }
+
+    /// Returns the type of a cached parameter, without calling AWS, e.g. to check at load time
+    /// that a secret is still a SecureString.
+    ///
+    /// Returns None if the parameter isn't cached, has expired, or AWS SSM didn't report its type.
+    pub fn get_parameter_type(&self, parameter_name: &str) -> Option<ParameterType> {
+        self.peek_unexpired(parameter_name)
+            .and_then(|cache_item| cache_item.parameter_type.clone())
+    }
+
+    /// Returns how long ago a cached parameter's value was fetched, without calling AWS.
+    ///
+    /// Distinguishes a value that was just refreshed from one that is about to expire. Returns
+    /// None if the parameter isn't cached or has expired.
+    pub fn get_parameter_age(&self, parameter_name: &str) -> Option<Duration> {
+        self.peek_unexpired(parameter_name).map(CacheItem::age)
+    }
+
+    /// Returns the cached value of a parameter, without calling AWS.
+    ///
+    /// This doesn't affect which entry is evicted next, e.g. for a debug endpoint. Returns None
+    /// if the parameter isn't cached or has expired.
+    pub fn peek(&self, parameter_name: &str) -> Option<String> {
+        self.peek_unexpired(parameter_name)
+            .map(|cache_item| cache_item.value.clone())
+    }
+
+    /// Returns true if the cache holds an unexpired value of a parameter, without calling AWS.
+    ///
+    /// This doesn't affect which entry is evicted next.
+    pub fn contains(&self, parameter_name: &str) -> bool {
+        self.peek_unexpired(parameter_name).is_some()
+    }
+
+    /// Evicts every cached value of a parameter, so the next request fetches it from AWS.
+    ///
+    /// Removes the parameter's values for both decryption settings, all accounts, and any
+    /// version or label selectors (e.g. "name:3"), as well as cache_batch entries including it.
+    /// Values cached under aliases of the parameter with also_cache_as are removed too.
+    /// Returns true if anything was evicted.
+    pub fn invalidate(&mut self, parameter_name: &str) -> bool {
+        let parameter_name = &*self.normalized_name(parameter_name);
+        let names: Vec<String> = self
+            .aliases
+            .keys()
+            .filter(|name| is_same_parameter(name, parameter_name))
+            .cloned()
+            .collect();
+        let mut evicted = self.invalidate_name(parameter_name);
+        for name in names {
+            for alias in self.aliases.remove(&name).unwrap_or_default() {
+                evicted |= self.invalidate_name(&alias);
             }
-            Err(e) => Err(e),
         }
+        evicted
     }
 
-    async fn fetch_parameter(&mut self) -> Result<String, SdkError<GetParameterError>> {
-        match self
-            .parameter_cache
-            .client
-            .get_parameter()
-            .name(self.parameter_name)
-            .send()
-            .await
-        {
-            Ok(resp) => return Ok(resp.parameter.unwrap().value.unwrap()),
-            Err(e) => Err(e),
+    /// Returns the name a parameter is fetched and cached under: with {env} replaced by the
+    /// configured environment, and normalized if normalize_names is set.
+    pub(crate) fn normalized_name<'n>(&self, parameter_name: &'n str) -> Cow<'n, str> {
+        let parameter_name = match &self.config.environment {
+            Some(environment) if parameter_name.contains(ENV_PLACEHOLDER) => {
+                Cow::Owned(parameter_name.replace(ENV_PLACEHOLDER, environment))
+            }
+            _ => Cow::Borrowed(parameter_name),
+        };
+        match (self.config.normalize_names, parameter_name) {
+            (true, Cow::Borrowed(parameter_name)) => normalize_name(parameter_name),
+            (true, Cow::Owned(parameter_name)) => {
+                Cow::Owned(normalize_name(&parameter_name).into_owned())
+            }
+            (false, parameter_name) => parameter_name,
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use aws_sdk_config::config::{Credentials, Region};
-    use aws_sdk_ssm::{Client as SSMClient, Config};
+    /// Records that a parameter's value is also cached under an alias.
+    pub(crate) fn add_alias(&mut self, parameter_name: &str, alias: &str) {
+        let aliases = self.aliases.entry(parameter_name.to_string()).or_default();
+        if !aliases.iter().any(|existing| existing == alias) {
+            aliases.push(alias.to_string());
+        }
+    }
 
-    #[test]
-    fn get_parameter_builder_defaults() {
-        let mock_ssm_client = get_mock_ssm_client();
-        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+    fn invalidate_name(&mut self, parameter_name: &str) -> bool {
+        let keys: Vec<CacheKey> = self
+            .cache
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| is_same_parameter(&key.name, parameter_name))
+            .cloned()
+            .collect();
+        for key in &keys {
+            if let Some(cache_item) = self.cache.pop(key) {
+                self.evicted(key, &cache_item, EvictionReason::Invalidated);
+            }
+        }
 
-        let builder = GetParameterStringBuilder::new(&mut parameter_cache, "service/parameter");
+        let batch_keys: Vec<String> = self
+            .batch_cache
+            .iter()
+            .map(|(batch_key, _)| batch_key)
+            .filter(|batch_key| {
+                batch_key
+                    .split('\n')
+                    .any(|name| is_same_parameter(name, parameter_name))
+            })
+            .cloned()
+            .collect();
+        for batch_key in &batch_keys {
+            self.batch_cache.pop(batch_key);
+        }
+        !keys.is_empty() || !batch_keys.is_empty()
+    }
 
-        assert_eq!(builder.parameter_name, "service/parameter");
-        assert!(!builder.force_refresh);
+    /// Evicts the cached values of the parameter identified by an ARN, as with invalidate.
+    ///
+    /// Accepts ARNs of standard and hierarchical parameters, for example
+    /// "arn:aws:ssm:us-east-1:123456789012:parameter/myapp/db/host", such as those carried by
+    /// EventBridge parameter change events. Returns false if the ARN isn't a parameter ARN or
+    /// nothing was evicted.
+    pub fn invalidate_by_arn(&mut self, arn: &str) -> bool {
+        match parameter_path_from_arn(arn) {
+            // The ARN drops the leading "/" of hierarchical names, so "parameter/a" may name
+            // either "a" or "/a"
+            Some(path) => {
+                let standard = self.invalidate(path);
+                let hierarchical = self.invalidate(&format!("/{}", path));
+                standard || hierarchical
+            }
+            None => false,
+        }
     }
 
-    #[test]
-    fn get_parameter_builder_force_refresh() {
-        let mock_ssm_client = get_mock_ssm_client();
-        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+    /// Keeps only the cached entries for which the predicate, given the parameter name and
+    /// value, returns true, like HashMap::retain; the others are invalidated.
+    ///
+    /// Generalizes invalidate, e.g. to drop every entry whose name matches a pattern. Returns
+    /// the number of entries evicted. This doesn't affect which of the kept entries is evicted
+    /// next.
+    pub fn retain(&mut self, keep: impl Fn(&str, &str) -> bool) -> usize {
+        let keys: Vec<CacheKey> = self
+            .cache
+            .iter()
+            .filter(|(key, cache_item)| !keep(&key.name, &cache_item.value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            if let Some(cache_item) = self.cache.pop(key) {
+                self.evicted(key, &cache_item, EvictionReason::Invalidated);
+            }
+        }
+        keys.len()
+    }
 
-        let builder = GetParameterStringBuilder::new(&mut parameter_cache, "service/parameter")
-            .force_refresh();
+    /// Empties the cache without calling AWS, e.g. for an admin endpoint that busts the cache.
+    ///
+    /// Evicts every cached value as invalidate does, along with cached get_parameters results,
+    /// descriptions and not found markers. Returns the number of values evicted.
+    pub fn clear(&mut self) -> usize {
+        let evicted = self.retain(|_, _| false);
+        self.batch_cache.clear();
+        self.descriptions.clear();
+        self.not_found.clear();
+        self.aliases.clear();
+        evicted
+    }
 
-        assert_eq!(builder.parameter_name, "service/parameter");
-        assert!(builder.force_refresh);
+    /// Extends the expiry of a cached parameter to new_ttl from now without fetching it, e.g.
+    /// when its value has been confirmed current by other means.
+    ///
+    /// Applies to the entries cached with either decryption setting, expired or not, but not to
+    /// version or label selectors. Returns false if the parameter isn't cached.
+    pub fn touch(&mut self, parameter_name: &str, new_ttl: Duration) -> bool {
+        let parameter_name = self.normalized_name(parameter_name).into_owned();
+        let mut touched = false;
+        for with_decryption in [false, true] {
+            let key = CacheKey::new(&*parameter_name, with_decryption);
+            if let Some(mut cache_item) = self.cache.pop(&key) {
+                cache_item.extend(new_ttl.as_nanos());
+                self.cache.put(key, cache_item);
+                touched = true;
+            }
+        }
+        touched
+    }
+
+    /// Returns true if a cached parameter is within the refresh_ahead window of expiring.
+    ///
+    /// A stale value is still served from the cache, but is due for a proactive refresh.
+    /// Returns false if the parameter isn't cached, has already expired, or no refresh_ahead
+    /// window is configured. This doesn't affect which entry is evicted next.
+    pub fn is_stale(&self, parameter_name: &str) -> bool {
+        match (
+            self.config.refresh_ahead,
+            self.peek_unexpired(parameter_name),
+        ) {
+            (Some(refresh_ahead), Some(cache_item)) => {
+                cache_item.expires_within(refresh_ahead.as_nanos())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the names of cached parameters whose values have expired.
+    ///
+    /// Expired entries stay in the cache until they are refreshed or evicted; this only reports
+    /// them and doesn't affect which entry is evicted next. A name is listed once even if both
+    /// its decrypted and encrypted values have expired.
+    pub fn expired_keys(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .cache
+            .iter()
+            .filter(|(_, cache_item)| cache_item.is_expired())
+            .map(|(key, _)| key.name.clone())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Returns an approximate number of bytes used by the cached parameters.
+    ///
+    /// Sums the lengths of the cached names and values plus a fixed overhead per entry for the
+    /// key and item structures. Allocator overhead, metadata strings and batch entries from
+    /// cache_batch aren't counted. This iterates over the cache without affecting which entry
+    /// is evicted next.
+    pub fn memory_estimate(&self) -> usize {
+        self.cache
+            .iter()
+            .map(|(key, cache_item)| {
+                ENTRY_OVERHEAD_BYTES
+                    + key.name.len()
+                    + key.account.as_ref().map_or(0, String::len)
+                    + key.tenant.as_ref().map_or(0, String::len)
+                    + cache_item.value.len()
+            })
+            .sum()
+    }
+
+    /// Answers a request for a parameter from the cache through a shared reference, as
+    /// get_parameter().send() answers a hit, so that SharedParameterCache can serve hits under
+    /// a read lock.
+    ///
+    /// Returns None, leaving the request to be made as usual, on a miss, or when hits must
+    /// update recency because update_recency_on_hit is set.
+    pub(crate) fn shared_hit(&self, parameter_name: &str) -> Option<String> {
+        if self.config.update_recency_on_hit || !self.config.caching_enabled {
+            return None;
+        }
+        let parameter_name = self.normalized_name(parameter_name);
+        let key = CacheKey::new(&*parameter_name, self.config.default_with_decryption);
+        let cache_item = self
+            .cache
+            .peek(&key)
+            .filter(|cache_item| !cache_item.is_expired())?;
+        self.record_lookup(&key.name, true);
+        if let Some(expires_in) = near_expiry(cache_item, &self.config) {
+            self.emit(|| CacheEvent::NearExpiry {
+                name: key.name.clone(),
+                expires_in,
+            });
+        }
+        Some(cache_item.value.clone())
+    }
+
+    // The unexpired cached item for a parameter name, normalized as requests normalize it,
+    // preferring the entry fetched with the default decryption setting over the entry fetched
+    // with the other setting
+    pub(crate) fn peek_unexpired(&self, parameter_name: &str) -> Option<&CacheItem<String>> {
+        let parameter_name = self.normalized_name(parameter_name);
+        let parameter_name = &*parameter_name;
+        let default_with_decryption = self.config.default_with_decryption;
+        [default_with_decryption, !default_with_decryption]
+            .into_iter()
+            .filter_map(|with_decryption| {
+                self.cache
+                    .peek(&CacheKey::new(parameter_name, with_decryption))
+            })
+            .find(|cache_item| !cache_item.is_expired())
+    }
+
+    /// Gets a parameter value and parses it as a bool.
+    ///
+    /// Matching is case-insensitive and ignores surrounding whitespace. Accepted values:
+    /// - true: "true", "t", "yes", "y", "on", "1"
+    /// - false: "false", "f", "no", "n", "off", "0"
+    ///
+    /// Any other value results in a CacheError::Parse. The raw string value is what gets cached.
+    pub async fn get_bool(&mut self, parameter_name: &str) -> Result<bool, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        parse::parse_bool(parameter_name, &value)
+    }
+
+    /// Gets a parameter value and splits it into lines, e.g. for a multi-line allowlist.
+    ///
+    /// Lines are split on "\n" with any trailing "\r" removed. Empty lines, including one
+    /// after a trailing newline, are left out if skip_empty is set. The raw string value is
+    /// what gets cached.
+    pub async fn get_lines(
+        &mut self,
+        parameter_name: &str,
+        skip_empty: bool,
+    ) -> Result<Vec<String>, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        Ok(parse::parse_lines(&value, skip_empty))
+    }
+
+    /// Gets a StringList parameter value as its elements, in order.
+    ///
+    /// Elements are split on "," and kept as they are; an empty value has no elements. Returns a
+    /// CacheError::WrongType if the parameter is known to be of another type, such as a String
+    /// holding commas; values whose type isn't known, e.g. inserted into the cache directly,
+    /// are split too. The raw string value is what gets cached.
+    pub async fn get_string_list(
+        &mut self,
+        parameter_name: &str,
+    ) -> Result<Vec<String>, CacheError> {
+        let cached_parameter = self
+            .get_parameter(parameter_name)
+            .send_with_metadata()
+            .await?;
+        match cached_parameter.parameter_type {
+            Some(actual) if actual != ParameterType::StringList => Err(CacheError::WrongType {
+                name: parameter_name.to_string(),
+                expected: ParameterType::StringList,
+                actual,
+            }),
+            _ => Ok(parse::parse_string_list(&cached_parameter.value)),
+        }
+    }
+
+    /// Gets a StringList parameter value as a set of its elements, e.g. for an allowlist.
+    ///
+    /// Elements are split on "," and trimmed, so duplicates and surrounding whitespace are
+    /// dropped, as are empty elements. The raw string value is what gets cached.
+    pub async fn get_string_set(
+        &mut self,
+        parameter_name: &str,
+    ) -> Result<HashSet<String>, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        Ok(parse::parse_string_set(&value))
+    }
+
+    /// Gets a parameter value holding a duration, such as a timeout, as a Duration.
+    ///
+    /// The value is one or more whole numbers each followed by a unit, e.g. "30s", "5m" or
+    /// "1h30m". Units are "ns", "us", "ms", "s", "m", "h" and "d". Any other value results in a
+    /// CacheError::Parse. The raw string value is what gets cached.
+    pub async fn get_duration(&mut self, parameter_name: &str) -> Result<Duration, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        parse::parse_duration(parameter_name, &value)
+    }
+
+    /// Gets a dotenv-style parameter value of KEY=VALUE lines as a map.
+    ///
+    /// Keys and values are trimmed and blank lines and "#" comments are skipped; any other line
+    /// that is not KEY=VALUE results in a CacheError::Parse. The raw string value is what gets
+    /// cached.
+    pub async fn get_env_map(
+        &mut self,
+        parameter_name: &str,
+    ) -> Result<HashMap<String, String>, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        parse::parse_env_map(parameter_name, &value)
+    }
+
+    /// Gets a parameter value and converts it with a parser of the caller's choosing, e.g. into
+    /// a connection settings struct.
+    ///
+    /// A parser error results in a CacheError::Parse with the error's message. The raw string
+    /// value is what gets cached, so the parser runs on every call, including cache hits.
+    pub async fn get_typed<T, E: fmt::Display>(
+        &mut self,
+        parameter_name: &str,
+        parser: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<T, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        parser(&value).map_err(|e| CacheError::Parse {
+            name: parameter_name.to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+// The fixed per-entry overhead counted by memory_estimate
+const ENTRY_OVERHEAD_BYTES: usize = std::mem::size_of::<(CacheKey, CacheItem<String>)>();
+
+// The source of the fractions from 0 to 1 that place each entry's expiry within the jitter, and
+// each retry's wait within its backoff
+pub(crate) type JitterSource = Arc<dyn Fn() -> f64 + Send + Sync>;
+
+// A closure checking a fetched value before it is cached
+type ValidateValue<'b> = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync + 'b>;
+
+// A closure deriving the value returned by a single request from the cached value
+type MapValue<'b> = Box<dyn Fn(String) -> String + Send + Sync + 'b>;
+
+// A closure adjusting the GetParameter request of a single fetch
+type CustomizeRequest<'b> =
+    Box<dyn Fn(GetParameterFluentBuilder) -> GetParameterFluentBuilder + Send + Sync + 'b>;
+
+// Locks stats, which stay usable if a panic poisoned the lock
+fn locked<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// The semaphore bounding concurrent requests for a Cache Configuration
+fn request_limit(config: &CacheConfig) -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(config.max_concurrent_requests.max(1)))
+}
+
+// A number from 0 to 1 for the default jitter source, from std's randomly seeded hasher, which
+// is keyed afresh for each call
+fn random_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u32(
+        SystemTime::now()
+            .elapsed()
+            .unwrap_or_default()
+            .subsec_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// The cached item for a key on a cache hit, marking it as recently used if update_recency_on_hit
+// is set
+pub(crate) fn lookup<'c, K, V, S: CacheStore<K, V>>(
+    cache: &'c mut S,
+    update_recency_on_hit: bool,
+    key: &K,
+) -> Option<&'c V> {
+    match update_recency_on_hit {
+        true => cache.get(key),
+        false => cache.peek(key),
+    }
+}
+
+// How long until a cached item expires, if that is within the near_expiry_window
+fn near_expiry(cache_item: &CacheItem<String>, config: &CacheConfig) -> Option<Duration> {
+    let window = config.near_expiry_window?;
+    cache_item
+        .expires_within(window.as_nanos())
+        .then(|| cache_item.expires_in())
+}
+
+// Whether a cached name (which may carry a version or label selector) is the named parameter
+fn is_same_parameter(cached_name: &str, parameter_name: &str) -> bool {
+    match cached_name.strip_prefix(parameter_name) {
+        Some(selector) => selector.is_empty() || selector.starts_with(':'),
+        None => false,
+    }
+}
+
+// The version a parameter name pins with a numeric selector, e.g. 3 for "name:3"
+fn pinned_version(parameter_name: &str) -> Option<i64> {
+    let (_, selector) = parameter_name.rsplit_once(':')?;
+    if selector.contains('/') {
+        return None;
+    }
+    selector.parse().ok()
+}
+
+// The part of a parameter ARN after "parameter/"
+fn parameter_path_from_arn(arn: &str) -> Option<&str> {
+    let mut parts = arn.splitn(6, ':');
+    if parts.next() != Some("arn") {
+        return None;
+    }
+    let service = parts.nth(1)?;
+    let resource = parts.nth(2)?;
+    match service {
+        "ssm" => resource
+            .strip_prefix("parameter/")
+            .filter(|path| !path.is_empty()),
+        _ => None,
+    }
+}
+
+// Whether a cached item is known to be at least the minimum version, if there is one
+fn meets_min_version(cache_item: &CacheItem<String>, min_version: Option<i64>) -> bool {
+    match min_version {
+        Some(min_version) => cache_item
+            .version
+            .is_some_and(|version| version >= min_version),
+        None => true,
+    }
+}
+
+// Whether a cached item was fetched no longer ago than the maximum age, if there is one
+fn within_max_age(cache_item: &CacheItem<String>, max_age: Option<Duration>) -> bool {
+    max_age.is_none_or(|max_age| cache_item.age() <= max_age)
+}
+
+// The LRU capacity for a Cache Configuration, holding one entry for a max_cache_size of 0
+pub(crate) fn capacity(config: &CacheConfig) -> NonZeroUsize {
+    NonZeroUsize::new(config.max_cache_size).unwrap_or(NonZeroUsize::MIN)
+}
+
+// An LruCache holding max_cache_size entries, or any number when unbounded
+pub(crate) fn new_lru<K: Hash + Eq, V>(config: &CacheConfig) -> LruCache<K, V> {
+    match config.max_cache_size {
+        usize::MAX => LruCache::unbounded(),
+        _ => LruCache::new(capacity(config)),
+    }
+}
+
+// ParameterCacheBuilder rejects a max_cache_size of 0; the other constructors hold one entry
+fn warn_zero_capacity(config: &CacheConfig) {
+    if config.max_cache_size == 0 {
+        trace::warn!("max_cache_size is 0, so the cache holds a single entry");
+    }
+}
+
+/// A builder for the get_parameter method.
+pub struct GetParameterStringBuilder<'a, 'b, S = LruCache<CacheKey, CacheItem<String>>> {
+    parameter_cache: &'a mut ParameterCache<S>,
+    parameter_name: Cow<'b, str>,
+    force_refresh: bool,
+    stale_on_error: bool,
+    read_only_cache: bool,
+    describe: bool,
+    with_decryption: bool,
+    account: Option<String>,
+    tenant: Option<String>,
+    deadline: Option<Instant>,
+    #[cfg(feature = "cancellation")]
+    cancel_token: Option<tokio_util::sync::CancellationToken>,
+    request_id: Option<String>,
+    customize: Option<CustomizeRequest<'b>>,
+    validate: Option<ValidateValue<'b>>,
+    map: Option<MapValue<'b>>,
+    #[cfg(feature = "checksum")]
+    expect_sha256: Option<String>,
+    ttl: Option<u128>,
+    aliases: Vec<String>,
+    min_version: Option<i64>,
+    max_age: Option<Duration>,
+    fallback_name: Option<String>,
+    synthetic: bool,
+    allow_large: bool,
+    fetched_version: Option<i64>,
+    fetched: Option<Parameter>,
+    source: ValueSource,
+}
+
+impl<'a, 'b, S: CacheStore<CacheKey, CacheItem<String>>> GetParameterStringBuilder<'a, 'b, S> {
+    pub fn new(parameter_cache: &'a mut ParameterCache<S>, parameter_name: &'b str) -> Self {
+        let with_decryption = parameter_cache.config.default_with_decryption;
+        let parameter_name = parameter_cache.normalized_name(parameter_name);
+        GetParameterStringBuilder {
+            parameter_cache,
+            parameter_name,
+            force_refresh: false,
+            stale_on_error: false,
+            read_only_cache: false,
+            describe: false,
+            with_decryption,
+            account: None,
+            tenant: None,
+            deadline: None,
+            #[cfg(feature = "cancellation")]
+            cancel_token: None,
+            request_id: None,
+            customize: None,
+            validate: None,
+            map: None,
+            #[cfg(feature = "checksum")]
+            expect_sha256: None,
+            ttl: None,
+            aliases: Vec::new(),
+            min_version: None,
+            max_age: None,
+            fallback_name: None,
+            synthetic: false,
+            allow_large: false,
+            fetched_version: None,
+            fetched: None,
+            source: ValueSource::Aws,
+        }
+    }
+
+    /// Sets whether a cached value may answer the request.
+    ///
+    /// Consistency::Cached, the default, serves an unexpired cached value. Consistency::Fresh
+    /// always fetches the parameter from AWS and updates the cache with the fresh value, which
+    /// is required when the cached parameter is out of date but not expired, for example due to
+    /// rotation.
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.force_refresh = consistency == Consistency::Fresh;
+        self
+    }
+
+    /// Forces a refresh of the parameter; the same as consistency(Consistency::Fresh).
+    #[deprecated(note = "use consistency(Consistency::Fresh)")]
+    pub fn force_refresh(self) -> Self {
+        self.consistency(Consistency::Fresh)
+    }
+
+    /// Forces a refresh of the parameter, falling back to the cached value if the fetch fails.
+    ///
+    /// Like Consistency::Fresh, the parameter is fetched from AWS and the cache updated with the fresh
+    /// value. If the fetch fails, for example because it was throttled, the value already in the
+    /// cache is returned instead, even if it has expired. The error is only returned if nothing is
+    /// cached. Plain Consistency::Fresh always returns the error.
+    pub fn force_refresh_or_stale(mut self) -> Self {
+        self.force_refresh = true;
+        self.stale_on_error = true;
+        self
+    }
+
+    /// Serves the parameter from the cache if present, but doesn't store it on a miss.
+    ///
+    /// Useful for rarely-read parameters that shouldn't occupy a cache slot. A cache miss
+    /// (including an expired cached value) fetches from AWS without updating the cache.
+    /// Consistency::Fresh takes precedence: a fresh read always updates the cache.
+    pub fn read_only_cache(mut self) -> Self {
+        self.read_only_cache = true;
+        self
+    }
+
+    /// Records the tier of the parameter in the cache when it is fetched.
+    ///
+    /// GetParameter doesn't return the tier, so each fetch makes an additional
+    /// DescribeParameters call (requiring the ssm:DescribeParameters permission).
+    /// If that call fails the value is still cached, without a tier.
+    /// The recorded tier can be read with ParameterCache::get_parameter_tier.
+    pub fn with_tier(mut self) -> Self {
+        self.describe = true;
+        self
+    }
+
+    /// Records the ID of the KMS key encrypting the parameter when it is fetched.
+    ///
+    /// This is read-only audit metadata and doesn't change how the value is fetched. Like
+    /// with_tier it needs a DescribeParameters call on each fetch; using both options together
+    /// makes a single call. The recorded key can be read with ParameterCache::get_parameter_key_id.
+    pub fn with_key_id(mut self) -> Self {
+        self.describe = true;
+        self
+    }
+
+    /// Sets whether SecureString values are fetched decrypted.
+    ///
+    /// Overrides default_with_decryption from the CacheConfig for this call. Decrypted and
+    /// encrypted values are cached separately, so this never returns a value fetched with
+    /// the other setting.
+    pub fn with_decryption(mut self, with_decryption: bool) -> Self {
+        self.with_decryption = with_decryption;
+        self
+    }
+
+    /// Fetches the parameter with the client registered for an account.
+    ///
+    /// Clients are registered with ParameterCache::with_account_client. Values fetched for an
+    /// account are cached separately from those of other accounts. Fetching for an account
+    /// with no registered client returns a CacheError::UnknownAccount.
+    pub fn account(mut self, account: impl Into<String>) -> Self {
+        self.account = Some(account.into());
+        self
+    }
+
+    /// Requests the parameter for a tenant of a multi-tenant service.
+    ///
+    /// The tenant replaces a {tenant} placeholder in the name, after any {env} placeholder and
+    /// name normalization, so "/app/{env}/{tenant}/db" reads "/app/prod/acme/db" for the
+    /// tenant "acme". Whether or not the name has a placeholder, the value is cached under the
+    /// tenant, apart from other tenants' values and those requested without a tenant. A
+    /// placeholder left with no tenant set is rejected as an invalid name.
+    pub fn tenant(mut self, tenant: impl Into<String>) -> Self {
+        let tenant = tenant.into();
+        if self.parameter_name.contains(TENANT_PLACEHOLDER) {
+            self.parameter_name =
+                Cow::Owned(self.parameter_name.replace(TENANT_PLACEHOLDER, &tenant));
+        }
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Tags fetches from AWS SSM with a request id, for correlation in logs.
+    ///
+    /// The id is recorded on the tracing span around the fetch, with the tracing feature. It has
+    /// no effect on caching.
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Verifies the value against an expected SHA-256, given as hex, e.g. to guard
+    /// integrity-sensitive configuration against unexpected changes or corruption.
+    ///
+    /// A fetched value that doesn't match isn't cached and send() returns a
+    /// CacheError::ChecksumMismatch, leaving any value already cached in place. Values served from
+    /// the cache are verified too, since they may have been stored without this option. Runs
+    /// alongside any validate check.
+    #[cfg(feature = "checksum")]
+    pub fn expect_sha256(mut self, expected: &str) -> Self {
+        self.expect_sha256 = Some(expected.to_ascii_lowercase());
+        self
+    }
+
+    /// Checks a fetched value before it is cached, e.g. that it parses as the expected JSON.
+    ///
+    /// A value that fails the check isn't cached and send() returns a
+    /// CacheError::ValidationFailed with the check's error message, leaving any value already
+    /// cached in place. Values served from the cache passed the check when they were stored.
+    pub fn validate<E: fmt::Display>(
+        mut self,
+        validate: impl Fn(&str) -> Result<(), E> + Send + Sync + 'b,
+    ) -> Self {
+        self.validate = Some(Box::new(move |value| {
+            validate(value).map_err(|e| e.to_string())
+        }));
+        self
+    }
+
+    /// Transforms the value returned by this request, e.g. to interpolate the current region
+    /// into a template value.
+    ///
+    /// The closure runs on every read, both cache hits and fetches, just before the value is
+    /// returned. The cache keeps the raw value from AWS, so other requests for the parameter
+    /// are unaffected; checks such as validate see the raw value too.
+    pub fn map(mut self, map: impl Fn(String) -> String + Send + Sync + 'b) -> Self {
+        self.map = Some(Box::new(map));
+        self
+    }
+
+    /// Adjusts the GetParameter request before it is sent, for SDK options the cache doesn't
+    /// model, such as customizing the operation's configuration.
+    ///
+    /// The closure receives the request with the name and decryption setting already set, and
+    /// applies only to fetches made by this builder. The value is cached under the name and
+    /// decryption setting of the builder, whatever the closure changes.
+    pub fn customize(
+        mut self,
+        customize: impl Fn(GetParameterFluentBuilder) -> GetParameterFluentBuilder + Send + Sync + 'b,
+    ) -> Self {
+        self.customize = Some(Box::new(customize));
+        self
+    }
+
+    /// Sets a deadline for fetching the parameter from AWS SSM.
+    ///
+    /// If the fetch hasn't completed by the deadline it is abandoned and send() returns
+    /// a CacheError::DeadlineExceeded, leaving the cache unchanged. Values served from the
+    /// cache are unaffected. This composes with an upstream request deadline, unlike a fixed
+    /// timeout. Requires a Tokio runtime with the time driver enabled.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Abandons fetching the parameter from AWS SSM when a token is cancelled, e.g. when the
+    /// client of a request-scoped task disconnects.
+    ///
+    /// send() then returns a CacheError::Cancelled, leaving the cache unchanged. Values served
+    /// from the cache are unaffected.
+    #[cfg(feature = "cancellation")]
+    pub fn cancel_token(mut self, cancel_token: tokio_util::sync::CancellationToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+
+    /// Stores the fetched value with this TTL instead of the cache_item_ttl from the CacheConfig.
+    ///
+    /// A TTL of zero stores the value already expired, so every send() for the parameter
+    /// fetches from AWS while the stored value remains available to force_refresh_or_stale.
+    /// Values served from the cache keep the TTL they were stored with.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl.as_nanos());
+        self
+    }
+
+    /// Fetches this version of the parameter, as the "name:version" selector does.
+    ///
+    /// The value is cached under the name with the selector, apart from the latest value, and
+    /// with the pinned_version_ttl from the CacheConfig if one is set. send() returns a
+    /// CacheError::InvalidName if the name already has a selector, e.g. from label.
+    pub fn version(mut self, version: i64) -> Self {
+        self.parameter_name = Cow::Owned(format!("{}:{}", self.parameter_name, version));
+        self
+    }
+
+    /// Fetches the version of the parameter with this label, as the "name:label" selector does.
+    ///
+    /// The value is cached under the name with the selector, apart from the latest value and
+    /// other labels. Labels can be moved, so the value expires and is refreshed as usual.
+    /// send() returns a CacheError::InvalidName if the name already has a selector, e.g. from
+    /// version.
+    pub fn label(mut self, label: &str) -> Self {
+        self.parameter_name = Cow::Owned(format!("{}:{}", self.parameter_name, label));
+        self
+    }
+
+    /// Also stores the fetched value under other names, for parameters referenced by several
+    /// logical names.
+    ///
+    /// Whenever the value is stored in the cache it is stored under each alias too, with the
+    /// same TTL, decryption setting and account, so later lookups by an alias are hits instead of
+    /// fetching again. Values served from the cache don't update the aliases. Invalidating the
+    /// parameter by its real name also invalidates its aliases; invalidating an alias only
+    /// removes that alias.
+    pub fn also_cache_as(mut self, aliases: &[&str]) -> Self {
+        self.aliases
+            .extend(aliases.iter().map(|alias| alias.to_string()));
+        self
+    }
+
+    /// Rejects values of the parameter below a known-good version, to avoid serving a
+    /// rolled-back configuration.
+    ///
+    /// A cached value is only served if it is known to be at least this version; otherwise the
+    /// parameter is fetched. If AWS SSM returns an older version, send() returns a
+    /// CacheError::StaleVersion and the value isn't cached. GetParameter returns the version,
+    /// so this needs no extra calls. Not checked in dry run mode.
+    pub fn min_version(mut self, min_version: i64) -> Self {
+        self.min_version = Some(min_version);
+        self
+    }
+
+    /// Only serves a cached value fetched at most this long ago, otherwise refreshing it first.
+    ///
+    /// A per-request freshness requirement independent of the cache_item_ttl: the age is
+    /// measured from when the value was stored, and a value within its TTL but older than this
+    /// is fetched again and replaces the cached value.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Fetches another parameter if this one doesn't exist, e.g. the old name while a parameter
+    /// is being renamed.
+    ///
+    /// Only a NotFound error from AWS SSM triggers the fallback; any other error is returned.
+    /// The fallback's value is cached under this parameter's name, so later reads hit the cache
+    /// until it expires, and then try this parameter first again.
+    pub fn fallback_name(mut self, fallback_name: &str) -> Self {
+        self.fallback_name = Some(
+            self.parameter_cache
+                .normalized_name(fallback_name)
+                .into_owned(),
+        );
+        self
+    }
+
+    /// Marks the request as synthetic (e.g. canary traffic), exercising the fetch path
+    /// without touching the cache.
+    ///
+    /// The parameter is always fetched from AWS and the cache is neither read nor updated.
+    /// The fetch is counted in the synthetic count of CacheStats rather than in the hit, miss
+    /// and fetch counts, so canary traffic doesn't skew the other stats.
+    pub fn synthetic(mut self) -> Self {
+        self.synthetic = true;
+        self
+    }
+
+    /// Caches the fetched value even if it is larger than max_value_bytes, e.g. for one known
+    /// large parameter.
+    ///
+    /// This overrides the max_value_bytes limit from the CacheConfig for this call only; the
+    /// value still counts towards max_memory_bytes.
+    pub fn allow_large(mut self) -> Self {
+        self.allow_large = true;
+        self
+    }
+
+    /// Makes send() return Ok(None) rather than an error if the parameter doesn't exist.
+    ///
+    /// Other failures, such as throttling or access denied, are still returned as errors.
+    /// The parameter is looked up in the cache and fetched exactly as with send().
+    pub fn optional(self) -> OptionalParameterBuilder<'a, 'b, S> {
+        OptionalParameterBuilder { builder: self }
+    }
+
+    /// Fetches the parameter value from the cache.
+    ///
+    /// If the parameter value exists in the cache and hasn't expired it will be immediately returned.
+    /// The parameter will be fetched by calling AWS SSM and updated in the cache if:
+    /// - the parameter value hasn't been stored in the cache
+    /// - the parameter stored in the cache but has expired
+    /// - consistency(Consistency::Fresh) was requested
+    ///
+    /// Values are stored in the cache with the cache_item_ttl from the CacheConfig (or the ttl
+    /// option), unless the read_only_cache option was provided.
+    ///
+    /// The builder is consumed; start a new one with get_parameter for the next request.
+    pub async fn send(mut self) -> Result<String, CacheError> {
+        self.send_value().await
+    }
+
+    async fn send_value(&mut self) -> Result<String, CacheError> {
+        let parameter_value = self.resolve_value().await?;
+        Ok(match &self.map {
+            Some(map) => map(parameter_value),
+            None => parameter_value,
+        })
+    }
+
+    // The raw value, from the cache or AWS
+    async fn resolve_value(&mut self) -> Result<String, CacheError> {
+        if self.synthetic {
+            return self
+                .fetch_parameter()
+                .await
+                .map(|(parameter_value, _)| parameter_value);
+        }
+        let mut expired = false;
+        if !self.force_refresh && self.parameter_cache.config.caching_enabled {
+            let key = self.cache_key();
+            let update_recency_on_hit = self.parameter_cache.config.update_recency_on_hit;
+            if let Some(cache_item) =
+                lookup(&mut self.parameter_cache.cache, update_recency_on_hit, &key)
+            {
+                expired = cache_item.is_expired();
+                if !expired
+                    && meets_min_version(cache_item, self.min_version)
+                    && within_max_age(cache_item, self.max_age)
+                {
+                    let parameter_value = cache_item.value.clone();
+                    let expires_in = near_expiry(cache_item, &self.parameter_cache.config);
+                    #[cfg(feature = "checksum")]
+                    self.check_sha256(&parameter_value)?;
+                    self.parameter_cache
+                        .record_lookup(&self.parameter_name, true);
+                    if let Some(expires_in) = expires_in {
+                        self.parameter_cache.emit(|| CacheEvent::NearExpiry {
+                            name: self.parameter_name.to_string(),
+                            expires_in,
+                        });
+                    }
+                    self.source = ValueSource::Cache;
+                    return Ok(parameter_value);
+                }
+            }
+        }
+        self.parameter_cache
+            .record_lookup(&self.parameter_name, false);
+        if expired {
+            self.parameter_cache.record_expired(&self.parameter_name);
+        }
+        if self.force_refresh {
+            self.parameter_cache
+                .record_forced_refresh(&self.parameter_name);
+        }
+        if let Some(parameter_value) = self.secondary_value().await {
+            self.source = ValueSource::Secondary;
+            return Ok(parameter_value);
+        }
+
+        let fetched = self.fetch_parameter().await;
+        if let Ok((parameter_value, _)) = &fetched {
+            self.check_value(parameter_value)?;
+        }
+        match fetched {
+            Ok((_, version)) if self.is_below_min_version(version) => {
+                Err(CacheError::StaleVersion {
+                    name: self.parameter_name.to_string(),
+                    version,
+                    min_version: self.min_version.unwrap_or_default(),
+                })
+            }
+            Ok((parameter_value, _)) if self.read_only_cache && !self.force_refresh => {
+                Ok(parameter_value)
+            }
+            Ok((parameter_value, version)) => {
+                self.store_value(parameter_value.clone(), version).await;
+                Ok(parameter_value)
+            }
+            Err(e) => {
+                let key = self.cache_key();
+                self.parameter_cache.record_failed_refresh(&key);
+                let stale_value = match self.stale_on_error {
+                    true => self
+                        .parameter_cache
+                        .cache
+                        .peek(&key)
+                        .map(|cache_item| cache_item.value.clone()),
+                    false => None,
+                };
+                match stale_value {
+                    Some(parameter_value) => {
+                        self.parameter_cache
+                            .record_stale_served(&self.parameter_name);
+                        self.source = ValueSource::Stale;
+                        Ok(parameter_value)
+                    }
+                    None => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Gets both the encrypted and the decrypted form of a SecureString parameter, e.g. to log
+    /// the ciphertext while using the plaintext.
+    ///
+    /// AWS SSM returns one form per call, so a miss costs two GetParameter calls, one with and
+    /// one without decryption. Each form is cached separately under its own decryption setting.
+    /// The with_decryption option is ignored. For other parameter types both forms are the same.
+    pub fn with_both(self) -> BothFormsParameterBuilder<'a, 'b, S> {
+        BothFormsParameterBuilder { builder: self }
+    }
+
+    /// Fetches the parameter value as with send(), along with where the value came from, e.g.
+    /// to debug why a configuration looks wrong.
+    pub async fn send_with_source(mut self) -> Result<(String, ValueSource), CacheError> {
+        let value = self.send_value().await?;
+        Ok((value, self.source))
+    }
+
+    /// Fetches the parameter value as with send(), along with when it was fetched from AWS and
+    /// its version.
+    ///
+    /// For a value served from the cache these come from the cache entry. The version is None
+    /// if it isn't known, such as for values inserted into the cache directly.
+    pub async fn send_timestamped(mut self) -> Result<Timestamped<String>, CacheError> {
+        let value = self.send_value().await?;
+        let now = self.parameter_cache.clock.now();
+        let timestamped = match self.fetched_version {
+            Some(version) => Timestamped {
+                value,
+                fetched_at: now,
+                version: Some(version),
+            },
+            None => {
+                let cache_item = self.parameter_cache.cache.peek(&self.cache_key());
+                Timestamped {
+                    value,
+                    fetched_at: cache_item.map_or(now, CacheItem::inserted_at),
+                    version: cache_item.and_then(|cache_item| cache_item.version),
+                }
+            }
+        };
+        Ok(timestamped)
+    }
+
+    /// Fetches the parameter value as with send(), along with its version, type, last modified
+    /// time and ARN, e.g. to detect rotations or log where a value came from.
+    ///
+    /// For a value served from the cache these come from the cache entry, so repeated reads
+    /// don't call AWS again. Each is None if it isn't known, such as for values inserted into
+    /// the cache directly. The tier and KMS key ID are only known with the with_tier and
+    /// with_key_id options.
+    pub async fn send_with_metadata(mut self) -> Result<CachedParameter, CacheError> {
+        let value = self.send_value().await?;
+        let cache_item = self.parameter_cache.cache.peek(&self.cache_key());
+        let tier = cache_item.and_then(|cache_item| cache_item.tier.clone());
+        let key_id = cache_item.and_then(|cache_item| cache_item.key_id.clone());
+        let cached_parameter = match &self.fetched {
+            Some(parameter) => CachedParameter {
+                value,
+                version: Some(parameter.version),
+                parameter_type: parameter.r#type.clone(),
+                last_modified: parameter
+                    .last_modified_date
+                    .and_then(|date| SystemTime::try_from(date).ok()),
+                arn: parameter.arn.clone(),
+                tier,
+                key_id,
+            },
+            None => CachedParameter {
+                value,
+                version: cache_item.and_then(|cache_item| cache_item.version),
+                parameter_type: cache_item.and_then(|cache_item| cache_item.parameter_type.clone()),
+                last_modified: cache_item.and_then(|cache_item| cache_item.last_modified),
+                arn: cache_item.and_then(|cache_item| cache_item.arn.clone()),
+                tier,
+                key_id,
+            },
+        };
+        Ok(cached_parameter)
+    }
+
+    /// Fetches the parameter value as with send(), along with its generation, e.g. to tell
+    /// whether a value has changed since it was last read without comparing the values.
+    ///
+    /// The generation starts at 0 when the parameter is first cached and increases by one each
+    /// time a refresh brings a different value. It starts again at 0 if the entry leaves the
+    /// cache, and is always 0 when caching is disabled.
+    pub async fn send_with_generation(mut self) -> Result<(String, u64), CacheError> {
+        let value = self.send_value().await?;
+        let generation = self
+            .parameter_cache
+            .cache
+            .peek(&self.cache_key())
+            .map_or(0, |cache_item| cache_item.generation);
+        Ok((value, generation))
+    }
+
+    /// Fetches the parameter value as with send(), borrowing it from the cache when possible.
+    ///
+    /// A cache hit borrows the cached value, avoiding a clone; the borrow holds the cache
+    /// until it is dropped. A value that had to be fetched from AWS is returned owned, as are
+    /// values when caching is disabled and values transformed with map.
+    pub async fn send_cow(self) -> Result<Cow<'a, str>, CacheError> {
+        let key = self.cache_key();
+        let update_recency_on_hit = self.parameter_cache.config.update_recency_on_hit;
+        let hit = !self.force_refresh
+            && !self.synthetic
+            && self.map.is_none()
+            && self.parameter_cache.config.caching_enabled
+            && matches!(lookup(&mut self.parameter_cache.cache, update_recency_on_hit, &key),
+                Some(cache_item) if !cache_item.is_expired()
+                    && meets_min_version(cache_item, self.min_version)
+                    && within_max_age(cache_item, self.max_age));
+        if hit {
+            #[cfg(feature = "checksum")]
+            if let Some(cache_item) = self.parameter_cache.cache.peek(&key) {
+                self.check_sha256(&cache_item.value)?;
+            }
+            self.parameter_cache
+                .record_lookup(&self.parameter_name, true);
+            let expires_in = self
+                .parameter_cache
+                .cache
+                .peek(&key)
+                .and_then(|cache_item| near_expiry(cache_item, &self.parameter_cache.config));
+            if let Some(expires_in) = expires_in {
+                self.parameter_cache.emit(|| CacheEvent::NearExpiry {
+                    name: self.parameter_name.to_string(),
+                    expires_in,
+                });
+            }
+            let parameter_cache: &'a ParameterCache<S> = self.parameter_cache;
+            let cache_item = parameter_cache
+                .cache
+                .peek(&key)
+                .expect("cache hit checked above");
+            return Ok(Cow::Borrowed(&cache_item.value));
+        }
+        self.send().await.map(Cow::Owned)
+    }
+
+    /// Fetches the parameter value as with send(), as an `Arc<str>` that can be cloned and
+    /// shared between tasks without copying the value again.
+    ///
+    /// The cache stores values as String, so this copies the value once into the Arc; use
+    /// send_cow to avoid the copy on a cache hit when the value isn't shared.
+    pub async fn send_arc(self) -> Result<Arc<str>, CacheError> {
+        self.send_cow().await.map(Arc::from)
+    }
+
+    /// Fetches the parameter value as with send() and decodes it from base64.
+    ///
+    /// The encoded string is what gets cached; decoding happens on every call.
+    /// Returns a CacheError::Parse if the value is not valid base64.
+    #[cfg(feature = "base64")]
+    pub async fn send_bytes(self) -> Result<Vec<u8>, CacheError> {
+        let parameter_name = self.parameter_name.clone();
+        let value = self.send().await?;
+        parse::parse_base64(&parameter_name, &value)
+    }
+
+    /// Fetches the parameter value as with send() and deserializes it from JSON, e.g. into a
+    /// config struct.
+    ///
+    /// The JSON string is what gets cached; deserialization happens on every call.
+    /// Returns a CacheError::Parse if the value is not valid JSON for T.
+    #[cfg(feature = "serde")]
+    pub async fn send_json<T: serde::de::DeserializeOwned>(self) -> Result<T, CacheError> {
+        let parameter_name = self.parameter_name.clone();
+        let value = self.send().await?;
+        parse::parse_json(&parameter_name, &value)
+    }
+
+    /// Fetches the parameter from AWS SSM and returns the full GetParameterOutput.
+    ///
+    /// This is an escape hatch for response fields the cache doesn't model. The parameter is
+    /// always fetched from AWS, regardless of what is cached, and the fetched value then
+    /// replaces any cached value with the cache_item_ttl from the CacheConfig, unless the
+    /// read_only_cache option was provided. The fetch counts toward the stats and events like
+    /// any other fetch from AWS, though not as a cache lookup.
+    pub async fn send_raw(mut self) -> Result<GetParameterOutput, CacheError> {
+        let output = self.fetch_output().await?;
+        if self.synthetic || (self.read_only_cache && !self.force_refresh) {
+            return Ok(output);
+        }
+        if let Some(parameter) = output.parameter.as_ref() {
+            if let Some(parameter_value) = parameter.value.clone() {
+                self.check_value(&parameter_value)?;
+                self.fetched = Some(parameter.clone());
+                self.store_value(parameter_value, parameter.version).await;
+            }
+        }
+        Ok(output)
+    }
+
+    async fn store_value(&mut self, parameter_value: String, version: i64) {
+        let ttl = self.item_ttl();
+        let clock = self.parameter_cache.clock.clone();
+        let mut cache_item = CacheItem::new_with_clock(parameter_value, ttl, clock);
+        cache_item.version = Some(version);
+        if let Some(parameter) = &self.fetched {
+            cache_item.set_metadata(parameter);
+        }
+        if self.describe && !self.parameter_cache.config.dry_run {
+            if let Some(metadata) = self.describe_parameter().await {
+                cache_item.tier = metadata.tier;
+                cache_item.key_id = metadata.key_id;
+            }
+        }
+        for alias in &self.aliases {
+            let alias_key = CacheKey::new(alias.clone(), self.with_decryption)
+                .with_account(self.account.clone())
+                .with_tenant(self.tenant.clone());
+            self.parameter_cache
+                .insert_value(alias_key, cache_item.clone(), self.allow_large);
+            self.parameter_cache.add_alias(&self.parameter_name, alias);
+        }
+        let key = self.cache_key();
+        if let (Some(secondary), false) = (
+            self.parameter_cache.secondary.clone(),
+            self.parameter_cache.config.dry_run,
+        ) {
+            let ttl = Duration::from_nanos(u64::try_from(ttl).unwrap_or(u64::MAX));
+            secondary.put(&key, &cache_item.value, ttl).await;
+        }
+        self.parameter_cache
+            .insert_value(key, cache_item, self.allow_large);
+    }
+
+    // The TTL to store the value with: the request's, else pinned_version_ttl for a pinned
+    // version, else the cache_item_ttl
+    fn item_ttl(&self) -> u128 {
+        let config = &self.parameter_cache.config;
+        self.ttl
+            .or_else(|| {
+                pinned_version(&self.parameter_name)
+                    .and(config.pinned_version_ttl)
+                    .map(|ttl| ttl.as_nanos())
+            })
+            .unwrap_or(config.cache_item_ttl)
+    }
+
+    // The value from the secondary store, if there is one and it can be used, cached in-process
+    async fn secondary_value(&mut self) -> Option<String> {
+        let config = &self.parameter_cache.config;
+        if self.force_refresh
+            || self.min_version.is_some()
+            || self.max_age.is_some()
+            || config.dry_run
+            || !config.caching_enabled
+        {
+            return None;
+        }
+        let secondary = self.parameter_cache.secondary.clone()?;
+        let key = self.cache_key();
+        let parameter_value = secondary.get(&key).await?;
+        self.check_value(&parameter_value).ok()?;
+        if !self.read_only_cache {
+            let clock = self.parameter_cache.clock.clone();
+            let cache_item =
+                CacheItem::new_with_clock(parameter_value.clone(), self.item_ttl(), clock);
+            self.parameter_cache
+                .insert_value(key, cache_item, self.allow_large);
+        }
+        Some(parameter_value)
+    }
+
+    fn check_value(&self, parameter_value: &str) -> Result<(), CacheError> {
+        #[cfg(feature = "checksum")]
+        self.check_sha256(parameter_value)?;
+        match &self.validate {
+            Some(validate) => {
+                validate(parameter_value).map_err(|message| CacheError::ValidationFailed {
+                    name: self.parameter_name.to_string(),
+                    message,
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "checksum")]
+    fn check_sha256(&self, parameter_value: &str) -> Result<(), CacheError> {
+        let Some(expected) = &self.expect_sha256 else {
+            return Ok(());
+        };
+        let actual = parse::sha256_hex(parameter_value);
+        match actual == *expected {
+            true => Ok(()),
+            false => Err(CacheError::ChecksumMismatch {
+                name: self.parameter_name.to_string(),
+                expected: expected.clone(),
+                actual,
+            }),
+        }
+    }
+
+    fn is_below_min_version(&self, version: i64) -> bool {
+        !self.parameter_cache.config.dry_run
+            && matches!(self.min_version, Some(min_version) if version < min_version)
+    }
+
+    // Keyed by the requested name, selector included, rather than the Name SSM returns, so later
+    // reads of the same selector hit
+    fn cache_key(&self) -> CacheKey {
+        CacheKey::new(&*self.parameter_name, self.with_decryption)
+            .with_account(self.account.clone())
+            .with_tenant(self.tenant.clone())
+    }
+
+    async fn describe_parameter(&self) -> Option<ParameterMetadata> {
+        let filter = ParameterStringFilter::builder()
+            .key("Name")
+            .option("Equals")
+            .values(&*self.parameter_name)
+            .build()
+            .ok()?;
+        self.parameter_cache.fetch_rate.acquire().await;
+        let output = self
+            .parameter_cache
+            .client_for(self.account.as_deref())
+            .ok()?
+            .describe_parameters()
+            .parameter_filters(filter)
+            .send()
+            .await
+            .ok()?;
+        output
+            .parameters
+            .unwrap_or_default()
+            .into_iter()
+            .find(|metadata| metadata.name() == Some(&*self.parameter_name))
+    }
+
+    // The fetched value and its version
+    async fn fetch_parameter(&mut self) -> Result<(String, i64), CacheError> {
+        let resp = match self.fetch_output().await {
+            Err(e) if e.is_not_found() && self.fallback_name.is_some() => {
+                let fallback_name = self.fallback_name.take().map(Cow::Owned);
+                let primary_name =
+                    std::mem::replace(&mut self.parameter_name, fallback_name.unwrap_or_default());
+                let resp = self.fetch_output().await;
+                self.parameter_name = primary_name;
+                if resp.is_ok() {
+                    self.parameter_cache
+                        .record_fallback_used(&self.parameter_name);
+                }
+                resp?
+            }
+            resp => resp?,
+        };
+        let (parameter_value, parameter) = self
+            .parameter_cache
+            .take_value(&self.parameter_name, resp)?;
+        let version = parameter.version;
+        self.fetched_version = Some(version);
+        self.fetched = Some(parameter);
+        self.source = match self.parameter_cache.config.dry_run {
+            true => ValueSource::DryRun,
+            false => ValueSource::Aws,
+        };
+        Ok((parameter_value, version))
+    }
+
+    async fn fetch_output(&mut self) -> Result<GetParameterOutput, CacheError> {
+        validate_name(&self.parameter_name)?;
+        if self.parameter_cache.config.dry_run {
+            return self.parameter_cache.dry_run_output(
+                &self.parameter_name,
+                self.with_decryption,
+                self.account.as_deref(),
+            );
+        }
+        let key = self.cache_key();
+        if let Some(e) = self.parameter_cache.fetch_refused(&key, self.force_refresh) {
+            return Err(e);
+        }
+        let retry = self.parameter_cache.retry_policy();
+        let started = Instant::now();
+        let request = self
+            .parameter_cache
+            .client_for(self.account.as_deref())?
+            .get_parameter()
+            .name(&*self.parameter_name)
+            .with_decryption(self.with_decryption);
+        let request = match &self.customize {
+            Some(customize) => customize(request),
+            None => request,
+        };
+        let request = async move { retry.send(|| request.clone().send()).await }.instrument(
+            trace::debug_span!(
+                "fetch_parameter",
+                parameter_name = &*self.parameter_name,
+                account = self.account.as_deref(),
+                request_id = self.request_id.as_deref(),
+            ),
+        );
+        let deadline = self.deadline;
+        let request = async {
+            match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline.into(), request)
+                    .await
+                    .map_err(|_| CacheError::DeadlineExceeded {
+                        name: self.parameter_name.to_string(),
+                    }),
+                None => Ok(request.await),
+            }
+        };
+        #[cfg(feature = "cancellation")]
+        let request = cancellable(request, self.cancel_token.clone(), &self.parameter_name);
+        let result = request
+            .await?
+            .map_err(|e| CacheError::from_sdk_error(&self.parameter_name, e));
+        self.parameter_cache
+            .record_get_parameter(&key, started, &result, self.synthetic);
+        result
+    }
+}
+
+// Runs a fetch until it completes or the token, if any, is cancelled
+#[cfg(feature = "cancellation")]
+async fn cancellable<T>(
+    fetch: impl std::future::Future<Output = Result<T, CacheError>>,
+    cancel_token: Option<tokio_util::sync::CancellationToken>,
+    parameter_name: &str,
+) -> Result<T, CacheError> {
+    match cancel_token {
+        Some(cancel_token) => cancel_token
+            .run_until_cancelled(fetch)
+            .await
+            .unwrap_or_else(|| {
+                Err(CacheError::Cancelled {
+                    name: parameter_name.to_string(),
+                })
+            }),
+        None => fetch.await,
+    }
+}
+
+/// A builder for a parameter that may not exist, returned by the optional option.
+pub struct OptionalParameterBuilder<'a, 'b, S = LruCache<CacheKey, CacheItem<String>>> {
+    builder: GetParameterStringBuilder<'a, 'b, S>,
+}
+
+impl<'a, 'b, S: CacheStore<CacheKey, CacheItem<String>>> OptionalParameterBuilder<'a, 'b, S> {
+    /// Fetches the parameter value as with GetParameterStringBuilder::send().
+    ///
+    /// Returns Ok(None) if the parameter doesn't exist.
+    pub async fn send(self) -> Result<Option<String>, CacheError> {
+        match self.builder.send().await {
+            Ok(parameter_value) => Ok(Some(parameter_value)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Whether a request may be answered from the cache, set with the consistency option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Consistency {
+    /// An unexpired cached value answers the request; otherwise it is fetched from AWS SSM.
+    #[default]
+    Cached,
+
+    /// The request is always fetched from AWS SSM, bypassing any cached value.
+    Fresh,
+}
+
+/// Where a parameter value returned by send_with_source came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValueSource {
+    /// The value was cached and hadn't expired.
+    Cache,
+
+    /// The value was fetched from AWS SSM.
+    Aws,
+
+    /// Fetching the value failed, so the stale cached value was served (see stale_on_error).
+    Stale,
+
+    /// The cache is in dry run mode and returned its dry_run_placeholder without calling AWS.
+    DryRun,
+
+    /// The value wasn't cached in-process but was found in the secondary store.
+    Secondary,
+}
+
+/// A parameter value with when it was fetched from AWS and its version, returned by
+/// send_timestamped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timestamped<T> {
+    /// The parameter value.
+    pub value: T,
+
+    /// When the value was fetched from AWS SSM.
+    pub fetched_at: SystemTime,
+
+    /// The version of the parameter, if known.
+    pub version: Option<i64>,
+}
+
+/// A parameter value with its version, last modified time and ARN, returned by
+/// send_with_metadata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedParameter {
+    /// The parameter value.
+    pub value: String,
+
+    /// The version of the parameter, if known.
+    pub version: Option<i64>,
+
+    /// The type of the parameter, if known.
+    pub parameter_type: Option<ParameterType>,
+
+    /// When the parameter was last written, if known.
+    pub last_modified: Option<SystemTime>,
+
+    /// The ARN of the parameter, if known.
+    pub arn: Option<String>,
+
+    /// The tier of the parameter, if recorded with the with_tier option.
+    pub tier: Option<ParameterTier>,
+
+    /// The ID of the KMS key encrypting a SecureString, if recorded with the with_key_id option.
+    pub key_id: Option<String>,
+}
+
+/// The encrypted and decrypted forms of a parameter value, returned by the with_both option.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterForms {
+    /// The value fetched without decryption: the ciphertext of a SecureString.
+    pub encrypted: String,
+
+    /// The value fetched with decryption: the plaintext of a SecureString.
+    pub decrypted: String,
+}
+
+/// A builder for both forms of a parameter value, returned by the with_both option.
+pub struct BothFormsParameterBuilder<'a, 'b, S = LruCache<CacheKey, CacheItem<String>>> {
+    builder: GetParameterStringBuilder<'a, 'b, S>,
+}
+
+impl<'a, 'b, S: CacheStore<CacheKey, CacheItem<String>>> BothFormsParameterBuilder<'a, 'b, S> {
+    /// Fetches each form of the parameter value as with GetParameterStringBuilder::send().
+    pub async fn send(mut self) -> Result<ParameterForms, CacheError> {
+        self.builder.with_decryption = false;
+        let encrypted = self.builder.send_value().await?;
+        self.builder.with_decryption = true;
+        let decrypted = self.builder.send_value().await?;
+        Ok(ParameterForms {
+            encrypted,
+            decrypted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{
+        error_response, mock_ssm_client, never_responding_ssm_client, parameter_response,
+        ManualClock,
+    };
+    use aws_sdk_config::config::{Credentials, Region};
+    use aws_sdk_ssm::{Client as SSMClient, Config};
+    use std::collections::HashMap;
+    use std::hash::Hash;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn get_parameter_builder_defaults() {
+        let mock_ssm_client = get_mock_ssm_client();
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let builder = GetParameterStringBuilder::new(&mut parameter_cache, "service/parameter");
+
+        assert_eq!(builder.parameter_name, "service/parameter");
+        assert!(!builder.force_refresh);
+        assert!(builder.request_id.is_none());
+    }
+
+    #[test]
+    fn get_parameter_builder_request_id() {
+        let mock_ssm_client = get_mock_ssm_client();
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let builder = GetParameterStringBuilder::new(&mut parameter_cache, "service/parameter")
+            .request_id("req-123");
+
+        assert_eq!(builder.request_id.as_deref(), Some("req-123"));
+    }
+
+    #[test]
+    fn parameter_cache_config() {
+        let mock_ssm_client = get_mock_ssm_client();
+        let config = CacheConfig::new().max_cache_size(10).cache_item_ttl(1000);
+        let parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        assert_eq!(parameter_cache.config().max_cache_size, 10);
+        assert_eq!(parameter_cache.config().cache_item_ttl, 1000);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn get_parameter_builder_force_refresh() {
+        let mock_ssm_client = get_mock_ssm_client();
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let builder = GetParameterStringBuilder::new(&mut parameter_cache, "service/parameter")
+            .force_refresh();
+
+        assert_eq!(builder.parameter_name, "service/parameter");
+        assert!(builder.force_refresh);
+    }
+
+    #[test]
+    fn get_parameter_builder_consistency_fresh() {
+        let mock_ssm_client = get_mock_ssm_client();
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let builder = GetParameterStringBuilder::new(&mut parameter_cache, "service/parameter")
+            .consistency(Consistency::Fresh);
+
+        assert_eq!(builder.parameter_name, "service/parameter");
+        assert!(builder.force_refresh);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_send_raw_always_fetches() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            (
+                200,
+                parameter_response("service/parameter", &format!("value{}", n)),
+            )
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value0");
+
+        let output = parameter_cache
+            .get_parameter("service/parameter")
+            .send_raw()
+            .await
+            .unwrap();
+        let parameter = output.parameter.unwrap();
+        assert_eq!(parameter.value.as_deref(), Some("value1"));
+        assert_eq!(parameter.version, 1);
+
+        // the raw fetch refreshed the cached value
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value1");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(parameter_cache.stats().fetches, 2);
+
+        parameter_cache
+            .get_parameter("service/parameter")
+            .read_only_cache()
+            .send_raw()
+            .await
+            .unwrap();
+        assert_eq!(parameter_cache.peek("service/parameter").unwrap(), "value1");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_read_only_cache() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        // misses are not stored
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("service/parameter")
+                .read_only_cache()
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // hits are served from the cache
+        parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        parameter_cache
+            .get_parameter("service/parameter")
+            .read_only_cache()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_with_description_metadata() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| {
+            match operation {
+            "GetParameter" => (200, parameter_response("service/parameter", "value")),
+            "DescribeParameters" => (
+                200,
+                r#"{"Parameters":[{"Name":"service/parameter","Tier":"Advanced","KeyId":"alias/aws/ssm"}]}"#.to_string(),
+            ),
+            _ => panic!("unexpected operation {}", operation),
+        }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            parameter_cache.get_parameter_tier("service/parameter"),
+            None
+        );
+        assert_eq!(
+            parameter_cache.get_parameter_key_id("service/parameter"),
+            None
+        );
+
+        parameter_cache
+            .get_parameter("service/parameter")
+            .with_tier()
+            .with_key_id()
+            .consistency(Consistency::Fresh)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            parameter_cache.get_parameter_tier("service/parameter"),
+            Some(ParameterTier::Advanced)
+        );
+        assert_eq!(
+            parameter_cache
+                .get_parameter_key_id("service/parameter")
+                .as_deref(),
+            Some("alias/aws/ssm")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_parameter_with_custom_store() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new_with_store(
+            mock_ssm_client,
+            CacheConfig::new(),
+            MapStore::default(),
+        );
+
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(parameter_cache.cache.0.len(), 1);
+        assert_eq!(parameter_cache.capacity(), None);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_default_with_decryption() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, body| match body.contains(r#""WithDecryption":true"#) {
+                true => (200, parameter_response("secret", "plaintext")),
+                false => (200, parameter_response("secret", "ciphertext")),
+            });
+        let config = CacheConfig::new().default_with_decryption(true);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let decrypted = parameter_cache
+            .get_parameter("secret")
+            .send()
+            .await
+            .unwrap();
+        let encrypted = parameter_cache
+            .get_parameter("secret")
+            .with_decryption(false)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(decrypted, "plaintext");
+        assert_eq!(encrypted, "ciphertext");
+        assert_eq!(parameter_cache.cache.len(), 2);
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("secret", true))
+            .is_some());
+    }
+
+    #[test]
+    fn expired_keys() {
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(get_mock_ssm_client())
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        parameter_cache.insert(
+            CacheKey::new("fresh", false),
+            CacheItem::new("a".to_string(), 3600000000000),
+        );
+        parameter_cache.insert(
+            CacheKey::new("stale", false),
+            CacheItem::new("b".to_string(), 0),
+        );
+        parameter_cache.insert(
+            CacheKey::new("stale", true),
+            CacheItem::new("c".to_string(), 0),
+        );
+        clock.advance(Duration::from_millis(10));
+
+        assert_eq!(parameter_cache.expired_keys(), vec!["stale".to_string()]);
+        assert_eq!(parameter_cache.cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_force_refresh_or_stale() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, parameter_response("service/parameter", "value")),
+                _ => error_response("ThrottlingException"),
+            });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .force_refresh_or_stale()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        assert!(parameter_cache
+            .get_parameter("service/parameter")
+            .consistency(Consistency::Fresh)
+            .send()
+            .await
+            .is_err());
+        assert!(parameter_cache
+            .get_parameter("service/other")
+            .force_refresh_or_stale()
+            .send()
+            .await
+            .is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(parameter_cache.stats().stale_served, 1);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_caching_disabled() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache =
+            ParameterCache::new_with_config(mock_ssm_client, CacheConfig::disabled());
+
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(parameter_cache.cache.is_empty());
+    }
+
+    #[test]
+    fn is_near_capacity() {
+        let config = CacheConfig::new()
+            .max_cache_size(4)
+            .capacity_warn_fraction(0.75);
+        let mut parameter_cache = ParameterCache::new_with_config(get_mock_ssm_client(), config);
+        for name in ["a", "b", "c"] {
+            assert!(!parameter_cache.is_near_capacity());
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new(name.to_string(), 3600000000000),
+            );
+        }
+        assert!(parameter_cache.is_near_capacity());
+
+        parameter_cache.set_config(CacheConfig::new().max_cache_size(4));
+        assert!(!parameter_cache.is_near_capacity());
+    }
+
+    #[tokio::test]
+    async fn stats_count_expirations_and_evictions() {
+        let mock_ssm_client = mock_ssm_client(|_, _| (200, parameter_response("p", "value")));
+        let config = CacheConfig::new().max_cache_size(1);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        parameter_cache.insert(
+            CacheKey::new("p", false),
+            CacheItem::new("old".to_string(), 0),
+        );
+        parameter_cache.get_parameter("p").send().await.unwrap();
+        parameter_cache.insert(
+            CacheKey::new("other", false),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+        parameter_cache.invalidate("other");
+
+        let stats = parameter_cache.stats();
+        assert_eq!(
+            (stats.misses, stats.expirations, stats.evictions),
+            (1, 1, 1)
+        );
+        parameter_cache.reset_stats();
+        assert_eq!(parameter_cache.stats().evictions, 0);
+    }
+
+    #[tokio::test]
+    async fn stats_by_name() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("hot") {
+            true => (200, parameter_response("hot", "value")),
+            false => (200, parameter_response("cold", "value")),
+        });
+        let config = CacheConfig::new().stats_by_name(true);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        for _ in 0..3 {
+            parameter_cache.get_parameter("hot").send().await.unwrap();
+        }
+        parameter_cache
+            .get_parameter("cold")
+            .consistency(Consistency::Fresh)
+            .send()
+            .await
+            .unwrap();
+
+        let stats = parameter_cache.stats_by_name();
+        assert_eq!(
+            stats["hot"],
+            CacheStats {
+                hits: 2,
+                misses: 1,
+                fetches: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            stats["cold"],
+            CacheStats {
+                hits: 0,
+                misses: 1,
+                fetches: 1,
+                forced_refreshes: 1,
+                ..Default::default()
+            }
+        );
+        let totals = parameter_cache.stats();
+        assert_eq!(
+            totals,
+            CacheStats {
+                hits: 2,
+                misses: 2,
+                fetches: 2,
+                forced_refreshes: 1,
+                fetch_latency: totals.fetch_latency,
+                ..Default::default()
+            }
+        );
+        assert_eq!(totals.fetch_latency.count(), 2);
+
+        parameter_cache.set_config(CacheConfig::new());
+        parameter_cache.get_parameter("hot").send().await.unwrap();
+        assert!(parameter_cache.stats_by_name().is_empty());
+        assert_eq!(parameter_cache.stats().hits, 3);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_zero_ttl() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, parameter_response("service/parameter", "value")),
+                _ => error_response("ThrottlingException"),
+            });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .ttl(Duration::ZERO)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        // stored but already expired, so the next send fetches again and can fall back to it
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::Throttled { .. })));
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .force_refresh_or_stale()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn expiry_grace_spares_only_unexpired_entries() {
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(get_mock_ssm_client())
+            .config(CacheConfig::new().expiry_grace(Duration::from_secs(60)))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        for (name, ttl) in [("zero", 0), ("short", 5000000)] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new("value".to_string(), ttl),
+            );
+        }
+        clock.advance(Duration::from_millis(10));
+
+        assert_eq!(parameter_cache.expired_keys(), vec!["zero".to_string()]);
+    }
+
+    #[test]
+    fn jitter_moves_expiry_per_entry() {
+        let draws = Arc::new(AtomicUsize::new(0));
+        let config = CacheConfig::new().jitter(Duration::from_secs(60));
+        let mut parameter_cache = ParameterCache::new_with_config(get_mock_ssm_client(), config)
+            .with_jitter_source(move || draws.fetch_add(1, Ordering::SeqCst) as f64);
+        for name in ["early", "late"] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new("value".to_string(), 3600000000000),
+            );
+        }
+
+        let expires_at = |name| {
+            let cache_item = parameter_cache.cache.peek(&CacheKey::new(name, false));
+            cache_item.unwrap().expires_at()
+        };
+        let spread = expires_at("late")
+            .duration_since(expires_at("early"))
+            .unwrap();
+        assert!(spread >= Duration::from_secs(120) && spread < Duration::from_secs(121));
+    }
+
+    #[test]
+    fn next_eviction_candidate_is_least_recently_used() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        assert_eq!(parameter_cache.next_eviction_candidate(), None);
+        for name in ["a", "b"] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new("value".to_string(), 3600000000000),
+            );
+        }
+        assert_eq!(parameter_cache.next_eviction_candidate().unwrap(), "a");
+
+        parameter_cache.cache.get(&CacheKey::new("a", false));
+        assert_eq!(parameter_cache.next_eviction_candidate().unwrap(), "b");
+        assert_eq!(parameter_cache.next_eviction_candidate().unwrap(), "b");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_without_recency_updates() {
+        for (update_recency_on_hit, candidate) in [(true, "b"), (false, "a")] {
+            let config = CacheConfig::new().update_recency_on_hit(update_recency_on_hit);
+            let mut parameter_cache =
+                ParameterCache::new_with_config(get_mock_ssm_client(), config);
+            for name in ["a", "b"] {
+                parameter_cache.insert(
+                    CacheKey::new(name, false),
+                    CacheItem::new("value".to_string(), 3600000000000),
+                );
+            }
+
+            let value = parameter_cache.get_parameter("a").send().await.unwrap();
+            assert_eq!(value, "value");
+            assert_eq!(
+                parameter_cache.next_eviction_candidate().unwrap(),
+                candidate
+            );
+        }
+    }
+
+    #[test]
+    fn set_config_keeps_entries_and_resizes() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        for name in ["a", "b", "c"] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new(name.to_string(), 3600000000000),
+            );
+        }
+
+        parameter_cache.set_config(CacheConfig::new().max_cache_size(2).cache_item_ttl(0));
+
+        assert_eq!(parameter_cache.config().max_cache_size, 2);
+        assert_eq!(parameter_cache.cache.len(), 2);
+        assert_eq!(parameter_cache.memory_bytes, 2);
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("a", false))
+            .is_none());
+        // entries keep the expiry they were stored with
+        assert!(parameter_cache.expired_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_parameter_evict_after_failed_refreshes() {
+        let mock_ssm_client = mock_ssm_client(|_, _| error_response("ThrottlingException"));
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(mock_ssm_client)
+            .config(CacheConfig::new().evict_after_failed_refreshes(2))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let key = CacheKey::new("service/parameter", false);
+        parameter_cache.insert(key.clone(), CacheItem::new("stale".to_string(), 0));
+        clock.advance(Duration::from_millis(10));
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .force_refresh_or_stale()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "stale");
+        assert_eq!(
+            parameter_cache.cache.peek(&key).unwrap().failed_refreshes,
+            1
+        );
+
+        assert!(parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .is_err());
+        assert!(parameter_cache.cache.peek(&key).is_none());
+        assert_eq!(parameter_cache.memory_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_send_cow() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send_cow()
+            .await
+            .unwrap();
+        assert!(matches!(value, Cow::Owned(v) if v == "value"));
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send_cow()
+            .await
+            .unwrap();
+        assert!(matches!(value, Cow::Borrowed("value")));
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send_arc()
+            .await
+            .unwrap();
+        assert_eq!(&*value, "value");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_account() {
+        let default_client =
+            mock_ssm_client(|_, _| (200, parameter_response("shared", "default-value")));
+        let account_client =
+            mock_ssm_client(|_, _| (200, parameter_response("shared", "account-value")));
+        let mut parameter_cache = ParameterCache::new(default_client)
+            .with_account_client("shared-services", account_client);
+
+        let value = parameter_cache
+            .get_parameter("shared")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "default-value");
+        let value = parameter_cache
+            .get_parameter("shared")
+            .account("shared-services")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "account-value");
+        assert_eq!(parameter_cache.cache.len(), 2);
+
+        match parameter_cache
+            .get_parameter("shared")
+            .account("unknown")
+            .send()
+            .await
+        {
+            Err(CacheError::UnknownAccount { account }) => assert_eq!(account, "unknown"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_parameter_tenant() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            let value = match body.contains("/app/prod/acme/db") {
+                true => "acme-db",
+                false => "globex-db",
+            };
+            (200, parameter_response("db", value))
+        });
+        let config = CacheConfig::new().environment("prod");
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        for (tenant, expected) in [("acme", "acme-db"), ("globex", "globex-db")] {
+            let value = parameter_cache
+                .get_parameter("/app/{env}/{tenant}/db")
+                .tenant(tenant)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, expected);
+        }
+        let key = CacheKey::new("/app/prod/acme/db", false).with_tenant(Some("acme".to_string()));
+        assert_eq!(parameter_cache.cache.peek(&key).unwrap().value, "acme-db");
+        assert_eq!(parameter_cache.cache.len(), 2);
+
+        let result = parameter_cache
+            .get_parameter("/app/{tenant}/db")
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::InvalidName { .. })));
+    }
+
+    #[test]
+    fn is_stale() {
+        let config = CacheConfig::new().refresh_ahead(Duration::from_secs(60));
+        let mut parameter_cache = ParameterCache::new_with_config(get_mock_ssm_client(), config);
+        parameter_cache.insert(
+            CacheKey::new("fresh", false),
+            CacheItem::new("a".to_string(), 3600000000000),
+        );
+        parameter_cache.insert(
+            CacheKey::new("stale", false),
+            CacheItem::new("b".to_string(), 30000000000),
+        );
+
+        assert!(!parameter_cache.is_stale("fresh"));
+        assert!(parameter_cache.is_stale("stale"));
+        assert!(!parameter_cache.is_stale("missing"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn get_parameter_max_fetch_rate() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let config = CacheConfig::new().max_fetch_rate(1.0);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+        let start = tokio::time::Instant::now();
+
+        for _ in 0..3 {
+            parameter_cache
+                .get_parameter("service/parameter")
+                .consistency(Consistency::Fresh)
+                .send()
+                .await
+                .unwrap();
+        }
+        // cache hits don't wait
+        parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(2));
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn get_parameter_customize() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(body.contains(r#""Name":"service/parameter:3""#));
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .customize(|request| request.name("service/parameter:3"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("service/parameter", false))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn get_parameter_keys_by_requested_selector() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            assert!(body.contains(r#""Name":"service/parameter:prod""#));
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("service/parameter:prod")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("service/parameter:prod", false))
+            .is_some());
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("service/parameter", false))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn get_parameter_pinned_version() {
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            assert!(body.contains(r#""Name":"service/parameter:3""#));
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let config = CacheConfig::new().pinned_version_ttl(Duration::from_secs(86400));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .version(3)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        let cache_item = parameter_cache
+            .cache
+            .peek(&CacheKey::new("service/parameter:3", false))
+            .unwrap();
+        assert!(!cache_item.expires_within(Duration::from_secs(7200).as_nanos()));
+        assert_eq!(pinned_version("service/parameter:prod"), None);
+        assert_eq!(
+            pinned_version("arn:aws:ssm:eu-west-1:123456789012:parameter/service/parameter"),
+            None
+        );
+    }
+
+    #[test]
+    fn unbounded_cache_never_evicts() {
+        let config = CacheConfig::new().unbounded();
+        let mut parameter_cache = ParameterCache::new_with_config(get_mock_ssm_client(), config);
+        for i in 0..2000 {
+            parameter_cache.insert(
+                CacheKey::new(format!("service/parameter-{}", i), false),
+                CacheItem::new("value".to_string(), 3600000000000),
+            );
+        }
+        assert_eq!(parameter_cache.len(), 2000);
+        assert_eq!(parameter_cache.stats().evictions, 0);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_retries_throttling() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            match body.contains("missing") {
+                true => error_response("ParameterNotFound"),
+                false if call < 2 => error_response("ThrottlingException"),
+                false => (200, parameter_response("service/parameter", "value")),
+            }
+        });
+        let config = CacheConfig::new()
+            .max_retries(2)
+            .base_backoff(Duration::from_millis(1));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let result = parameter_cache.get_parameter("missing").send().await;
+        assert!(result.unwrap_err().is_not_found());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn random_fraction_is_spread() {
+        let fractions: Vec<f64> = (0..100).map(|_| random_fraction()).collect();
+        assert!(fractions
+            .iter()
+            .all(|fraction| (0.0..1.0).contains(fraction)));
+        assert!(fractions.iter().any(|fraction| *fraction < 0.5));
+        assert!(fractions.iter().any(|fraction| *fraction >= 0.5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn get_parameter_retry_backoff_is_jittered() {
+        for (jitter, expected) in [(0.0, 1500), (1.0, 3000)] {
+            let mock_ssm_client = mock_ssm_client(|_, _| error_response("ThrottlingException"));
+            let config = CacheConfig::new()
+                .max_retries(2)
+                .base_backoff(Duration::from_secs(1));
+            let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config)
+                .with_jitter_source(move || jitter);
+
+            let started = tokio::time::Instant::now();
+            let result = parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await;
+            assert!(matches!(result, Err(CacheError::Throttled { .. })));
+            assert_eq!(started.elapsed(), Duration::from_millis(expected));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn get_parameter_retry_waits_are_capped_in_total() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            error_response("ThrottlingException")
+        });
+        let config = CacheConfig::new()
+            .max_retries(100)
+            .base_backoff(Duration::from_secs(1));
+        let mut parameter_cache =
+            ParameterCache::new_with_config(mock_ssm_client, config).with_jitter_source(|| 1.0);
+
+        let started = tokio::time::Instant::now();
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::Throttled { .. })));
+        // waits of 1, 2, 4 and 8 seconds, then 5 to reach the 20 second total
+        assert_eq!(started.elapsed(), Duration::from_secs(20));
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_retries_kms_throttling() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            error_response("KMSThrottlingException")
+        });
+        let config = CacheConfig::new()
+            .max_retries(2)
+            .base_backoff(Duration::from_millis(1));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .with_decryption(true)
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::Throttled { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_label() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(body.contains(r#""Name":"service/parameter:prod""#));
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .label("prod")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        assert!(parameter_cache.contains("service/parameter:prod"));
+        assert!(!parameter_cache.contains("service/parameter"));
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .version(3)
+            .label("prod")
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::InvalidName { .. })));
+    }
+
+    #[tokio::test]
+    async fn get_parameter_error_cache_ttl() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            error_response("AccessDeniedException")
+        });
+        let config = CacheConfig::new().error_cache_ttl(Duration::from_secs(60));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let first = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await;
+        assert!(!matches!(first, Err(CacheError::RecentFailure { .. })));
+        let second = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await;
+        assert!(matches!(second, Err(CacheError::RecentFailure { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let refreshed = parameter_cache
+            .get_parameter("service/parameter")
+            .consistency(Consistency::Fresh)
+            .send()
+            .await;
+        assert!(!matches!(refreshed, Err(CacheError::RecentFailure { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_error_cache_ttl_keeps_not_found() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match body.contains("service/old") {
+                true => (200, parameter_response("service/old", "old-value")),
+                false => error_response("ParameterNotFound"),
+            }
+        });
+        let config = CacheConfig::new()
+            .error_cache_ttl(Duration::from_secs(60))
+            .cache_item_ttl(0);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("missing")
+                .optional()
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, None);
+        }
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("service/new")
+                .fallback_name("service/old")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, "old-value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_negative_ttl() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match body.contains("throttled") {
+                true => error_response("ThrottlingException"),
+                false => error_response("ParameterNotFound"),
+            }
+        });
+        let config = CacheConfig::new().negative_ttl(Duration::from_secs(60));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        for _ in 0..2 {
+            let result = parameter_cache.get_parameter("missing").send().await;
+            assert!(result.unwrap_err().is_not_found());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let read = parameter_cache.get_parameter("missing");
+        let refreshed = read.consistency(Consistency::Fresh).send().await;
+        assert!(refreshed.unwrap_err().is_not_found());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // only not found errors are remembered
+        for _ in 0..2 {
+            let result = parameter_cache.get_parameter("throttled").send().await;
+            assert!(matches!(result, Err(CacheError::Throttled { .. })));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn failure_windows_read_the_clock() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match body.contains("missing") {
+                true => error_response("ParameterNotFound"),
+                false => error_response("AccessDeniedException"),
+            }
+        });
+        let config = CacheConfig::new()
+            .error_cache_ttl(Duration::from_secs(60))
+            .negative_ttl(Duration::from_secs(60))
+            .breaker_threshold(2)
+            .breaker_cooldown(Duration::from_secs(30));
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(mock_ssm_client)
+            .config(config)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        for name in ["denied", "denied", "missing", "missing"] {
+            let _ = parameter_cache.get_parameter(name).send().await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        clock.advance(Duration::from_secs(61));
+        for name in ["missing", "denied"] {
+            let _ = parameter_cache.get_parameter(name).send().await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        let _ = parameter_cache.get_parameter("other").send().await;
+        assert_eq!(parameter_cache.stats().breaker, BreakerState::Open);
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(parameter_cache.stats().breaker, BreakerState::HalfOpen);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_circuit_breaker() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, parameter_response("service/parameter", "value")),
+                _ => error_response("AccessDeniedException"),
+            });
+        let config = CacheConfig::new().breaker_threshold(2).cache_item_ttl(0);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+        parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let result = parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await;
+            assert!(matches!(result, Err(CacheError::AccessDenied { .. })));
+        }
+        assert_eq!(parameter_cache.stats().breaker, BreakerState::Open);
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::CircuitOpen { .. })));
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .force_refresh_or_stale()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn touch_extends_expiry() {
+        let mock_ssm_client = mock_ssm_client(|_, _| panic!("touched entries aren't fetched"));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache.cache.put(
+            CacheKey::new("service/parameter", false),
+            CacheItem::new("value".to_string(), 0),
+        );
+
+        assert!(parameter_cache.touch("service/parameter", Duration::from_secs(60)));
+        assert!(!parameter_cache.touch("missing", Duration::from_secs(60)));
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_fallback_name() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match body {
+                body if body.contains("service/new") => error_response("ParameterNotFound"),
+                body if body.contains("service/throttled") => error_response("ThrottlingException"),
+                _ => (200, parameter_response("service/old", "old-value")),
+            }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let value = parameter_cache
+                .get_parameter("service/new")
+                .fallback_name("service/old")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(value, "old-value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(parameter_cache.stats().fallback_used, 1);
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("service/new", false))
+            .is_some());
+
+        let result = parameter_cache
+            .get_parameter("service/throttled")
+            .fallback_name("service/old")
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::Throttled { .. })));
+    }
+
+    #[cfg(feature = "checksum")]
+    #[tokio::test]
+    async fn get_parameter_expect_sha256() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "hello")));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .expect_sha256(&"0".repeat(64))
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::ChecksumMismatch { .. })));
+        assert_eq!(parameter_cache.cache.len(), 0);
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .expect_sha256("2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "hello");
+        assert_eq!(parameter_cache.cache.len(), 1);
+    }
+
+    #[cfg(feature = "checksum")]
+    #[tokio::test]
+    async fn send_cow_and_send_raw_expect_sha256() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "hello")));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .expect_sha256(&"0".repeat(64))
+            .send_raw()
+            .await;
+        assert!(matches!(result, Err(CacheError::ChecksumMismatch { .. })));
+        assert_eq!(parameter_cache.cache.len(), 0);
+
+        parameter_cache.insert(
+            CacheKey::new("service/parameter", false),
+            CacheItem::new("hello".to_string(), 3600000000000),
+        );
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .expect_sha256(&"0".repeat(64))
+            .send_cow()
+            .await;
+        assert!(matches!(result, Err(CacheError::ChecksumMismatch { .. })));
+    }
+
+    #[tokio::test]
+    async fn get_parameter_invalid_name() {
+        let mock_ssm_client = mock_ssm_client(|_, _| panic!("invalid names never reach AWS"));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let result = parameter_cache.get_parameter("service param").send().await;
+        assert!(matches!(result, Err(CacheError::InvalidName { .. })));
+    }
+
+    #[tokio::test]
+    async fn get_parameter_send_timestamped() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let start = SystemTime::now();
+
+        let fetched = parameter_cache
+            .get_parameter("service/parameter")
+            .send_timestamped()
+            .await
+            .unwrap();
+        let cached = parameter_cache
+            .get_parameter("service/parameter")
+            .send_timestamped()
+            .await
+            .unwrap();
+
+        assert_eq!(fetched.value, "value");
+        assert!(fetched.fetched_at >= start);
+        assert_eq!(fetched.version, Some(1));
+        assert_eq!(cached.value, "value");
+        assert_eq!(cached.version, Some(1));
+        assert!(cached.fetched_at <= SystemTime::now());
+    }
+
+    #[tokio::test]
+    async fn send_timestamped_reads_the_clock() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(mock_ssm_client)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let start = clock.now();
+
+        let fetched = parameter_cache
+            .get_parameter("service/parameter")
+            .send_timestamped()
+            .await
+            .unwrap();
+        clock.advance(Duration::from_secs(30));
+        let cached = parameter_cache
+            .get_parameter("service/parameter")
+            .send_timestamped()
+            .await
+            .unwrap();
+        assert_eq!(fetched.fetched_at, start);
+        assert!(cached.fetched_at < start + Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn get_parameter_max_value_bytes() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "0123456789")));
+        let config = CacheConfig::new().max_value_bytes(4);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+        let key = CacheKey::new("service/parameter", false);
+        parameter_cache.insert(key.clone(), CacheItem::new("old".to_string(), 0));
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "0123456789");
+        assert!(parameter_cache.cache.peek(&key).is_none());
+
+        parameter_cache
+            .get_parameter("service/parameter")
+            .allow_large()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            parameter_cache.cache.peek(&key).unwrap().value,
+            "0123456789"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_parameter_send_with_source() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, parameter_response("service/parameter", "value")),
+                _ => error_response("ThrottlingException"),
+            });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let mut sources = Vec::new();
+        for force_refresh in [false, false, true] {
+            let mut builder = parameter_cache.get_parameter("service/parameter");
+            if force_refresh {
+                builder = builder.force_refresh_or_stale();
+            }
+            let (value, source) = builder.send_with_source().await.unwrap();
+            assert_eq!(value, "value");
+            sources.push(source);
+        }
+        assert_eq!(
+            sources,
+            vec![ValueSource::Aws, ValueSource::Cache, ValueSource::Stale]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_parameter_validate() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "not json")));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let key = CacheKey::new("service/parameter", false);
+        parameter_cache.insert(key.clone(), CacheItem::new("{}".to_string(), 0));
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .validate(|value| match value.starts_with('{') {
+                true => Ok(()),
+                false => Err("not a JSON object"),
+            })
+            .send()
+            .await;
+        match result {
+            Err(CacheError::ValidationFailed { name, message }) => {
+                assert_eq!(name, "service/parameter");
+                assert_eq!(message, "not a JSON object");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(parameter_cache.cache.peek(&key).unwrap().value, "{}");
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .validate(|value| match value.starts_with('{') {
+                true => Ok(()),
+                false => Err("not a JSON object"),
+            })
+            .send_raw()
+            .await;
+        assert!(matches!(result, Err(CacheError::ValidationFailed { .. })));
+        assert_eq!(parameter_cache.cache.peek(&key).unwrap().value, "{}");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_map_on_read() {
+        let mock_ssm_client = mock_ssm_client(|_, _| {
+            (
+                200,
+                parameter_response("service/url", "https://{region}.example.com"),
+            )
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let url = parameter_cache
+                .get_parameter("service/url")
+                .map(|value| value.replace("{region}", "ap-southeast-2"))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(url, "https://ap-southeast-2.example.com");
+        }
+        let raw = parameter_cache.get_parameter("service/url").send_cow();
+        assert_eq!(raw.await.unwrap(), "https://{region}.example.com");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_with_metadata() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, _| {
+            match operation {
+            "DescribeParameters" => (
+                200,
+                r#"{"Parameters":[{"Name":"service/parameter","Tier":"Advanced","KeyId":"alias/aws/ssm"}]}"#
+                    .to_string(),
+            ),
+            _ => {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (
+                    200,
+                    r#"{"Parameter":{"Name":"service/parameter","Value":"value","Version":3,"Type":"String",
+                        "LastModifiedDate":1700000000,
+                        "ARN":"arn:aws:ssm:us-east-1:123456789012:parameter/service/parameter"}}"#
+                        .to_string(),
+                )
+            }
+        }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let read = parameter_cache
+                .get_parameter("service/parameter")
+                .with_tier()
+                .with_key_id();
+            let cached_parameter = read.send_with_metadata().await.unwrap();
+            assert_eq!(
+                cached_parameter,
+                CachedParameter {
+                    value: "value".to_string(),
+                    version: Some(3),
+                    parameter_type: Some(ParameterType::String),
+                    last_modified: Some(
+                        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+                    ),
+                    arn: Some(
+                        "arn:aws:ssm:us-east-1:123456789012:parameter/service/parameter"
+                            .to_string()
+                    ),
+                    tier: Some(ParameterTier::Advanced),
+                    key_id: Some("alias/aws/ssm".to_string()),
+                }
+            );
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_with_generation() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            let value = ["a", "a", "b"][counter.fetch_add(1, Ordering::SeqCst).min(2)];
+            (200, parameter_response("service/parameter", value))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let read = parameter_cache.get_parameter("service/parameter");
+        assert_eq!(
+            read.send_with_generation().await.unwrap(),
+            ("a".to_string(), 0)
+        );
+        for expected in [("a", 0), ("b", 1)] {
+            let read = parameter_cache
+                .get_parameter("service/parameter")
+                .consistency(Consistency::Fresh);
+            let (value, generation) = read.send_with_generation().await.unwrap();
+            assert_eq!((&*value, generation), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn get_string_list_checks_type() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            let parameter_type = match body.contains("hosts") {
+                true => "StringList",
+                false => "String",
+            };
+            (
+                200,
+                format!(
+                    r#"{{"Parameter":{{"Name":"p","Value":"a,b","Type":"{}"}}}}"#,
+                    parameter_type
+                ),
+            )
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let hosts = parameter_cache.get_string_list("hosts").await.unwrap();
+            assert_eq!(hosts, vec!["a", "b"]);
+        }
+        assert!(matches!(
+            parameter_cache.get_string_list("greeting").await,
+            Err(CacheError::WrongType {
+                actual: ParameterType::String,
+                ..
+            })
+        ));
+        assert_eq!(
+            parameter_cache.get_parameter_type("hosts"),
+            Some(ParameterType::StringList)
+        );
+        assert_eq!(
+            parameter_cache.get_parameter_type("greeting"),
+            Some(ParameterType::String)
+        );
+        assert_eq!(parameter_cache.get_parameter_type("missing"), None);
+    }
+
+    #[tokio::test]
+    async fn get_typed() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("port") {
+            true => (200, parameter_response("service/port", "5432")),
+            false => (200, parameter_response("service/host", "db.internal")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let port = parameter_cache
+            .get_typed("service/port", str::parse::<u16>)
+            .await
+            .unwrap();
+        assert_eq!(port, 5432);
+        let result = parameter_cache
+            .get_typed("service/host", str::parse::<u16>)
+            .await;
+        assert!(matches!(result, Err(CacheError::Parse { name, .. }) if name == "service/host"));
+    }
+
+    #[tokio::test]
+    async fn get_parameter_empty_as_not_found() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "")));
+        let config = CacheConfig::new().empty_as_not_found(true);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await;
+        assert!(result.unwrap_err().is_not_found());
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .optional()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, None);
+        assert_eq!(parameter_cache.cache.len(), 0);
+
+        parameter_cache.set_config(CacheConfig::new());
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "");
+    }
+
+    #[tokio::test]
+    async fn get_parameter_synthetic() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "fresh"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache.insert(
+            CacheKey::new("service/parameter", false),
+            CacheItem::new("cached".to_string(), 3600000000000),
+        );
+
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .synthetic()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "fresh");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            parameter_cache
+                .cache
+                .peek(&CacheKey::new("service/parameter", false))
+                .unwrap()
+                .value,
+            "cached"
+        );
+        assert_eq!(
+            parameter_cache.stats(),
+            CacheStats {
+                synthetic: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn get_parameter_with_both() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match body.contains(r#""WithDecryption":true"#) {
+                true => (200, parameter_response("secret", "plaintext")),
+                false => (200, parameter_response("secret", "ciphertext")),
+            }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let forms = parameter_cache
+                .get_parameter("secret")
+                .with_both()
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(
+                forms,
+                ParameterForms {
+                    encrypted: "ciphertext".to_string(),
+                    decrypted: "plaintext".to_string()
+                }
+            );
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(parameter_cache.cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_normalize_names() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            assert!(body.contains(r#""Name":"/service/param""#), "{}", body);
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("/service/param", "value"))
+        });
+        let config = CacheConfig::new().normalize_names(true);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        for name in ["service/param", "/service/param", "//service//param"] {
+            assert_eq!(
+                parameter_cache.get_parameter(name).send().await.unwrap(),
+                "value"
+            );
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(parameter_cache.peek("service//param").unwrap(), "value");
+        assert!(parameter_cache.contains("service/param"));
+        assert!(parameter_cache
+            .get_parameter_age("//service/param")
+            .is_some());
+        assert!(parameter_cache.invalidate("service//param"));
+        assert!(parameter_cache.cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_parameter_environment_placeholder() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(body.contains(r#""Name":"/service/staging/db""#), "{}", body);
+            (200, parameter_response("/service/staging/db", "value"))
+        });
+        let config = CacheConfig::new().environment("staging");
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        assert_eq!(
+            parameter_cache
+                .get_parameter("/service/{env}/db")
+                .send()
+                .await
+                .unwrap(),
+            "value"
+        );
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("/service/staging/db", false))
+            .is_some());
+        assert!(parameter_cache.contains("/service/{env}/db"));
+        assert!(parameter_cache.invalidate("/service/{env}/db"));
+    }
+
+    #[tokio::test]
+    async fn get_fresher_than_refreshes_old_values() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let now = SystemTime::now();
+        parameter_cache.insert(
+            CacheKey::new("service/parameter", false),
+            CacheItem::restored(
+                "cached".to_string(),
+                now - Duration::from_secs(120),
+                now + Duration::from_secs(3600),
+            ),
+        );
+
+        let max_age = Duration::from_secs(300);
+        let value = parameter_cache.get_fresher_than("service/parameter", max_age);
+        assert_eq!(value.send().await.unwrap(), "cached");
+
+        let max_age = Duration::from_secs(60);
+        for _ in 0..2 {
+            let value = parameter_cache.get_fresher_than("service/parameter", max_age);
+            assert_eq!(value.send().await.unwrap(), "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_min_version() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("rolled-back") {
+            true => (
+                200,
+                r#"{"Parameter":{"Name":"rolled-back","Type":"String","Value":"old","Version":2}}"#
+                    .to_string(),
+            ),
+            false => (
+                200,
+                r#"{"Parameter":{"Name":"current","Type":"String","Value":"new","Version":5}}"#
+                    .to_string(),
+            ),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache.insert(
+            CacheKey::new("current", false),
+            CacheItem::new("unversioned".to_string(), 3600000000000),
+        );
+
+        // the cached value's version is unknown, so it is fetched
+        let value = parameter_cache
+            .get_parameter("current")
+            .min_version(5)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "new");
+        assert_eq!(
+            parameter_cache
+                .cache
+                .peek(&CacheKey::new("current", false))
+                .unwrap()
+                .version,
+            Some(5)
+        );
+
+        let result = parameter_cache
+            .get_parameter("rolled-back")
+            .min_version(3)
+            .send()
+            .await;
+        match result {
+            Err(CacheError::StaleVersion {
+                version,
+                min_version,
+                ..
+            }) => assert_eq!((version, min_version), (2, 3)),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("rolled-back", false))
+            .is_none());
+    }
+
+    #[test]
+    fn get_parameter_age() {
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(get_mock_ssm_client())
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        parameter_cache.insert(
+            CacheKey::new("fresh", false),
+            CacheItem::new("v".to_string(), 3600000000000),
+        );
+        parameter_cache.insert(
+            CacheKey::new("expired", false),
+            CacheItem::new("v".to_string(), 0),
+        );
+        clock.advance(Duration::from_secs(30));
+
+        let age = parameter_cache.get_parameter_age("fresh").unwrap();
+        assert!(age >= Duration::from_secs(30) && age < Duration::from_secs(31));
+        assert_eq!(parameter_cache.get_parameter_age("expired"), None);
+        assert_eq!(parameter_cache.get_parameter_age("missing"), None);
+    }
+
+    #[test]
+    fn peek_does_not_touch_recency() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        parameter_cache.insert(
+            CacheKey::new("first", false),
+            CacheItem::new("one".to_string(), 3600000000000),
+        );
+        parameter_cache.insert(
+            CacheKey::new("second", false),
+            CacheItem::new("two".to_string(), 3600000000000),
+        );
+        parameter_cache.insert(
+            CacheKey::new("expired", false),
+            CacheItem::new("v".to_string(), 0),
+        );
+
+        assert_eq!(parameter_cache.peek("first").as_deref(), Some("one"));
+        assert!(parameter_cache.contains("second"));
+        assert!(!parameter_cache.contains("expired"));
+        assert_eq!(parameter_cache.peek("missing"), None);
+        assert_eq!(
+            parameter_cache.next_eviction_candidate().as_deref(),
+            Some("first")
+        );
+        assert_eq!(parameter_cache.len(), 3);
+        assert_eq!(
+            parameter_cache.capacity(),
+            Some(parameter_cache.config().max_cache_size)
+        );
+    }
+
+    #[tokio::test]
+    async fn get_parameter_also_cache_as() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("/real/name", "value"))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        parameter_cache
+            .get_parameter("/real/name")
+            .also_cache_as(&["db-host", "legacy/host"])
+            .send()
+            .await
+            .unwrap();
+        for alias in ["db-host", "legacy/host"] {
+            assert_eq!(
+                parameter_cache.get_parameter(alias).send().await.unwrap(),
+                "value"
+            );
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert!(parameter_cache.invalidate("/real/name"));
+        assert!(parameter_cache.cache.is_empty());
+        assert!(parameter_cache.aliases.is_empty());
+    }
+
+    #[test]
+    fn retain_evicts_rejected_entries() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        for (name, value) in [("/a/x", "keep"), ("/a/y", "drop"), ("/b/x", "keep")] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new(value.to_string(), 3600000000000),
+            );
+        }
+
+        let evicted =
+            parameter_cache.retain(|name, value| !name.starts_with("/a/") || value == "keep");
+
+        assert_eq!(evicted, 1);
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("/a/y", false))
+            .is_none());
+        assert_eq!(parameter_cache.cache.len(), 2);
+        assert_eq!(parameter_cache.memory_bytes, 8);
+    }
+
+    #[test]
+    fn clear_empties_the_cache() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        for name in ["a", "b"] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new("value".to_string(), 3600000000000),
+            );
+        }
+
+        assert_eq!(parameter_cache.clear(), 2);
+        assert!(parameter_cache.cache.is_empty());
+        assert_eq!(parameter_cache.memory_bytes, 0);
+        assert_eq!(parameter_cache.clear(), 0);
+    }
+
+    #[test]
+    fn invalidate_by_arn() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        for name in [
+            "/myapp/db/host",
+            "/myapp/db/host:2",
+            "/myapp/db/hostname",
+            "standard",
+        ] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new("v".to_string(), 3600000000000),
+            );
+        }
+        parameter_cache.insert(
+            CacheKey::new("standard", true),
+            CacheItem::new("v".to_string(), 3600000000000),
+        );
+
+        assert!(parameter_cache
+            .invalidate_by_arn("arn:aws:ssm:us-east-1:123456789012:parameter/myapp/db/host"));
+        assert!(parameter_cache
+            .invalidate_by_arn("arn:aws:ssm:us-east-1:123456789012:parameter/standard"));
+        assert!(!parameter_cache
+            .invalidate_by_arn("arn:aws:ssm:us-east-1:123456789012:parameter/standard"));
+        assert!(!parameter_cache.invalidate_by_arn("arn:aws:s3:::bucket/standard"));
+
+        let names: Vec<&str> = parameter_cache
+            .cache
+            .iter()
+            .map(|(key, _)| key.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["/myapp/db/hostname"]);
+        assert_eq!(parameter_cache.memory_bytes, 1);
+    }
+
+    #[test]
+    fn memory_estimate() {
+        let mut parameter_cache = ParameterCache::new(get_mock_ssm_client());
+        assert_eq!(parameter_cache.memory_estimate(), 0);
+
+        parameter_cache.insert(
+            CacheKey::new("name", false),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+        parameter_cache.insert(
+            CacheKey::new("name", false).with_account(Some("acct".to_string())),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+
+        assert_eq!(
+            parameter_cache.memory_estimate(),
+            2 * ENTRY_OVERHEAD_BYTES + 9 + 13
+        );
+    }
+
+    #[tokio::test]
+    async fn get_parameter_optional() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            if body.contains("missing") {
+                error_response("ParameterNotFound")
+            } else if body.contains("throttled") {
+                error_response("ThrottlingException")
+            } else {
+                (200, parameter_response("present", "value"))
+            }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let value = parameter_cache
+            .get_parameter("present")
+            .optional()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value.as_deref(), Some("value"));
+        let value = parameter_cache
+            .get_parameter("missing")
+            .optional()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, None);
+        assert!(parameter_cache
+            .get_parameter("throttled")
+            .optional()
+            .send()
+            .await
+            .is_err());
+        assert!(parameter_cache
+            .get_parameter("missing")
+            .send()
+            .await
+            .unwrap_err()
+            .is_not_found());
+    }
+
+    #[tokio::test]
+    async fn get_parameter_dry_run() {
+        let mock_ssm_client = mock_ssm_client(|_, _| panic!("dry run must not call AWS"));
+        let mut parameter_cache = ParameterCache::new_with_config(
+            mock_ssm_client.clone(),
+            CacheConfig::new()
+                .dry_run(true)
+                .dry_run_placeholder("placeholder"),
+        );
+        let value = parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "placeholder");
+
+        let mut parameter_cache =
+            ParameterCache::new_with_config(mock_ssm_client, CacheConfig::new().dry_run(true));
+        match parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+        {
+            Err(CacheError::DryRun { name }) => assert_eq!(name, "service/parameter"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_parameter_max_memory_bytes() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            let name = ["small", "medium", "large"]
+                .into_iter()
+                .find(|name| body.contains(name))
+                .unwrap();
+            let value = match name {
+                "small" => "1",
+                "medium" => "123456",
+                _ => "1234567890123",
+            };
+            (200, parameter_response(name, value))
+        });
+        let config = CacheConfig::new().max_memory_bytes(10);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        parameter_cache.get_parameter("small").send().await.unwrap();
+        parameter_cache
+            .get_parameter("medium")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(parameter_cache.memory_bytes, 7);
+
+        // a second medium value would exceed the budget, evicting the least recently used
+        parameter_cache.get_parameter("small").send().await.unwrap();
+        parameter_cache.insert(
+            CacheKey::new("medium-2", false),
+            CacheItem::new("123456".to_string(), 0),
+        );
+        assert_eq!(parameter_cache.memory_bytes, 7);
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("medium", false))
+            .is_none());
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("small", false))
+            .is_some());
+
+        // a value larger than the whole budget is not kept
+        parameter_cache.get_parameter("large").send().await.unwrap();
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("large", false))
+            .is_none());
+        assert!(parameter_cache.memory_bytes <= 10);
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn new_with_config_from_ssm() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            if body.contains("missing") {
+                error_response("ParameterNotFound")
+            } else {
+                (
+                    200,
+                    parameter_response("config", r#"{\"max_cache_size\": 7}"#),
+                )
+            }
+        });
+
+        let parameter_cache =
+            ParameterCache::new_with_config_from_ssm(mock_ssm_client.clone(), "config")
+                .await
+                .unwrap();
+        assert_eq!(parameter_cache.config().max_cache_size, 7);
+
+        let parameter_cache = ParameterCache::new_with_config_from_ssm(mock_ssm_client, "missing")
+            .await
+            .unwrap();
+        assert_eq!(parameter_cache.config().max_cache_size, 1024);
+    }
+
+    #[tokio::test]
+    async fn get_parameter_access_denied() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => error_response("AccessDeniedException"),
+                _ => (200, parameter_response("service/parameter", "value")),
+            });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        match parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+        {
+            Err(CacheError::AccessDenied { name, .. }) => assert_eq!(name, "service/parameter"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(parameter_cache.is_degraded());
+
+        parameter_cache
+            .get_parameter("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert!(!parameter_cache.is_degraded());
+    }
+
+    // an unbounded store used to check that the cache works with any CacheStore
+    struct MapStore<K, V>(HashMap<K, V>);
+
+    impl<K, V> Default for MapStore<K, V> {
+        fn default() -> Self {
+            MapStore(HashMap::new())
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V> CacheStore<K, V> for MapStore<K, V> {
+        fn get(&mut self, key: &K) -> Option<&V> {
+            self.0.get(key)
+        }
+
+        fn peek(&self, key: &K) -> Option<&V> {
+            self.0.get(key)
+        }
+
+        fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+            self.0
+                .insert(key.clone(), value)
+                .map(|previous| (key, previous))
+        }
+
+        fn pop(&mut self, key: &K) -> Option<V> {
+            self.0.remove(key)
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a> {
+            Box::new(self.0.iter())
+        }
+    }
+
+    #[tokio::test]
+    async fn get_parameter_deadline_exceeded() {
+        let mut parameter_cache = ParameterCache::new(never_responding_ssm_client());
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .deadline(Instant::now() + Duration::from_millis(50))
+            .send()
+            .await;
+
+        match result {
+            Err(CacheError::DeadlineExceeded { name }) => assert_eq!(name, "service/parameter"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "cancellation")]
+    #[tokio::test]
+    async fn get_parameter_cancelled() {
+        let mut parameter_cache = ParameterCache::new(never_responding_ssm_client());
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+        let canceller = cancel_token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let result = parameter_cache
+            .get_parameter("service/parameter")
+            .cancel_token(cancel_token)
+            .send()
+            .await;
+
+        match result {
+            Err(CacheError::Cancelled { name }) => assert_eq!(name, "service/parameter"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(parameter_cache.cache.is_empty());
     }
 
     // provides a mocked AWS SSM client for testing
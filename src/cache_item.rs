@@ -1,15 +1,57 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[derive(Clone, Copy)]
+use aws_sdk_ssm::types::{Parameter, ParameterTier, ParameterType};
+
+use super::clock::{system_clock, Clock};
+
+#[derive(Clone)]
 /// Stores a cached item value with an expiry TTL.
 pub struct CacheItem<T> {
     /// The item value stored in the cache.
     pub value: T,
 
+    /// The tier (Standard, Advanced or Intelligent-Tiering) of the cached parameter, if known.
+    pub tier: Option<ParameterTier>,
+
+    /// The ID of the KMS key that encrypts the cached SecureString parameter, if known.
+    pub key_id: Option<String>,
+
+    /// The version of the cached parameter, if known.
+    pub version: Option<i64>,
+
+    /// The data type of the cached parameter, "text" or e.g. "aws:ec2:image", if known.
+    pub data_type: Option<String>,
+
+    /// The type (String, StringList or SecureString) of the cached parameter, if known.
+    pub parameter_type: Option<ParameterType>,
+
+    /// When the cached parameter was last written, if known.
+    pub last_modified: Option<SystemTime>,
+
+    /// The ARN of the cached parameter, if known.
+    pub arn: Option<String>,
+
+    /// The number of times the cached value has been replaced by a different value, counting
+    /// from 0 when the parameter was first cached.
+    pub generation: u64,
+
+    /// The number of consecutive failed attempts to refresh the item since it expired.
+    pub(crate) failed_refreshes: usize,
+
+    /// When the item was stored, which is when its value was fetched.
+    inserted_at: SystemTime,
+
     /// The expiry time of the cached item.
     ///
     /// Defined as the number of nanoseconds elapsed since the unix epoch.
     ttl: u128,
+
+    /// The nanoseconds past its expiry time during which the item still isn't expired.
+    pub(crate) expiry_grace: u128,
+
+    /// The clock the item's times are read from.
+    clock: Arc<dyn Clock>,
 }
 
 impl<T> CacheItem<T> {
@@ -18,32 +60,142 @@ impl<T> CacheItem<T> {
     /// Sets the TTL of the item to the current time in nanoseconds since the unix epoch
     /// plus the provided cache_item_ttl value.
     pub fn new(value: T, cache_item_ttl: u128) -> Self {
+        CacheItem::new_with_clock(value, cache_item_ttl, system_clock())
+    }
+
+    /// Returns a cached item as new does, reading the current time from a clock.
+    pub fn new_with_clock(value: T, cache_item_ttl: u128, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
         CacheItem {
             value,
-            ttl: current_time_in_nanoseconds() + cache_item_ttl,
+            tier: None,
+            key_id: None,
+            version: None,
+            data_type: None,
+            parameter_type: None,
+            last_modified: None,
+            arn: None,
+            generation: 0,
+            failed_refreshes: 0,
+            inserted_at: now,
+            ttl: nanoseconds_since_epoch(now) + cache_item_ttl,
+            expiry_grace: 0,
+            clock,
         }
     }
 
     /// Determines whether the cached item has expired.
     ///
     /// Expiration is determined by comparing the current time
-    /// in nanoseconds to the cached item's TTL value.
+    /// in nanoseconds to the cached item's TTL value plus any expiry grace. An item stored with
+    /// a TTL of zero is expired as soon as it is stored.
     pub fn is_expired(&self) -> bool {
-        current_time_in_nanoseconds() > self.ttl
+        self.now() >= self.ttl.saturating_add(self.expiry_grace)
+    }
+
+    /// Returns how long ago the cached item was stored.
+    pub fn age(&self) -> Duration {
+        self.clock
+            .now()
+            .duration_since(self.inserted_at)
+            .unwrap_or_default()
+    }
+
+    /// Returns when the cached item was stored, as wall-clock time.
+    pub fn inserted_at(&self) -> SystemTime {
+        self.inserted_at
+    }
+
+    /// Returns when the cached item expires, as wall-clock time.
+    pub fn expires_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(u64::try_from(self.ttl).unwrap_or(u64::MAX))
+    }
+
+    /// Returns how long until the cached item expires, or zero if it has expired.
+    pub fn expires_in(&self) -> Duration {
+        let nanos = self.ttl.saturating_sub(self.now());
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+
+    /// Returns a cached item that was stored and expires at the given times, e.g. when restoring
+    /// it from a snapshot.
+    pub(crate) fn restored(value: T, inserted_at: SystemTime, expires_at: SystemTime) -> Self {
+        CacheItem {
+            value,
+            tier: None,
+            key_id: None,
+            version: None,
+            data_type: None,
+            parameter_type: None,
+            last_modified: None,
+            arn: None,
+            generation: 0,
+            failed_refreshes: 0,
+            inserted_at,
+            ttl: nanoseconds_since_epoch(expires_at),
+            expiry_grace: 0,
+            clock: system_clock(),
+        }
+    }
+
+    /// Sets the version, data type, type, last modified time and ARN from the fetched parameter.
+    pub(crate) fn set_metadata(&mut self, parameter: &Parameter) {
+        self.version = Some(parameter.version);
+        self.data_type = parameter.data_type.clone();
+        self.parameter_type = parameter.r#type.clone();
+        self.last_modified = parameter
+            .last_modified_date
+            .and_then(|date| SystemTime::try_from(date).ok());
+        self.arn = parameter.arn.clone();
+    }
+
+    /// Moves the item onto another clock, keeping its age and the time left until it expires.
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        if Arc::ptr_eq(&self.clock, &clock) {
+            return;
+        }
+        let (now, age) = (self.now(), self.age());
+        self.clock = clock;
+        let offset = nanoseconds_since_epoch(self.clock.now()) as i128 - now as i128;
+        self.shift_expiry(offset);
+        self.inserted_at = self.clock.now().checked_sub(age).unwrap_or(UNIX_EPOCH);
+    }
+
+    /// Moves the expiry of the cached item by offset nanoseconds, earlier if negative.
+    pub(crate) fn shift_expiry(&mut self, offset: i128) {
+        self.ttl = self.ttl.saturating_add_signed(offset);
+    }
+
+    /// Resets the expiry of the cached item to the current time plus cache_item_ttl, without
+    /// changing its value or age.
+    pub(crate) fn extend(&mut self, cache_item_ttl: u128) {
+        self.ttl = self.now() + cache_item_ttl;
+    }
+
+    /// Determines whether the cached item expires within the given number of nanoseconds.
+    ///
+    /// Expired items also expire within any window.
+    pub fn expires_within(&self, window: u128) -> bool {
+        self.now().saturating_add(window) > self.ttl
+    }
+
+    // The current nanoseconds since the UNIX epoch, read from the item's clock
+    fn now(&self) -> u128 {
+        nanoseconds_since_epoch(self.clock.now())
     }
 }
 
-// Helper function that returns the current nanoseconds since the UNIX epoch
-fn current_time_in_nanoseconds() -> u128 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos()
+// Helper function that returns the nanoseconds since the UNIX epoch of a time
+fn nanoseconds_since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_nanos())
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::ManualClock;
     use std::{thread, time};
 
     #[test]
@@ -56,6 +208,78 @@ mod tests {
         assert!(!cache_item.is_expired());
     }
 
+    #[test]
+    fn cache_item_age() {
+        let clock = ManualClock::new();
+        let cache_item =
+            CacheItem::new_with_clock("parameter_value", 60000000000, Arc::new(clock.clone()));
+        assert_eq!(cache_item.age(), time::Duration::ZERO);
+
+        clock.advance(time::Duration::from_secs(5));
+        assert_eq!(cache_item.age(), time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn cache_item_expires_within() {
+        // 1 min in nanoseconds
+        let cache_item = CacheItem::new("parameter_value", 60000000000);
+
+        assert!(!cache_item.expires_within(30000000000));
+        assert!(cache_item.expires_within(90000000000));
+    }
+
+    #[test]
+    fn cache_item_expiry_grace() {
+        let clock = ManualClock::new();
+        let mut cache_item =
+            CacheItem::new_with_clock("parameter_value", 1, Arc::new(clock.clone()));
+        clock.advance(time::Duration::from_millis(1));
+        assert!(cache_item.is_expired());
+
+        cache_item.expiry_grace = 60000000000;
+        assert!(!cache_item.is_expired());
+        assert!(cache_item.expires_within(0));
+    }
+
+    #[test]
+    fn cache_item_zero_ttl_expired() {
+        let cache_item = CacheItem::new("parameter_value", 0);
+
+        assert!(cache_item.is_expired());
+    }
+
+    #[test]
+    fn cache_item_restored_times() {
+        let cache_item = CacheItem::new("parameter_value", 60000000000);
+        let restored = CacheItem::restored(
+            "parameter_value",
+            cache_item.inserted_at(),
+            cache_item.expires_at(),
+        );
+
+        assert_eq!(restored.expires_at(), cache_item.expires_at());
+        assert!(!restored.is_expired());
+        assert!(restored.age() < time::Duration::from_secs(60));
+    }
+
+    #[test]
+    fn cache_item_reads_its_clock() {
+        let clock = ManualClock::new();
+        let mut cache_item = CacheItem::new("parameter_value", 60000000000);
+        cache_item.set_clock(Arc::new(clock.clone()));
+        assert!(!cache_item.is_expired());
+
+        let expires_in = cache_item.expires_in();
+        assert!(
+            expires_in > time::Duration::from_secs(59)
+                && expires_in <= time::Duration::from_secs(60)
+        );
+        clock.advance(time::Duration::from_secs(61));
+        assert!(cache_item.is_expired());
+        assert!(cache_item.age() >= time::Duration::from_secs(61));
+        assert_eq!(cache_item.expires_in(), time::Duration::ZERO);
+    }
+
     #[test]
     fn cache_item_expired() {
         let cache_item = CacheItem::new("parameter_value", 0);
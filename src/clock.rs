@@ -0,0 +1,27 @@
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+/// The source of the current time used to decide when cached values expire.
+///
+/// The cache uses SystemClock unless another is given with ParameterCacheBuilder::clock,
+/// e.g. a fake clock that is advanced by hand to test expiry without sleeping.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's wall clock, which the cache uses by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+// The SystemClock shared by every cache and item not given another clock
+pub(crate) fn system_clock() -> Arc<dyn Clock> {
+    static SYSTEM_CLOCK: OnceLock<Arc<dyn Clock>> = OnceLock::new();
+    SYSTEM_CLOCK.get_or_init(|| Arc::new(SystemClock)).clone()
+}
@@ -0,0 +1,94 @@
+use lru::LruCache;
+
+use super::cache::{GetParameterStringBuilder, ParameterCache};
+use super::cache_item::CacheItem;
+use super::key::CacheKey;
+use super::store::CacheStore;
+
+/// Routes parameter requests to one of several ParameterCaches by name prefix, e.g. to cache
+/// secrets for minutes and feature flags for seconds behind a single call site.
+///
+/// Each cache keeps its own CacheConfig. A request goes to the cache with the longest prefix
+/// matching its name, or to the fallback cache when no prefix matches. Names are matched as
+/// given, before any normalization or {env} substitution the chosen cache applies.
+pub struct CompositeCache<S = LruCache<CacheKey, CacheItem<String>>> {
+    routes: Vec<(String, ParameterCache<S>)>,
+    fallback: ParameterCache<S>,
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> CompositeCache<S> {
+    /// Returns a composite cache sending every request to the fallback cache until routes are
+    /// added.
+    pub fn new(fallback: ParameterCache<S>) -> Self {
+        CompositeCache {
+            routes: Vec::new(),
+            fallback,
+        }
+    }
+
+    /// Sends requests for names starting with the prefix to the cache.
+    ///
+    /// Adding a prefix that is already routed replaces its cache.
+    pub fn route(mut self, prefix: impl Into<String>, cache: ParameterCache<S>) -> Self {
+        let prefix = prefix.into();
+        self.routes.retain(|(routed, _)| *routed != prefix);
+        self.routes.push((prefix, cache));
+        self.routes
+            .sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        self
+    }
+
+    /// Returns the cache requests for a parameter are routed to, e.g. to use the full
+    /// ParameterCache API.
+    pub fn cache_for(&mut self, parameter_name: &str) -> &mut ParameterCache<S> {
+        self.routes
+            .iter_mut()
+            .find(|(prefix, _)| parameter_name.starts_with(prefix.as_str()))
+            .map_or(&mut self.fallback, |(_, cache)| cache)
+    }
+
+    /// Returns a builder for getting a parameter string from the cache it is routed to.
+    ///
+    /// Retrieve the parameter value with send()
+    pub fn get_parameter<'a, 'b>(
+        &'a mut self,
+        parameter_name: &'b str,
+    ) -> GetParameterStringBuilder<'a, 'b, S> {
+        self.cache_for(parameter_name).get_parameter(parameter_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{mock_ssm_client, parameter_response};
+
+    #[tokio::test]
+    async fn composite_routes_by_longest_prefix() {
+        let cache = |ttl: Duration| {
+            let client = mock_ssm_client(|_, _| (200, parameter_response("/app/flags/beta", "v")));
+            ParameterCache::new_with_config(client, CacheConfig::new().cache_item_ttl_duration(ttl))
+        };
+        let mut composite = CompositeCache::new(cache(Duration::from_secs(3600)))
+            .route("/app/", cache(Duration::from_secs(300)))
+            .route("/app/flags/", cache(Duration::from_secs(10)));
+
+        let value = composite
+            .get_parameter("/app/flags/beta")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(value, "v");
+        let ttl = |composite: &mut CompositeCache, name: &str| {
+            composite.cache_for(name).config().cache_item_ttl
+        };
+        assert_eq!(ttl(&mut composite, "/app/flags/beta"), 10_000_000_000);
+        assert_eq!(ttl(&mut composite, "/app/db"), 300_000_000_000);
+        assert_eq!(ttl(&mut composite, "/other"), 3_600_000_000_000);
+        assert_eq!(composite.cache_for("/app/flags/beta").cache.len(), 1);
+        assert_eq!(composite.cache_for("/app/db").cache.len(), 0);
+    }
+}
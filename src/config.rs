@@ -1,6 +1,12 @@
+use std::time::Duration;
+
 const DEFAULT_MAX_CACHE_SIZE: usize = 1024;
 const DEFAULT_CACHE_ITEM_TTL: u128 = 3600000000000; // 1 hour in nanoseconds
 const DEFAULT_VERSION_STAGE: &str = "AWSCURRENT";
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 10;
+const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+const DEFAULT_DESCRIPTION_TTL: Duration = Duration::from_secs(3600);
+const DEFAULT_BASE_BACKOFF: Duration = Duration::from_millis(100);
 
 /// Configuration options for the ParameterCache.
 ///
@@ -8,11 +14,47 @@ const DEFAULT_VERSION_STAGE: &str = "AWSCURRENT";
 /// - max_cache_size: 1024
 /// - cache_item_ttl: 3600000000000 (1hr)
 /// - version_stage: "AWSCURRENT"
+/// - max_memory_bytes: None (no memory budget)
+/// - max_value_bytes: None (no size limit)
+/// - default_with_decryption: false
+/// - caching_enabled: true
+/// - evict_after_failed_refreshes: None (never evict)
+/// - dry_run: false
+/// - dry_run_placeholder: None
+/// - max_concurrent_requests: 10
+/// - refresh_ahead: None
+/// - stats_by_name: false
+/// - capacity_warn_fraction: None (no warning)
+/// - normalize_names: false
+/// - empty_as_not_found: false
+/// - max_fetch_rate: None (no rate limit)
+/// - max_retries: 0
+/// - base_backoff: 100ms
+/// - expiry_grace: None
+/// - jitter: None
+/// - environment: None
+/// - error_cache_ttl: None
+/// - negative_ttl: None
+/// - pinned_version_ttl: None
+/// - breaker_threshold: None (no circuit breaker)
+/// - breaker_cooldown: 30s
+/// - description_ttl: 1hr
+/// - near_expiry_window: None
+/// - update_recency_on_hit: true
+/// - max_inflight_groups: None (no limit)
+/// - inflight_overflow: Uncoalesced
+/// - refresh_ahead_in_background: false
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CacheConfig {
     /// The maximum number of parameters to maintain in the cache.
     ///
     /// The least frequently accessed items will be evicted from the cache
-    /// once a max_cache_size number of items are stored.
+    /// once a max_cache_size number of items are stored. usize::MAX, as set by unbounded,
+    /// never evicts: entries only leave the cache when invalidated or replaced. Zero is
+    /// rejected by ParameterCacheBuilder::build with a CacheError::InvalidConfig; the other
+    /// constructors and set_config clamp it to a single entry and log a warning.
     ///
     /// Default: 1024
     pub max_cache_size: usize,
@@ -21,6 +63,8 @@ pub struct CacheConfig {
     ///
     /// Determines the number of nanoseconds a cached parameter will be considered valid before
     /// the parameter value is required to be refreshed. Refreshing happens synchronously.
+    /// A TTL of zero stores values already expired, so every request fetches from AWS; the
+    /// stored value still serves as the fallback for force_refresh_or_stale.
     ///
     /// Default: 3600000000000 (1 hour in nanoseconds)
     pub cache_item_ttl: u128,
@@ -29,6 +73,292 @@ pub struct CacheConfig {
     ///
     /// Default: "AWSCURRENT"
     pub version_stage: String,
+
+    /// The approximate maximum number of bytes of parameter values to keep in the cache.
+    ///
+    /// When storing a value would take the cache over this budget, the least recently used
+    /// entries are evicted until it fits. This is an approximation: it sums the lengths of the
+    /// cached value strings and ignores keys, metadata and allocator overhead. It applies in
+    /// addition to max_cache_size.
+    ///
+    /// Default: None (no memory budget)
+    pub max_memory_bytes: Option<usize>,
+
+    /// The largest value, in bytes, that is cached.
+    ///
+    /// A larger value is still returned to the caller but isn't cached, and replaces any value
+    /// cached for it, so a few outliers can't crowd out the rest of the cache. A single request
+    /// can lift the limit with the allow_large builder option.
+    ///
+    /// Default: None (no size limit)
+    pub max_value_bytes: Option<usize>,
+
+    /// Whether SecureString parameters are fetched decrypted when a request doesn't say.
+    ///
+    /// Individual requests can override this with the with_decryption builder option.
+    ///
+    /// Default: false
+    pub default_with_decryption: bool,
+
+    /// Whether values are cached at all.
+    ///
+    /// When false the cache is a pass-through: every request is fetched from AWS SSM and
+    /// nothing is read from or written to the cache. Intended for tests that need to exercise
+    /// the fetch path deterministically.
+    ///
+    /// Default: true
+    pub caching_enabled: bool,
+
+    /// The number of consecutive failed refreshes after which an expired entry is evicted.
+    ///
+    /// An expired entry is kept while refreshing it fails, and can still be served by
+    /// force_refresh_or_stale. Once this many refreshes in a row have failed the entry is
+    /// evicted, bounding how long stale data is served for a parameter that is gone for good.
+    /// The count resets when a refresh succeeds.
+    ///
+    /// Default: None (never evict)
+    pub evict_after_failed_refreshes: Option<usize>,
+
+    /// Not for production use: logs parameters that would be fetched instead of calling AWS.
+    ///
+    /// In dry run mode the cache never calls AWS SSM. Each fetch get_parameter, get_parameters
+    /// or a warm method would have made is logged at info level with the parameter name (with
+    /// the tracing feature), and
+    /// is answered with the dry_run_placeholder, or a CacheError::DryRun if there is none.
+    /// Writes, path and history reads, descriptions and access checks are logged and refused
+    /// with a CacheError::DryRun. Useful for auditing which parameters a service reads before
+    /// granting it IAM permissions.
+    ///
+    /// Default: false
+    pub dry_run: bool,
+
+    /// The value returned for every parameter in dry run mode.
+    ///
+    /// Default: None (dry run fetches return a CacheError::DryRun)
+    pub dry_run_placeholder: Option<String>,
+
+    /// The maximum number of requests to AWS SSM the cache makes at once for operations that
+    /// issue several requests concurrently, such as put_parameters.
+    ///
+    /// Default: 10
+    pub max_concurrent_requests: usize,
+
+    /// How long before expiry a cached item becomes stale and due for a proactive refresh.
+    ///
+    /// A stale item is still valid and served from the cache; ParameterCache::is_stale
+    /// reports it so that it can be refreshed before it expires.
+    ///
+    /// Default: None (items are never stale before they expire)
+    pub refresh_ahead: Option<Duration>,
+
+    /// Whether hit, miss and fetch counts are also kept for each parameter name.
+    ///
+    /// ParameterCache::stats_by_name then reports which parameters are hot or cold, or causing
+    /// throttling. It holds an entry for every name ever requested, which costs memory when
+    /// there are many distinct names.
+    ///
+    /// Default: false
+    pub stats_by_name: bool,
+
+    /// The fraction of max_cache_size at which the cache counts as nearly full.
+    ///
+    /// When an insert takes the number of entries to this fraction of max_cache_size or more, a
+    /// warning is logged, giving early notice to raise the capacity before capacity evictions
+    /// start causing misses. ParameterCache::is_near_capacity reports the same condition.
+    ///
+    /// Default: None (no warning)
+    pub capacity_warn_fraction: Option<f64>,
+
+    /// Whether parameter names are normalized before they are fetched and cached.
+    ///
+    /// Normalizing collapses repeated slashes, removes a trailing slash and adds a leading
+    /// slash, so "service/param", "/service/param" and "//service//param" all read the
+    /// parameter "/service/param" and share one cache entry. It applies to get_parameter and
+    /// invalidate. Note that AWS SSM treats "service/param" and "/service/param" as different
+    /// parameters, so only enable this if names are always meant to be hierarchical.
+    ///
+    /// Default: false
+    pub normalize_names: bool,
+
+    /// Whether a parameter whose value is an empty string is treated as missing.
+    ///
+    /// For teams that use an empty value to mean "unset": get_parameter returns a
+    /// CacheError::NotFound (or Ok(None) with the optional option) and get_parameters lists the
+    /// name in invalid_parameters. Empty values are not cached.
+    ///
+    /// Default: false
+    pub empty_as_not_found: bool,
+
+    /// The most requests a second the cache makes to AWS SSM, to stay under its throughput
+    /// limits.
+    ///
+    /// Requests wait for a token bucket before they are sent, smoothing out bursts that
+    /// max_concurrent_requests alone doesn't. Up to a second's worth of requests can be made at
+    /// once after an idle period. Retries made by the AWS SDK within a request, which back off
+    /// on throttling, aren't counted against the rate, so the two don't compound each other's
+    /// delays; count them in by setting the rate below SSM's limit.
+    ///
+    /// Default: None (no rate limit)
+    pub max_fetch_rate: Option<f64>,
+
+    /// The number of times the cache retries a request that AWS SSM, or KMS decrypting a
+    /// SecureString, throttled.
+    ///
+    /// Applies to get_parameter, get_parameters and the warm methods alike. Other errors, such
+    /// as not found or access denied, are returned without retrying. Each retry waits for the
+    /// token bucket of max_fetch_rate again, and a deadline or cancellation also cuts the waits
+    /// short.
+    ///
+    /// Default: 0
+    pub max_retries: u32,
+
+    /// The wait before the first retry of a throttled fetch, doubled for each retry after it.
+    /// Each wait is jittered to between half and all of that, with the source set by
+    /// ParameterCache::with_jitter_source. The waits for one request add up to at most 20
+    /// seconds, after which its throttling error is returned whatever max_retries allows.
+    ///
+    /// Default: 100ms
+    pub base_backoff: Duration,
+
+    /// The environment substituted for an {env} placeholder in parameter names.
+    ///
+    /// With an environment of "prod", get_parameter("/service/{env}/db") fetches and caches
+    /// "/service/prod/db", so a single code path reads each environment's parameters by config
+    /// alone. It applies wherever normalize_names does. Names without the placeholder are
+    /// used as given; a placeholder left with no environment set is rejected as an invalid name.
+    ///
+    /// Default: None
+    pub environment: Option<String>,
+
+    /// A tolerance added to each entry's expiry, so it isn't considered expired until its
+    /// expiry time plus the grace.
+    ///
+    /// Expiry is measured against the system clock, so small clock adjustments can make entries
+    /// expire early and be refreshed needlessly. The tradeoff is that values may be served for
+    /// up to the grace period after their TTL. Entries stored already expired, with a TTL of
+    /// zero, get no grace. The grace is fixed when an entry is stored.
+    ///
+    /// Default: None
+    pub expiry_grace: Option<Duration>,
+
+    /// The most each entry's expiry is moved earlier or later, at random, when it is stored.
+    ///
+    /// Spreads out the expiry of entries stored together, e.g. by a fleet of instances started
+    /// at once, so they don't all refresh at the same time. Each entry draws its own offset, with
+    /// the source set by ParameterCache::with_jitter_source. Entries stored already expired,
+    /// with a TTL of zero, aren't moved. Keep it below cache_item_ttl.
+    ///
+    /// Default: None
+    pub jitter: Option<Duration>,
+
+    /// How long a failed fetch of a parameter is remembered, so reads within the window return
+    /// a CacheError::RecentFailure without calling AWS.
+    ///
+    /// A simple circuit breaker that keeps a failing SSM from facing a retry storm. Any error
+    /// except not found counts, which negative_ttl remembers instead; Consistency::Fresh ignores
+    /// the remembered failure and tries AWS again.
+    ///
+    /// Default: None
+    pub error_cache_ttl: Option<Duration>,
+
+    /// How long a parameter that AWS SSM reported as not found is remembered, so reads within
+    /// the window return a CacheError::NotFound without calling AWS.
+    ///
+    /// Suits parameters that are sometimes intentionally absent. Only not found errors are
+    /// remembered, not throttling or other failures; Consistency::Fresh ignores the marker,
+    /// and storing a value for the parameter clears it.
+    ///
+    /// Default: None
+    pub negative_ttl: Option<Duration>,
+
+    /// The TTL of values fetched by a numeric version selector, e.g. "name:3", instead of the
+    /// cache_item_ttl.
+    ///
+    /// The value of a parameter version never changes, so pinned versions can be cached for
+    /// much longer than the latest value. A ttl given on the request still takes precedence.
+    ///
+    /// Default: None
+    pub pinned_version_ttl: Option<Duration>,
+
+    /// The number of consecutive failed fetches from AWS SSM that opens the circuit breaker.
+    ///
+    /// While the breaker is open get_parameter fails fast with a CacheError::CircuitOpen (or
+    /// serves a stale value with force_refresh_or_stale) without calling AWS, protecting both
+    /// the caller's latency and SSM during an outage. See BreakerState for the state machine.
+    /// NotFound errors don't count as failures.
+    ///
+    /// Default: None (no circuit breaker)
+    pub breaker_threshold: Option<usize>,
+
+    /// How long the circuit breaker stays open before letting a trial fetch through.
+    ///
+    /// Default: 30s
+    pub breaker_cooldown: Duration,
+
+    /// How long the parameter metadata returned by describe is cached.
+    ///
+    /// Default: 1hr
+    pub description_ttl: Duration,
+
+    /// How close to expiry a value served from the cache must be to publish a
+    /// CacheEvent::NearExpiry, so external logic can schedule a refresh just in time.
+    ///
+    /// Default: None
+    pub near_expiry_window: Option<Duration>,
+
+    /// Whether a cache hit marks the entry as recently used.
+    ///
+    /// When false, hits look entries up without changing the store's usage order, so entries
+    /// are evicted in the order they were stored or last refreshed, however often they are
+    /// read: the cache behaves as a TTL cache with FIFO eviction. A hot parameter can then be
+    /// evicted ahead of cold ones, costing an extra fetch, but hits no longer write to the store,
+    /// which suits read-heavy shared caches.
+    ///
+    /// Default: true
+    pub update_recency_on_hit: bool,
+
+    /// The most distinct parameters a SharedParameterCache coalesces get_parameter fetches of
+    /// at once.
+    ///
+    /// Each parameter being fetched has an entry shared by its callers until the fetch
+    /// completes, so a cold start reading thousands of distinct parameters could otherwise
+    /// hold thousands of entries. A miss for another parameter once the limit is reached is
+    /// handled as inflight_overflow says. The limit is read when the SharedParameterCache is
+    /// created.
+    ///
+    /// Default: None (no limit)
+    pub max_inflight_groups: Option<usize>,
+
+    /// How a SharedParameterCache handles a miss once max_inflight_groups parameters are
+    /// already being fetched.
+    ///
+    /// Default: Uncoalesced
+    pub inflight_overflow: InflightOverflow,
+
+    /// Whether a SharedParameterCache refreshes values in their refresh_ahead window in the
+    /// background.
+    ///
+    /// When set, a get_parameter that is served a value within refresh_ahead of expiring
+    /// returns it at once and spawns a refresh on the current Tokio runtime; only callers of
+    /// a value that has fully expired wait for AWS. A refresh already in flight for the
+    /// parameter isn't duplicated. The setting is read when the SharedParameterCache is
+    /// created, and has no effect without refresh_ahead.
+    ///
+    /// Default: false
+    pub refresh_ahead_in_background: bool,
+}
+
+/// How a miss is handled once max_inflight_groups parameters are already being fetched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InflightOverflow {
+    /// Fetch the parameter without sharing the fetch, so concurrent callers for it each make
+    /// their own request.
+    #[default]
+    Uncoalesced,
+
+    /// Wait until a fetch in flight completes and a slot is free, then coalesce as usual.
+    Wait,
 }
 
 impl CacheConfig {
@@ -38,25 +368,282 @@ impl CacheConfig {
     /// - max_cache_size: 1024
     /// - cache_item_ttl: 3600000000000 (1hr)
     /// - version_stage: "AWSCURRENT"
+    /// - max_memory_bytes: None (no memory budget)
+    /// - max_value_bytes: None (no size limit)
+    /// - default_with_decryption: false
+    /// - caching_enabled: true
+    /// - evict_after_failed_refreshes: None (never evict)
+    /// - dry_run: false
+    /// - dry_run_placeholder: None
+    /// - max_concurrent_requests: 10
+    /// - refresh_ahead: None
+    /// - stats_by_name: false
+    /// - capacity_warn_fraction: None (no warning)
+    /// - normalize_names: false
+    /// - empty_as_not_found: false
+    /// - max_fetch_rate: None (no rate limit)
+    /// - max_retries: 0
+    /// - base_backoff: 100ms
+    /// - expiry_grace: None
+    /// - jitter: None
+    /// - environment: None
+    /// - error_cache_ttl: None
+    /// - negative_ttl: None
+    /// - pinned_version_ttl: None
+    /// - breaker_threshold: None (no circuit breaker)
+    /// - breaker_cooldown: 30s
+    /// - description_ttl: 1hr
+    /// - near_expiry_window: None
+    /// - update_recency_on_hit: true
+    /// - max_inflight_groups: None (no limit)
+    /// - inflight_overflow: Uncoalesced
+    /// - refresh_ahead_in_background: false
     pub fn new() -> Self {
         CacheConfig {
             max_cache_size: DEFAULT_MAX_CACHE_SIZE,
             cache_item_ttl: DEFAULT_CACHE_ITEM_TTL,
             version_stage: DEFAULT_VERSION_STAGE.to_string(),
+            max_memory_bytes: None,
+            max_value_bytes: None,
+            default_with_decryption: false,
+            caching_enabled: true,
+            evict_after_failed_refreshes: None,
+            dry_run: false,
+            dry_run_placeholder: None,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            refresh_ahead: None,
+            stats_by_name: false,
+            capacity_warn_fraction: None,
+            normalize_names: false,
+            empty_as_not_found: false,
+            max_fetch_rate: None,
+            max_retries: 0,
+            base_backoff: DEFAULT_BASE_BACKOFF,
+            environment: None,
+            expiry_grace: None,
+            jitter: None,
+            error_cache_ttl: None,
+            negative_ttl: None,
+            pinned_version_ttl: None,
+            breaker_threshold: None,
+            breaker_cooldown: DEFAULT_BREAKER_COOLDOWN,
+            description_ttl: DEFAULT_DESCRIPTION_TTL,
+            near_expiry_window: None,
+            update_recency_on_hit: true,
+            max_inflight_groups: None,
+            inflight_overflow: InflightOverflow::Uncoalesced,
+            refresh_ahead_in_background: false,
         }
     }
 
+    /// Returns a Cache Configuration with caching disabled, so every request is fetched from AWS.
+    pub fn disabled() -> Self {
+        CacheConfig::new().caching_enabled(false)
+    }
+
     /// Sets the max_cache_size cache configuration option to a different value.
     pub fn max_cache_size(mut self, max_cache_size: usize) -> Self {
         self.max_cache_size = max_cache_size;
         self
     }
 
+    /// Sets max_cache_size to usize::MAX, so no entries are evicted to make room, e.g. for a
+    /// small fixed set of parameters.
+    pub fn unbounded(self) -> Self {
+        self.max_cache_size(usize::MAX)
+    }
+
     /// Sets the cache_item_ttl cache configuration option to a different value.
     pub fn cache_item_ttl(mut self, cache_item_ttl: u128) -> Self {
         self.cache_item_ttl = cache_item_ttl;
         self
     }
+
+    /// Sets the cache_item_ttl cache configuration option from a Duration.
+    pub fn cache_item_ttl_duration(mut self, cache_item_ttl: Duration) -> Self {
+        self.cache_item_ttl = cache_item_ttl.as_nanos();
+        self
+    }
+
+    /// Sets the max_memory_bytes cache configuration option.
+    pub fn max_memory_bytes(mut self, max_memory_bytes: usize) -> Self {
+        self.max_memory_bytes = Some(max_memory_bytes);
+        self
+    }
+
+    /// Sets the max_value_bytes cache configuration option.
+    pub fn max_value_bytes(mut self, max_value_bytes: usize) -> Self {
+        self.max_value_bytes = Some(max_value_bytes);
+        self
+    }
+
+    /// Sets the default_with_decryption cache configuration option.
+    pub fn default_with_decryption(mut self, default_with_decryption: bool) -> Self {
+        self.default_with_decryption = default_with_decryption;
+        self
+    }
+
+    /// Sets the caching_enabled cache configuration option.
+    pub fn caching_enabled(mut self, caching_enabled: bool) -> Self {
+        self.caching_enabled = caching_enabled;
+        self
+    }
+
+    /// Sets the evict_after_failed_refreshes cache configuration option.
+    pub fn evict_after_failed_refreshes(mut self, evict_after_failed_refreshes: usize) -> Self {
+        self.evict_after_failed_refreshes = Some(evict_after_failed_refreshes);
+        self
+    }
+
+    /// Sets the dry_run cache configuration option. Not for production use.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets the dry_run_placeholder cache configuration option.
+    pub fn dry_run_placeholder(mut self, dry_run_placeholder: impl Into<String>) -> Self {
+        self.dry_run_placeholder = Some(dry_run_placeholder.into());
+        self
+    }
+
+    /// Sets the max_concurrent_requests cache configuration option.
+    pub fn max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    /// Sets the refresh_ahead cache configuration option.
+    pub fn refresh_ahead(mut self, refresh_ahead: Duration) -> Self {
+        self.refresh_ahead = Some(refresh_ahead);
+        self
+    }
+
+    /// Sets the stats_by_name cache configuration option.
+    pub fn stats_by_name(mut self, stats_by_name: bool) -> Self {
+        self.stats_by_name = stats_by_name;
+        self
+    }
+
+    /// Sets the capacity_warn_fraction cache configuration option.
+    pub fn capacity_warn_fraction(mut self, capacity_warn_fraction: f64) -> Self {
+        self.capacity_warn_fraction = Some(capacity_warn_fraction);
+        self
+    }
+
+    /// Sets the normalize_names cache configuration option.
+    pub fn normalize_names(mut self, normalize_names: bool) -> Self {
+        self.normalize_names = normalize_names;
+        self
+    }
+
+    /// Sets the empty_as_not_found cache configuration option.
+    pub fn empty_as_not_found(mut self, empty_as_not_found: bool) -> Self {
+        self.empty_as_not_found = empty_as_not_found;
+        self
+    }
+
+    /// Sets the max_fetch_rate cache configuration option, in requests a second.
+    pub fn max_fetch_rate(mut self, per_second: f64) -> Self {
+        self.max_fetch_rate = Some(per_second);
+        self
+    }
+
+    /// Sets the max_retries cache configuration option.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the base_backoff cache configuration option.
+    pub fn base_backoff(mut self, base_backoff: Duration) -> Self {
+        self.base_backoff = base_backoff;
+        self
+    }
+
+    /// Sets the expiry_grace cache configuration option.
+    pub fn expiry_grace(mut self, expiry_grace: Duration) -> Self {
+        self.expiry_grace = Some(expiry_grace);
+        self
+    }
+
+    /// Sets the jitter cache configuration option.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// Sets the error_cache_ttl cache configuration option.
+    pub fn error_cache_ttl(mut self, error_cache_ttl: Duration) -> Self {
+        self.error_cache_ttl = Some(error_cache_ttl);
+        self
+    }
+
+    /// Sets the negative_ttl cache configuration option.
+    pub fn negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = Some(negative_ttl);
+        self
+    }
+
+    /// Sets the pinned_version_ttl cache configuration option.
+    pub fn pinned_version_ttl(mut self, pinned_version_ttl: Duration) -> Self {
+        self.pinned_version_ttl = Some(pinned_version_ttl);
+        self
+    }
+
+    /// Sets the breaker_threshold cache configuration option.
+    pub fn breaker_threshold(mut self, breaker_threshold: usize) -> Self {
+        self.breaker_threshold = Some(breaker_threshold);
+        self
+    }
+
+    /// Sets the breaker_cooldown cache configuration option.
+    pub fn breaker_cooldown(mut self, breaker_cooldown: Duration) -> Self {
+        self.breaker_cooldown = breaker_cooldown;
+        self
+    }
+
+    /// Sets the description_ttl cache configuration option.
+    pub fn description_ttl(mut self, description_ttl: Duration) -> Self {
+        self.description_ttl = description_ttl;
+        self
+    }
+
+    /// Sets the near_expiry_window cache configuration option.
+    pub fn near_expiry_window(mut self, near_expiry_window: Duration) -> Self {
+        self.near_expiry_window = Some(near_expiry_window);
+        self
+    }
+
+    /// Sets the update_recency_on_hit cache configuration option.
+    pub fn update_recency_on_hit(mut self, update_recency_on_hit: bool) -> Self {
+        self.update_recency_on_hit = update_recency_on_hit;
+        self
+    }
+
+    /// Sets the max_inflight_groups cache configuration option.
+    pub fn max_inflight_groups(mut self, max_inflight_groups: usize) -> Self {
+        self.max_inflight_groups = Some(max_inflight_groups);
+        self
+    }
+
+    /// Sets the inflight_overflow cache configuration option.
+    pub fn inflight_overflow(mut self, inflight_overflow: InflightOverflow) -> Self {
+        self.inflight_overflow = inflight_overflow;
+        self
+    }
+
+    /// Sets the refresh_ahead_in_background cache configuration option.
+    pub fn refresh_ahead_in_background(mut self, refresh_ahead_in_background: bool) -> Self {
+        self.refresh_ahead_in_background = refresh_ahead_in_background;
+        self
+    }
+
+    /// Sets the environment cache configuration option.
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
 }
 
 impl Default for CacheConfig {
@@ -70,6 +657,17 @@ mod tests {
     use super::*;
     use std::time;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cache_config_deserialize_partial_json() {
+        let cache_config: CacheConfig =
+            serde_json::from_str(r#"{"max_cache_size": 10, "max_memory_bytes": 4096}"#).unwrap();
+
+        assert_eq!(cache_config.max_cache_size, 10);
+        assert_eq!(cache_config.max_memory_bytes, Some(4096));
+        assert_eq!(cache_config.cache_item_ttl, DEFAULT_CACHE_ITEM_TTL);
+    }
+
     #[test]
     fn cache_config_default() {
         let cache_config = CacheConfig::new();
@@ -77,6 +675,9 @@ mod tests {
         assert_eq!(cache_config.cache_item_ttl, DEFAULT_CACHE_ITEM_TTL);
         assert_eq!(cache_config.max_cache_size, DEFAULT_MAX_CACHE_SIZE);
         assert_eq!(cache_config.version_stage, DEFAULT_VERSION_STAGE);
+        assert_eq!(cache_config.max_memory_bytes, None);
+        assert!(!cache_config.default_with_decryption);
+        assert!(cache_config.caching_enabled);
     }
 
     #[test]
@@ -91,6 +692,21 @@ mod tests {
         assert_eq!(cache_config.version_stage, DEFAULT_VERSION_STAGE);
     }
 
+    #[test]
+    fn cache_config_clone_variation() {
+        let base_config = CacheConfig::new().max_cache_size(10);
+        let short_lived_config = base_config
+            .clone()
+            .cache_item_ttl_duration(time::Duration::from_secs(5));
+
+        assert_eq!(base_config.cache_item_ttl, DEFAULT_CACHE_ITEM_TTL);
+        assert_eq!(short_lived_config.max_cache_size, 10);
+        assert_eq!(
+            short_lived_config.cache_item_ttl,
+            time::Duration::from_secs(5).as_nanos()
+        );
+    }
+
     #[test]
     fn cache_config_partial_config() {
         let cache_config = CacheConfig::new().max_cache_size(10);
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+
+/// A type whose fields are loaded from AWS SSM parameters, implemented with
+/// #[derive(FromSsm)].
+pub trait FromSsm: Sized {
+    /// The names of the parameters the fields are loaded from.
+    const PARAMETER_NAMES: &'static [&'static str];
+
+    /// Builds the value from the loaded parameter values, keyed by name.
+    ///
+    /// A missing or unparsable parameter results in a CacheError::Field naming the field.
+    fn from_parameters(values: &HashMap<String, String>) -> Result<Self, CacheError>;
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Loads every parameter a FromSsm type needs and builds it, e.g. a service's configuration
+    /// at startup.
+    ///
+    /// The parameters are fetched in one get_parameters batch, using the cache where possible,
+    /// so loading takes as few round trips as GetParameters allows. A missing or unparsable
+    /// parameter results in a CacheError::Field naming the field and wrapping the cause.
+    pub async fn load_bundle<T: FromSsm>(&mut self) -> Result<T, CacheError> {
+        let result = self.get_parameters(T::PARAMETER_NAMES).send().await?;
+        T::from_parameters(&result.parameters)
+    }
+}
+
+/// Attaches the name of the field being loaded to an error.
+pub fn field<T>(field: &str, result: Result<T, CacheError>) -> Result<T, CacheError> {
+    result.map_err(|e| CacheError::Field {
+        field: field.to_string(),
+        source: Box::new(e),
+    })
+}
+
+/// Returns a loaded parameter value, or a CacheError::NotFound if it wasn't found.
+pub fn value(values: &HashMap<String, String>, name: &str) -> Result<String, CacheError> {
+    values
+        .get(name)
+        .cloned()
+        .ok_or_else(|| CacheError::not_found(name, "Parameter not found by GetParameters"))
+}
+
+/// Returns a loaded parameter value parsed with FromStr.
+pub fn parse<T>(values: &HashMap<String, String>, name: &str) -> Result<T, CacheError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value(values, name)?
+        .parse()
+        .map_err(|e: T::Err| CacheError::Parse {
+            name: name.to_string(),
+            message: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_ssm_client;
+    use crate::FromSsm;
+
+    #[derive(Debug, FromSsm)]
+    struct Config {
+        #[ssm("/svc/db_url")]
+        db_url: String,
+        #[ssm("/svc/pool", parse)]
+        pool: u32,
+    }
+
+    #[tokio::test]
+    async fn derive_loads_struct_in_one_call() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| {
+            assert_eq!(operation, "GetParameters");
+            let parameters = r#"{"Parameters": [
+                {"Name": "/svc/db_url", "Type": "String", "Value": "postgres://db", "Version": 1},
+                {"Name": "/svc/pool", "Type": "String", "Value": "8", "Version": 1}
+            ]}"#;
+            (200, parameters.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let config = Config::load(&mut parameter_cache).await.unwrap();
+
+        assert_eq!(config.db_url, "postgres://db");
+        assert_eq!(config.pool, 8);
+    }
+
+    #[tokio::test]
+    async fn derive_reports_missing_and_unparsable_values() {
+        let mock_ssm_client = mock_ssm_client(|_, _| {
+            let parameters = r#"{"Parameters": [
+                {"Name": "/svc/pool", "Type": "String", "Value": "many", "Version": 1}
+            ], "InvalidParameters": ["/svc/db_url"]}"#;
+            (200, parameters.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let error = Config::load(&mut parameter_cache).await.unwrap_err();
+        assert!(error.is_not_found());
+        match error {
+            CacheError::Field { field, .. } => assert_eq!(field, "db_url"),
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        let values = HashMap::from([
+            ("/svc/db_url".to_string(), "postgres://db".to_string()),
+            ("/svc/pool".to_string(), "many".to_string()),
+        ]);
+        let error = Config::from_parameters(&values).unwrap_err();
+        assert!(error.to_string().contains("'pool'"), "{}", error);
+
+        let error = parse::<u32>(&values, "/svc/pool").unwrap_err();
+        assert!(matches!(error, CacheError::Parse { .. }));
+    }
+
+    #[tokio::test]
+    async fn load_bundle_builds_the_type() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| {
+            assert_eq!(operation, "GetParameters");
+            let parameters = r#"{"Parameters": [
+                {"Name": "/svc/db_url", "Type": "String", "Value": "postgres://db", "Version": 1},
+                {"Name": "/svc/pool", "Type": "String", "Value": "8", "Version": 1}
+            ]}"#;
+            (200, parameters.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let config: Config = parameter_cache.load_bundle().await.unwrap();
+        assert_eq!(config.db_url, "postgres://db");
+        assert_eq!(Config::PARAMETER_NAMES, ["/svc/db_url", "/svc/pool"]);
+    }
+}
@@ -0,0 +1,155 @@
+use aws_sdk_ssm::types::{ParameterMetadata, ParameterStringFilter, ParameterTier, ParameterType};
+
+use super::cache::{lookup, ParameterCache};
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::{validate_name, CacheKey};
+use super::store::CacheStore;
+
+/// The metadata of a parameter, as returned by describe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterDescription {
+    /// The name of the parameter.
+    pub name: String,
+
+    /// The description given when the parameter was written.
+    pub description: Option<String>,
+
+    /// The regular expression values of the parameter must match.
+    pub allowed_pattern: Option<String>,
+
+    /// The type of the parameter.
+    pub parameter_type: Option<ParameterType>,
+
+    /// The tier of the parameter.
+    pub tier: Option<ParameterTier>,
+
+    /// The JSON text of each policy attached to the parameter, in the form
+    /// ParameterPolicy::to_json writes.
+    pub policies: Vec<String>,
+}
+
+impl From<ParameterMetadata> for ParameterDescription {
+    fn from(metadata: ParameterMetadata) -> Self {
+        ParameterDescription {
+            name: metadata.name.unwrap_or_default(),
+            description: metadata.description,
+            allowed_pattern: metadata.allowed_pattern,
+            parameter_type: metadata.r#type,
+            tier: metadata.tier,
+            policies: metadata
+                .policies
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|policy| policy.policy_text)
+                .collect(),
+        }
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Gets the description, allowed pattern, type, tier and policies of a parameter, e.g. for
+    /// a configuration admin UI.
+    ///
+    /// The metadata comes from DescribeParameters and is cached apart from parameter values, for
+    /// description_ttl from the CacheConfig, since it rarely changes. Returns a
+    /// CacheError::NotFound if the parameter doesn't exist.
+    pub async fn describe(
+        &mut self,
+        parameter_name: &str,
+    ) -> Result<ParameterDescription, CacheError> {
+        let parameter_name = self.normalized_name(parameter_name).into_owned();
+        validate_name(&parameter_name)?;
+        let update_recency_on_hit = self.config.update_recency_on_hit;
+        if let Some(cache_item) = lookup(
+            &mut self.descriptions,
+            update_recency_on_hit,
+            &parameter_name,
+        ) {
+            if !cache_item.is_expired() {
+                return Ok(cache_item.value.clone());
+            }
+        }
+        if let Some(e) = self.dry_run_refusal(&parameter_name, "DescribeParameters") {
+            return Err(e);
+        }
+
+        let filter = ParameterStringFilter::builder()
+            .key("Name")
+            .option("Equals")
+            .values(&parameter_name)
+            .build()
+            .map_err(|e| CacheError::InvalidName {
+                name: parameter_name.clone(),
+                reason: e.to_string(),
+            })?;
+        self.fetch_rate.acquire().await;
+        let output = self
+            .client
+            .describe_parameters()
+            .parameter_filters(filter)
+            .send()
+            .await
+            .map_err(|e| CacheError::from_sdk_error(&parameter_name, e))?;
+        let description = output
+            .parameters
+            .unwrap_or_default()
+            .into_iter()
+            .find(|metadata| metadata.name() == Some(&*parameter_name))
+            .map(ParameterDescription::from)
+            .ok_or_else(|| {
+                CacheError::not_found(&parameter_name, "DescribeParameters found no parameter")
+            })?;
+
+        let ttl = self.config.description_ttl.as_nanos();
+        let cache_item = CacheItem::new_with_clock(description.clone(), ttl, self.clock.clone());
+        self.descriptions.put(parameter_name, cache_item);
+        Ok(description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::test_util::mock_ssm_client;
+
+    #[tokio::test]
+    async fn describe_is_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, _| {
+            assert_eq!(operation, "DescribeParameters");
+            counter.fetch_add(1, Ordering::SeqCst);
+            (
+                200,
+                r#"{"Parameters":[{"Name":"service/parameter","Type":"String","Tier":"Standard",
+                    "Description":"The service port","AllowedPattern":"^\\d+$",
+                    "Policies":[{"PolicyText":"{}","PolicyType":"Expiration"}]}]}"#
+                    .to_string(),
+            )
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        for _ in 0..2 {
+            let description = parameter_cache.describe("service/parameter").await.unwrap();
+            assert_eq!(description.description.as_deref(), Some("The service port"));
+            assert_eq!(description.allowed_pattern.as_deref(), Some("^\\d+$"));
+            assert_eq!(description.parameter_type, Some(ParameterType::String));
+            assert_eq!(description.tier, Some(ParameterTier::Standard));
+            assert_eq!(description.policies, vec!["{}"]);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn describe_missing_parameter() {
+        let mock_ssm_client = mock_ssm_client(|_, _| (200, r#"{"Parameters":[]}"#.to_string()));
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let result = parameter_cache.describe("service/parameter").await;
+        assert!(result.unwrap_err().is_not_found());
+    }
+}
@@ -0,0 +1,477 @@
+use std::error::Error;
+use std::fmt;
+
+use aws_sdk_config::error::SdkError;
+use aws_sdk_ssm::error::ProvideErrorMetadata;
+use aws_sdk_ssm::types::ParameterType;
+
+const ACCESS_DENIED_CODE: &str = "AccessDeniedException";
+const NOT_FOUND_CODE: &str = "ParameterNotFound";
+const THROTTLING_CODES: [&str; 2] = ["ThrottlingException", "KMSThrottlingException"];
+
+/// Errors returned by the ParameterCache.
+#[derive(Debug)]
+pub enum CacheError {
+    /// The request to AWS SSM failed.
+    Ssm(Box<aws_sdk_ssm::Error>),
+
+    /// The caller's credentials don't allow reading the parameter.
+    ///
+    /// This is a configuration problem that retrying won't fix.
+    AccessDenied {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The error returned by AWS SSM.
+        source: Box<aws_sdk_ssm::Error>,
+    },
+
+    /// The parameter doesn't exist.
+    NotFound {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The error returned by AWS SSM.
+        source: Box<aws_sdk_ssm::Error>,
+    },
+
+    /// AWS SSM throttled the request.
+    ///
+    /// Retrying later, or reducing the request rate, can succeed. When KMS throttles the
+    /// decryption of a SecureString, AWS SSM reports it with a KMSThrottlingException, which is
+    /// mapped to this same error.
+    Throttled {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The error returned by AWS SSM.
+        source: Box<aws_sdk_ssm::Error>,
+    },
+
+    /// The request didn't reach AWS SSM or timed out, e.g. because of a network failure.
+    ///
+    /// Like throttling, this is usually transient.
+    Transport {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The error returned by the AWS SDK.
+        source: Box<aws_sdk_ssm::Error>,
+    },
+
+    /// AWS SSM answered the request without a parameter value.
+    MissingValue {
+        /// The name of the parameter being fetched.
+        name: String,
+    },
+
+    /// AWS SSM returned a version of the parameter below the minimum requested with min_version.
+    StaleVersion {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The version AWS SSM returned.
+        version: i64,
+        /// The minimum acceptable version.
+        min_version: i64,
+    },
+
+    /// The parameter isn't of the type the request needs, e.g. a String read as a StringList.
+    WrongType {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The type the request needs.
+        expected: ParameterType,
+        /// The type of the parameter.
+        actual: ParameterType,
+    },
+
+    /// The parameter value could not be parsed into the requested type.
+    Parse {
+        /// The name of the parameter whose value could not be parsed.
+        name: String,
+        /// A description of why the value was rejected.
+        message: String,
+    },
+
+    /// A fetched value failed the check given with the validate builder option, so it wasn't
+    /// cached.
+    ValidationFailed {
+        /// The name of the parameter whose value was rejected.
+        name: String,
+        /// The error returned by the check.
+        message: String,
+    },
+
+    /// No client has been registered for the account requested with the account builder option.
+    UnknownAccount {
+        /// The requested account name.
+        account: String,
+    },
+
+    /// The cache is in dry run mode and didn't call AWS: it has no placeholder value to return,
+    /// or the call would have written, listed or described parameters.
+    DryRun {
+        /// The name of the parameter, or the path, the call would have been made for.
+        name: String,
+    },
+
+    /// The parameter could not be fetched from AWS SSM before the requested deadline.
+    DeadlineExceeded {
+        /// The name of the parameter being fetched.
+        name: String,
+    },
+
+    /// The fetch of the parameter was abandoned because the token given with the cancel_token
+    /// builder option was cancelled.
+    Cancelled {
+        /// The name of the parameter being fetched.
+        name: String,
+    },
+
+    /// The SHA-256 of the parameter value isn't the one given with the expect_sha256 builder
+    /// option, so it wasn't cached.
+    ChecksumMismatch {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The expected SHA-256, as lowercase hex.
+        expected: String,
+        /// The SHA-256 of the value, as lowercase hex.
+        actual: String,
+    },
+
+    /// A field of a FromSsm type couldn't be loaded from its parameter.
+    Field {
+        /// The name of the field.
+        field: String,
+        /// Why the field's parameter couldn't be loaded, naming the parameter.
+        source: Box<CacheError>,
+    },
+
+    /// The parameter name breaks the AWS SSM naming rules, so it wasn't sent to AWS.
+    InvalidName {
+        /// The rejected parameter name.
+        name: String,
+        /// The rule the name breaks.
+        reason: String,
+    },
+
+    /// The value is too large for the tier of the parameter being written, so it wasn't sent to
+    /// AWS.
+    ValueTooLarge {
+        /// The name of the parameter being written.
+        name: String,
+        /// The size of the value.
+        bytes: usize,
+        /// The largest value the tier accepts.
+        max_bytes: usize,
+    },
+
+    /// The circuit breaker is open after repeated failures, so AWS SSM wasn't called.
+    CircuitOpen {
+        /// The name of the parameter being fetched.
+        name: String,
+    },
+
+    /// A fetch of the parameter failed within error_cache_ttl, so AWS SSM wasn't called again.
+    RecentFailure {
+        /// The name of the parameter being fetched.
+        name: String,
+        /// The message of the remembered failure.
+        message: String,
+    },
+
+    /// The policies given with the policies option can't be attached to the parameter, so it
+    /// wasn't sent to AWS.
+    InvalidPolicy {
+        /// The name of the parameter being written.
+        name: String,
+        /// Why the policies were rejected.
+        message: String,
+    },
+
+    /// The global cache was used before global::init was called.
+    Uninitialized,
+
+    /// The configuration passed to ParameterCacheBuilder::build can't work.
+    InvalidConfig {
+        /// A description of the rejected option.
+        message: String,
+    },
+
+    /// The manifest passed to warm_from_manifest could not be read or parsed.
+    Manifest {
+        /// The path of the manifest file.
+        path: String,
+        /// A description of why the manifest was rejected.
+        message: String,
+    },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Ssm(e) => write!(f, "AWS SSM request failed: {}", e),
+            CacheError::AccessDenied { name, source } => write!(
+                f,
+                "Access denied to parameter '{}', check the IAM permissions: {}",
+                name, source
+            ),
+            CacheError::NotFound { name, source } => {
+                write!(f, "Parameter '{}' not found: {}", name, source)
+            }
+            CacheError::Throttled { name, source } => write!(
+                f,
+                "Request for parameter '{}' was throttled, retry later or reduce the request rate: {}",
+                name, source
+            ),
+            CacheError::Transport { name, source } => write!(
+                f,
+                "Request for parameter '{}' didn't reach AWS SSM: {}",
+                name, source
+            ),
+            CacheError::MissingValue { name } => {
+                write!(f, "AWS SSM returned no value for parameter '{}'", name)
+            }
+            CacheError::StaleVersion {
+                name,
+                version,
+                min_version,
+            } => write!(
+                f,
+                "Parameter '{}' is at version {}, below the minimum version {}",
+                name, version, min_version
+            ),
+            CacheError::WrongType {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Parameter '{}' is a {}, not a {}",
+                name,
+                actual.as_str(),
+                expected.as_str()
+            ),
+            CacheError::Parse { name, message } => {
+                write!(f, "Failed to parse parameter '{}': {}", name, message)
+            }
+            CacheError::ValidationFailed { name, message } => {
+                write!(f, "Parameter '{}' failed validation: {}", name, message)
+            }
+            CacheError::UnknownAccount { account } => {
+                write!(f, "No client registered for account '{}'", account)
+            }
+            CacheError::DryRun { name } => {
+                write!(f, "Dry run: AWS was not called for '{}'", name)
+            }
+            CacheError::DeadlineExceeded { name } => {
+                write!(f, "Deadline exceeded fetching parameter '{}'", name)
+            }
+            CacheError::Cancelled { name } => {
+                write!(f, "Fetch of parameter '{}' was cancelled", name)
+            }
+            CacheError::InvalidName { name, reason } => {
+                write!(f, "Invalid parameter name '{}': {}", name, reason)
+            }
+            CacheError::ValueTooLarge {
+                name,
+                bytes,
+                max_bytes,
+            } => write!(
+                f,
+                "Value of parameter '{}' is {} bytes, more than the {} its tier allows",
+                name, bytes, max_bytes
+            ),
+            CacheError::CircuitOpen { name } => write!(
+                f,
+                "Parameter '{}' wasn't fetched, the circuit breaker is open after repeated failures",
+                name
+            ),
+            CacheError::RecentFailure { name, message } => write!(
+                f,
+                "Parameter '{}' failed recently and isn't retried until error_cache_ttl passes: {}",
+                name, message
+            ),
+            CacheError::InvalidPolicy { name, message } => {
+                write!(f, "Invalid policies for parameter '{}': {}", name, message)
+            }
+            CacheError::Uninitialized => write!(
+                f,
+                "The global cache isn't initialized, call global::init first"
+            ),
+            CacheError::ChecksumMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Value of parameter '{}' has SHA-256 {}, expected {}",
+                name, actual, expected
+            ),
+            CacheError::Field { field, source } => {
+                write!(f, "Failed to load field '{}': {}", field, source)
+            }
+            CacheError::InvalidConfig { message } => {
+                write!(f, "Invalid cache configuration: {}", message)
+            }
+            CacheError::Manifest { path, message } => {
+                write!(f, "Failed to read parameter manifest '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl Error for CacheError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CacheError::Ssm(e) => Some(e.as_ref()),
+            CacheError::AccessDenied { source, .. }
+            | CacheError::NotFound { source, .. }
+            | CacheError::Throttled { source, .. }
+            | CacheError::Transport { source, .. } => Some(source.as_ref()),
+            CacheError::Field { source, .. } => Some(source.as_ref()),
+            CacheError::MissingValue { .. }
+            | CacheError::StaleVersion { .. }
+            | CacheError::WrongType { .. }
+            | CacheError::Parse { .. }
+            | CacheError::ValidationFailed { .. }
+            | CacheError::UnknownAccount { .. }
+            | CacheError::DryRun { .. }
+            | CacheError::DeadlineExceeded { .. }
+            | CacheError::Cancelled { .. }
+            | CacheError::InvalidName { .. }
+            | CacheError::ValueTooLarge { .. }
+            | CacheError::CircuitOpen { .. }
+            | CacheError::RecentFailure { .. }
+            | CacheError::InvalidPolicy { .. }
+            | CacheError::Uninitialized
+            | CacheError::ChecksumMismatch { .. }
+            | CacheError::InvalidConfig { .. }
+            | CacheError::Manifest { .. } => None,
+        }
+    }
+}
+
+impl CacheError {
+    /// Returns true if the error means the parameter doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            CacheError::NotFound { .. } => true,
+            CacheError::Field { source, .. } => source.is_not_found(),
+            _ => false,
+        }
+    }
+
+    /// Returns a NotFound error for a parameter the cache treats as missing without AWS SSM
+    /// having said so.
+    pub(crate) fn not_found(name: &str, message: impl Into<String>) -> Self {
+        CacheError::NotFound {
+            name: name.to_string(),
+            source: Box::new(aws_sdk_ssm::Error::ParameterNotFound(
+                aws_sdk_ssm::types::error::ParameterNotFound::builder()
+                    .message(message)
+                    .build(),
+            )),
+        }
+    }
+
+    /// Returns true if AWS SSM, or KMS decrypting the value, throttled the request that failed
+    /// with this error.
+    pub(crate) fn is_throttling(e: &impl ProvideErrorMetadata) -> bool {
+        e.code()
+            .is_some_and(|code| THROTTLING_CODES.contains(&code))
+    }
+
+    /// Converts an error returned by AWS SSM for the named parameter into a CacheError.
+    pub(crate) fn from_sdk_error<E>(name: &str, e: SdkError<E>) -> Self
+    where
+        E: ProvideErrorMetadata,
+        aws_sdk_ssm::Error: From<SdkError<E>>,
+    {
+        if matches!(e, SdkError::DispatchFailure(_) | SdkError::TimeoutError(_)) {
+            return CacheError::Transport {
+                name: name.to_string(),
+                source: Box::new(e.into()),
+            };
+        }
+        match e.code() {
+            Some(ACCESS_DENIED_CODE) => CacheError::AccessDenied {
+                name: name.to_string(),
+                source: Box::new(e.into()),
+            },
+            Some(NOT_FOUND_CODE) => CacheError::NotFound {
+                name: name.to_string(),
+                source: Box::new(e.into()),
+            },
+            _ if Self::is_throttling(&e) => CacheError::Throttled {
+                name: name.to_string(),
+                source: Box::new(e.into()),
+            },
+            _ => e.into(),
+        }
+    }
+}
+
+impl<E> From<SdkError<E>> for CacheError
+where
+    aws_sdk_ssm::Error: From<SdkError<E>>,
+{
+    fn from(e: SdkError<E>) -> Self {
+        CacheError::Ssm(Box::new(e.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_ssm::types::error::ParameterNotFound;
+
+    fn ssm_error() -> Box<aws_sdk_ssm::Error> {
+        Box::new(aws_sdk_ssm::Error::ParameterNotFound(
+            ParameterNotFound::builder().message("cause").build(),
+        ))
+    }
+
+    #[test]
+    fn display_distinguishes_errors() {
+        let name = "service/parameter".to_string();
+        let errors = [
+            CacheError::NotFound {
+                name: name.clone(),
+                source: ssm_error(),
+            },
+            CacheError::Throttled {
+                name: name.clone(),
+                source: ssm_error(),
+            },
+            CacheError::AccessDenied {
+                name: name.clone(),
+                source: ssm_error(),
+            },
+            CacheError::MissingValue { name: name.clone() },
+            CacheError::DeadlineExceeded { name: name.clone() },
+            CacheError::Parse {
+                name: name.clone(),
+                message: "value is not a recognized boolean".to_string(),
+            },
+        ];
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+
+        for message in &messages {
+            assert!(message.contains("'service/parameter'"), "{}", message);
+        }
+        for (i, message) in messages.iter().enumerate() {
+            assert!(!messages[i + 1..].contains(message), "{}", message);
+        }
+        assert!(messages[0].contains("not found") && messages[0].contains("cause"));
+        assert!(messages[1].contains("throttled") && messages[1].contains("cause"));
+        assert!(errors[0].source().is_some() && errors[3].source().is_none());
+    }
+
+    #[test]
+    fn timeouts_are_transport_errors() {
+        let timeout: SdkError<aws_sdk_ssm::operation::get_parameter::GetParameterError> =
+            SdkError::timeout_error("no response");
+        let error = CacheError::from_sdk_error("service/parameter", timeout);
+        assert!(
+            matches!(error, CacheError::Transport { ref name, .. } if name == "service/parameter")
+        );
+        assert!(error.source().is_some());
+    }
+}
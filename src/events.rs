@@ -0,0 +1,261 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use super::cache::ParameterCache;
+use super::eviction::EvictionReason;
+
+// The number of events a subscriber can fall behind by before it misses some
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+// A callback called with each event, as set by ParameterCache::on_event
+pub(crate) type EventHook = Arc<dyn Fn(&CacheEvent) + Send + Sync>;
+
+/// An operation on the cache, published to the subscribers returned by ParameterCache::events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CacheEvent {
+    /// A request was served from the cache.
+    Hit {
+        /// The requested parameter name.
+        name: String,
+    },
+
+    /// A request wasn't cached, or had expired, so it was fetched from AWS SSM.
+    Miss {
+        /// The requested parameter name.
+        name: String,
+    },
+
+    /// A fetch from AWS SSM succeeded.
+    Fetched {
+        /// The fetched parameter name, or path for get_parameters_by_path.
+        name: String,
+        /// How long the fetch took.
+        elapsed: Duration,
+    },
+
+    /// A request was served from the cache with a value due to expire within the
+    /// near_expiry_window, e.g. to schedule a refresh just in time.
+    NearExpiry {
+        /// The requested parameter name.
+        name: String,
+        /// How long until the served value expires.
+        expires_in: Duration,
+    },
+
+    /// An entry left the cache.
+    Evicted {
+        /// The evicted parameter name.
+        name: String,
+        /// Why the entry was evicted.
+        reason: EvictionReason,
+    },
+
+    /// A fetch from AWS SSM failed.
+    Error {
+        /// The parameter name, or path for get_parameters_by_path, that failed to be fetched.
+        name: String,
+        /// The error, as displayed.
+        message: String,
+    },
+}
+
+impl<S> ParameterCache<S> {
+    /// Subscribes to the events of every operation on the cache, e.g. for a custom dashboard
+    /// or for asserting on cache behaviour in tests.
+    ///
+    /// Events are only built while there is a subscriber. Each subscriber receives the events
+    /// published after it subscribed. A subscriber that falls more than 256 events behind
+    /// misses the oldest ones, as reported by a RecvError::Lagged.
+    pub fn events(&self) -> broadcast::Receiver<CacheEvent> {
+        self.events.subscribe()
+    }
+
+    /// Calls a callback with each event, as it happens, e.g. to emit metrics or structured logs.
+    ///
+    /// The callback runs synchronously on the path raising the event, such as send() or an
+    /// eviction, so it should be quick. It receives the same events as the subscribers from
+    /// events, and replaces any callback set before.
+    pub fn on_event(mut self, callback: impl Fn(&CacheEvent) + Send + Sync + 'static) -> Self {
+        self.event_hook = Some(Arc::new(callback));
+        self
+    }
+
+    /// Publishes an event to the event callback and any subscribers, building it only if there
+    /// are some.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> CacheEvent) {
+        let subscribed = self.events.receiver_count() > 0;
+        if !subscribed && self.event_hook.is_none() {
+            return;
+        }
+        let event = event();
+        if let Some(event_hook) = &self.event_hook {
+            event_hook(&event);
+        }
+        if subscribed {
+            // with no subscribers left the event is simply dropped
+            let _ = self.events.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{error_response, mock_ssm_client, parameter_response, ManualClock};
+
+    #[tokio::test]
+    async fn events_report_operations() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("missing") {
+            true => error_response("ParameterNotFound"),
+            false => (200, parameter_response("service/parameter", "value")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let mut events = parameter_cache.events();
+
+        for name in ["service/parameter", "service/parameter", "missing"] {
+            let _ = parameter_cache.get_parameter(name).send().await;
+        }
+        assert!(parameter_cache.invalidate("service/parameter"));
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(match event {
+                CacheEvent::Fetched { name, .. } => format!("fetched {}", name),
+                CacheEvent::Error { name, .. } => format!("error {}", name),
+                other => format!("{:?}", other),
+            });
+        }
+        assert_eq!(
+            received,
+            vec![
+                r#"Miss { name: "service/parameter" }"#,
+                "fetched service/parameter",
+                r#"Hit { name: "service/parameter" }"#,
+                r#"Miss { name: "missing" }"#,
+                "error missing",
+                r#"Evicted { name: "service/parameter", reason: Invalidated }"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn on_event_callback() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events = received.clone();
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client)
+            .on_event(move |event| events.lock().unwrap().push(event.clone()));
+
+        for _ in 0..2 {
+            parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await
+                .unwrap();
+        }
+        let received = received.lock().unwrap();
+        assert!(matches!(
+            &received[..],
+            [
+                CacheEvent::Miss { .. },
+                CacheEvent::Fetched { .. },
+                CacheEvent::Hit { .. }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn near_expiry_reads_the_clock() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let clock = ManualClock::new();
+        let mut parameter_cache = ParameterCache::builder(mock_ssm_client)
+            .config(
+                CacheConfig::new()
+                    .cache_item_ttl_duration(Duration::from_secs(60))
+                    .near_expiry_window(Duration::from_secs(40)),
+            )
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let mut events = parameter_cache.events();
+
+        for _ in 0..2 {
+            parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await
+                .unwrap();
+            clock.advance(Duration::from_secs(30));
+        }
+        let near_expiry: Vec<CacheEvent> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|event| matches!(event, CacheEvent::NearExpiry { .. }))
+            .collect();
+        assert_eq!(
+            near_expiry,
+            vec![CacheEvent::NearExpiry {
+                name: "service/parameter".to_string(),
+                expires_in: Duration::from_secs(30),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn events_report_near_expiry() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let config = CacheConfig::new()
+            .cache_item_ttl_duration(Duration::from_secs(60))
+            .near_expiry_window(Duration::from_secs(120));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+        let mut events = parameter_cache.events();
+
+        for _ in 0..2 {
+            parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await
+                .unwrap();
+        }
+
+        let near_expiry: Vec<CacheEvent> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|event| matches!(event, CacheEvent::NearExpiry { .. }))
+            .collect();
+        match &near_expiry[..] {
+            [CacheEvent::NearExpiry { name, expires_in }] => {
+                assert_eq!(name, "service/parameter");
+                assert!(*expires_in <= Duration::from_secs(60));
+            }
+            other => panic!("unexpected events {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn events_report_near_expiry_on_borrowed_hits() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let config = CacheConfig::new()
+            .cache_item_ttl_duration(Duration::from_secs(60))
+            .near_expiry_window(Duration::from_secs(120));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+        let mut events = parameter_cache.events();
+
+        for _ in 0..2 {
+            parameter_cache
+                .get_parameter("service/parameter")
+                .send_cow()
+                .await
+                .unwrap();
+        }
+
+        let near_expiry = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|event| matches!(event, CacheEvent::NearExpiry { .. }))
+            .count();
+        assert_eq!(near_expiry, 1);
+    }
+}
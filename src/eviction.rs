@@ -0,0 +1,37 @@
+use std::fmt;
+
+/// Why an entry left the cache.
+///
+/// With the tracing feature every eviction is logged as a debug-level event with the
+/// parameter name and the reason, to give visibility into cache churn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EvictionReason {
+    /// The least recently used entry was evicted to make room under max_cache_size.
+    Capacity,
+
+    /// The least recently used entry was evicted to keep within max_memory_bytes.
+    MemoryBudget,
+
+    /// The entry was evicted because set_config reduced max_cache_size.
+    Resized,
+
+    /// The entry expired and refreshing it failed evict_after_failed_refreshes times in a row.
+    FailedRefreshes,
+
+    /// The entry was explicitly invalidated.
+    Invalidated,
+}
+
+impl fmt::Display for EvictionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            EvictionReason::Capacity => "capacity",
+            EvictionReason::MemoryBudget => "memory budget",
+            EvictionReason::Resized => "resized",
+            EvictionReason::FailedRefreshes => "failed refreshes",
+            EvictionReason::Invalidated => "invalidated",
+        };
+        f.write_str(reason)
+    }
+}
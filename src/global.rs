@@ -0,0 +1,63 @@
+//! A process-wide cache for the common one-cache-per-process pattern, e.g. in AWS Lambda,
+//! where the cache should outlive each invocation.
+//!
+//! Call init once, typically at the start of main, then read parameters from anywhere with
+//! get_parameter.
+
+use std::sync::OnceLock;
+
+use aws_sdk_ssm::Client as SSMClient;
+
+use super::cache::ParameterCache;
+use super::config::CacheConfig;
+use super::error::CacheError;
+use super::shared::{ParameterProvider, SharedParameterCache};
+
+static CACHE: OnceLock<SharedParameterCache> = OnceLock::new();
+
+/// Initializes the global cache with a client and Cache Configuration.
+///
+/// Returns a CacheError::InvalidConfig if the global cache was already initialized; the
+/// existing cache is kept.
+pub fn init(client: SSMClient, config: CacheConfig) -> Result<(), CacheError> {
+    let shared = SharedParameterCache::new(ParameterCache::new_with_config(client, config));
+    CACHE.set(shared).map_err(|_| CacheError::InvalidConfig {
+        message: "the global cache is already initialized".to_string(),
+    })
+}
+
+/// Returns the global cache, e.g. to use the full SharedParameterCache API.
+///
+/// Returns a CacheError::Uninitialized if init hasn't been called.
+pub fn cache() -> Result<&'static SharedParameterCache, CacheError> {
+    CACHE.get().ok_or(CacheError::Uninitialized)
+}
+
+/// Gets a parameter value from the global cache, as SharedParameterCache::get_parameter does.
+///
+/// Returns a CacheError::Uninitialized if init hasn't been called.
+pub async fn get_parameter(parameter_name: &str) -> Result<String, CacheError> {
+    cache()?.get_parameter(parameter_name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_ssm_client, parameter_response};
+
+    // the only test touching the global cache, as it can be initialized once per process
+    #[tokio::test]
+    async fn global_cache_lifecycle() {
+        let result = get_parameter("service/parameter").await;
+        assert!(matches!(result, Err(CacheError::Uninitialized)));
+
+        let client =
+            || mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        init(client(), CacheConfig::new()).unwrap();
+        assert_eq!(get_parameter("service/parameter").await.unwrap(), "value");
+        assert!(matches!(
+            init(client(), CacheConfig::new()),
+            Err(CacheError::InvalidConfig { .. })
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::SystemTime;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+use aws_sdk_ssm::types::ParameterHistory;
+
+/// One version of a parameter, as returned by get_parameter_history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterHistoryEntry {
+    /// The value of the parameter at this version.
+    pub value: Option<String>,
+
+    /// The version number.
+    pub version: i64,
+
+    /// When this version was created.
+    pub last_modified: Option<SystemTime>,
+
+    /// The ARN of the AWS user who created this version.
+    pub modified_user: Option<String>,
+}
+
+impl From<ParameterHistory> for ParameterHistoryEntry {
+    fn from(history: ParameterHistory) -> Self {
+        ParameterHistoryEntry {
+            value: history.value,
+            version: history.version,
+            last_modified: history
+                .last_modified_date
+                .and_then(|date| SystemTime::try_from(date).ok()),
+            modified_user: history.last_modified_user,
+        }
+    }
+}
+
+/// A builder for the get_parameter_history method.
+pub struct GetParameterHistoryBuilder<'a, 'b, S> {
+    parameter_cache: &'a ParameterCache<S>,
+    parameter_name: &'b str,
+    with_decryption: bool,
+}
+
+impl<'a, 'b, S: CacheStore<CacheKey, CacheItem<String>>> GetParameterHistoryBuilder<'a, 'b, S> {
+    pub fn new(parameter_cache: &'a ParameterCache<S>, parameter_name: &'b str) -> Self {
+        GetParameterHistoryBuilder {
+            parameter_cache,
+            parameter_name,
+            with_decryption: parameter_cache.config.default_with_decryption,
+        }
+    }
+
+    /// Sets whether SecureString values are returned decrypted.
+    ///
+    /// Defaults to default_with_decryption from the CacheConfig.
+    pub fn with_decryption(mut self, with_decryption: bool) -> Self {
+        self.with_decryption = with_decryption;
+        self
+    }
+
+    /// Fetches every version of the parameter from AWS SSM, oldest first.
+    ///
+    /// History always comes straight from AWS; it is never read from or stored in the cache.
+    /// All pages of GetParameterHistory results are fetched before returning.
+    pub async fn send(self) -> Result<Vec<ParameterHistoryEntry>, CacheError> {
+        if let Some(e) = self
+            .parameter_cache
+            .dry_run_refusal(self.parameter_name, "GetParameterHistory")
+        {
+            return Err(e);
+        }
+        let mut entries = Vec::new();
+        let mut next_token = None;
+        loop {
+            self.parameter_cache.fetch_rate.acquire().await;
+            let output = self
+                .parameter_cache
+                .client
+                .get_parameter_history()
+                .name(self.parameter_name)
+                .with_decryption(self.with_decryption)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| CacheError::from_sdk_error(self.parameter_name, e))?;
+            entries.extend(
+                output
+                    .parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(ParameterHistoryEntry::from),
+            );
+            match output.next_token {
+                Some(token) if !token.is_empty() => next_token = Some(token),
+                _ => return Ok(entries),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_ssm_client;
+
+    #[tokio::test]
+    async fn get_parameter_history_pages_through_results() {
+        let mock_ssm_client = mock_ssm_client(|operation, body| {
+            assert_eq!(operation, "GetParameterHistory");
+            assert!(body.contains(r#""WithDecryption":true"#), "{}", body);
+            let response = if body.contains("page-2") {
+                r#"{"Parameters":[{"Name":"p","Value":"two","Version":2}]}"#
+            } else {
+                r#"{"Parameters":[{"Name":"p","Value":"one","Version":1,
+                    "LastModifiedUser":"arn:aws:iam::123456789012:user/admin",
+                    "LastModifiedDate":1700000000}],"NextToken":"page-2"}"#
+            };
+            (200, response.to_string())
+        });
+        let parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let history = parameter_cache
+            .get_parameter_history("p")
+            .with_decryption(true)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].value.as_deref(), Some("one"));
+        assert_eq!(
+            history[0].modified_user.as_deref(),
+            Some("arn:aws:iam::123456789012:user/admin")
+        );
+        assert!(history[0].last_modified.is_some());
+        assert_eq!(history[1].version, 2);
+        assert!(parameter_cache.cache.is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+
+use super::error::CacheError;
+
+const MAX_NAME_LENGTH: usize = 2048;
+const MAX_HIERARCHY_LEVELS: usize = 15;
+const RESERVED_PREFIXES: [&str; 2] = ["aws", "ssm"];
+const PUBLIC_PARAMETERS_PATH: &str = "/aws/service/";
+const SECRETS_MANAGER_PATH: &str = "/aws/reference/secretsmanager/";
+
+/// The key a parameter value is cached under.
+///
+/// The same parameter fetched with and without decryption, or from different accounts, has
+/// different values, so the effective decryption flag and account are part of the key. The
+/// tenant a value was requested for is too, so tenants sharing a cache never see each other's
+/// entries.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// The parameter name, including any version or label selector.
+    pub name: String,
+
+    /// Whether the value was fetched with decryption.
+    pub with_decryption: bool,
+
+    /// The registered account client the value was fetched with, or None for the default client.
+    pub account: Option<String>,
+
+    /// The tenant the value was requested for, or None if it wasn't requested for a tenant.
+    pub tenant: Option<String>,
+}
+
+impl CacheKey {
+    /// Returns the key for a parameter name and decryption flag, fetched with the default client.
+    pub fn new(name: impl Into<String>, with_decryption: bool) -> Self {
+        CacheKey {
+            name: name.into(),
+            with_decryption,
+            account: None,
+            tenant: None,
+        }
+    }
+
+    /// Returns the key with the account whose client the value was fetched with.
+    pub fn with_account(mut self, account: Option<String>) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Returns the key with the tenant the value was requested for.
+    pub fn with_tenant(mut self, tenant: Option<String>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+}
+
+/// Normalizes a parameter name to a single leading slash and no repeated or trailing slashes.
+///
+/// Names that are already normalized are borrowed rather than copied.
+pub(crate) fn normalize_name(name: &str) -> Cow<'_, str> {
+    let normalized = name.starts_with('/') && !name.contains("//") && !name.ends_with('/');
+    if normalized || name.is_empty() {
+        return Cow::Borrowed(name);
+    }
+    let segments: Vec<&str> = name
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    Cow::Owned(format!("/{}", segments.join("/")))
+}
+
+/// Checks a parameter name against the AWS SSM naming rules.
+///
+/// The request builders call this before fetching, so a name AWS SSM would reject fails
+/// without a round trip. The rules applied are:
+/// - the name is 1 to 2048 characters long;
+/// - it contains only the letters a-z and A-Z, the digits 0-9 and "_", ".", "-" and "/",
+///   optionally followed by ":" and a version or label selector without "/";
+/// - it has at most 15 levels of hierarchy, counting the parts between "/";
+/// - it doesn't begin with "aws" or "ssm" in any case, with or without a leading "/", except
+///   for the AWS public parameters under "/aws/service/".
+///
+/// ARNs and Secrets Manager references under "/aws/reference/secretsmanager/" are only checked
+/// for length. Returns a CacheError::InvalidName naming the first rule the name breaks.
+pub fn validate_name(name: &str) -> Result<(), CacheError> {
+    let invalid = |reason: &str| {
+        Err(CacheError::InvalidName {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        })
+    };
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return invalid("names must be 1 to 2048 characters long");
+    }
+    if name.starts_with("arn:") || name.starts_with(SECRETS_MANAGER_PATH) {
+        return Ok(());
+    }
+    let (path, selector) = match name.split_once(':') {
+        Some((path, selector)) => (path, Some(selector)),
+        None => (name, None),
+    };
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || "_.-/".contains(c);
+    if !path.chars().all(is_name_char) {
+        return invalid("names may only contain a-z, A-Z, 0-9, '_', '.', '-' and '/'");
+    }
+    if let Some(selector) = selector {
+        if selector.contains(':') {
+            return invalid("names may have only one version or label selector");
+        }
+        if selector.is_empty() || !selector.chars().all(|c| c != '/' && is_name_char(c)) {
+            return invalid("a selector after ':' must be a version number or label");
+        }
+    }
+    if path.split('/').filter(|level| !level.is_empty()).count() > MAX_HIERARCHY_LEVELS {
+        return invalid("names may have at most 15 levels of hierarchy");
+    }
+    let unrooted = path.strip_prefix('/').unwrap_or(path);
+    let reserved = RESERVED_PREFIXES.iter().any(|prefix| {
+        unrooted
+            .get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+    });
+    if reserved && !path.starts_with(PUBLIC_PARAMETERS_PATH) {
+        return invalid("names may not begin with 'aws' or 'ssm'");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_name_variants() {
+        for name in [
+            "service/param",
+            "/service/param",
+            "//service//param",
+            "service/param/",
+        ] {
+            assert_eq!(normalize_name(name), "/service/param", "{}", name);
+        }
+        assert_eq!(normalize_name("/service/param:3"), "/service/param:3");
+        assert!(matches!(normalize_name("/service/param"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn validate_name_rules() {
+        let deep = "/a".repeat(16);
+        for name in [
+            "",
+            "service param",
+            "service/param:",
+            "service/param:a/b",
+            "service/param:3:live",
+            &deep,
+            "aws-param",
+            "/SSM/param",
+        ] {
+            assert!(
+                matches!(validate_name(name), Err(CacheError::InvalidName { .. })),
+                "{}",
+                name
+            );
+        }
+        for name in [
+            "service/param",
+            "/service/param-1.2_x:3",
+            "/service/param:live",
+            "/aws/service/ami-amazon-linux-latest/al2023-ami-kernel-default-x86_64",
+            "/aws/reference/secretsmanager/db+password",
+            "arn:aws:ssm:eu-west-1:123456789012:parameter/shared",
+            "awesome",
+        ] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+    }
+}
@@ -31,8 +31,96 @@
 //! }
 //! ```
 
+mod access;
+mod batch;
+mod breaker;
+mod builder;
 mod cache;
 mod cache_item;
+mod clock;
+mod composite;
 mod config;
-pub use cache::ParameterCache;
-pub use config::CacheConfig;
+#[cfg(feature = "derive")]
+mod derive;
+mod describe;
+mod error;
+mod events;
+mod eviction;
+#[cfg(feature = "global")]
+pub mod global;
+mod history;
+mod key;
+#[cfg(feature = "serde")]
+mod manifest;
+#[cfg(feature = "test-util")]
+mod mock;
+mod parse;
+mod path;
+mod policy;
+mod preflight;
+mod put;
+mod rate;
+mod retry;
+mod secondary;
+mod shared;
+#[cfg(all(unix, feature = "signal"))]
+mod signal;
+mod snapshot;
+mod stats;
+mod store;
+mod tagged;
+#[cfg(test)]
+mod test_util;
+mod trace;
+mod warm;
+#[cfg(feature = "derive")]
+pub use aws_ssm_parameter_cache_derive::FromSsm;
+#[cfg(feature = "derive")]
+pub use derive::FromSsm;
+
+// Referenced by the code FromSsm generates, and not part of the public API
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::derive::{field, parse, value};
+}
+
+// Lets the code FromSsm generates resolve inside this crate's own tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as aws_ssm_parameter_cache;
+
+pub use access::AccessResult;
+pub use batch::GetParametersResult;
+pub use breaker::BreakerState;
+pub use builder::ParameterCacheBuilder;
+pub use cache::{
+    CachedParameter, Consistency, ParameterCache, ParameterForms, Timestamped, ValueSource,
+};
+pub use clock::{Clock, SystemClock};
+pub use composite::CompositeCache;
+pub use config::{CacheConfig, InflightOverflow};
+pub use describe::ParameterDescription;
+pub use error::CacheError;
+pub use events::CacheEvent;
+pub use eviction::EvictionReason;
+pub use history::ParameterHistoryEntry;
+pub use key::{validate_name, CacheKey};
+#[cfg(feature = "test-util")]
+pub use mock::MockParameterCache;
+pub use policy::{ParameterPolicy, PolicyPeriod};
+pub use preflight::{PreflightError, PreflightIssue};
+pub use put::PutParameterBuilder;
+pub use secondary::SecondaryStore;
+pub use shared::{
+    CacheValueGuard, ParameterProvider, ScheduledRefresh, SharedParameterCache,
+    SharedRefreshBuilder,
+};
+#[cfg(all(unix, feature = "signal"))]
+pub use signal::SignalReload;
+pub use snapshot::{CacheSnapshot, SnapshotEntry};
+pub use stats::{CacheStats, LatencyHistogram};
+pub use store::CacheStore;
+pub use tagged::TaggedValue;
+pub use warm::WarmResult;
+#[cfg(feature = "stream")]
+pub use warm::WarmStream;
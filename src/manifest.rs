@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::cache::{Consistency, ParameterCache};
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+
+// An entry in a manifest: a parameter name, or a name with its own TTL
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ManifestEntry {
+    Name(String),
+    WithTtl { name: String, ttl_seconds: u64 },
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Fetches every parameter listed in a JSON manifest file into the cache.
+    ///
+    /// The manifest is a JSON array whose entries are either parameter names or objects with
+    /// a name and a ttl_seconds overriding the cache_item_ttl for that parameter, e.g.
+    /// `["/service/url", {"name": "/service/token", "ttl_seconds": 60}]`. The parameters are
+    /// fetched one at a time, in the order listed, replacing any cached values.
+    ///
+    /// Returns a CacheError::Manifest if the file can't be read or isn't a valid manifest.
+    /// Otherwise returns the result of fetching each name.
+    pub async fn warm_from_manifest(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<HashMap<String, Result<(), CacheError>>, CacheError> {
+        let path = path.as_ref();
+        let manifest_error = |message: String| CacheError::Manifest {
+            path: path.display().to_string(),
+            message,
+        };
+        let manifest = std::fs::read_to_string(path).map_err(|e| manifest_error(e.to_string()))?;
+        let entries: Vec<ManifestEntry> =
+            serde_json::from_str(&manifest).map_err(|e| manifest_error(e.to_string()))?;
+
+        let mut results = HashMap::new();
+        for entry in entries {
+            let (name, ttl) = match entry {
+                ManifestEntry::Name(name) => (name, None),
+                ManifestEntry::WithTtl { name, ttl_seconds } => {
+                    (name, Some(Duration::from_secs(ttl_seconds)))
+                }
+            };
+            let mut builder = self.get_parameter(&name).consistency(Consistency::Fresh);
+            if let Some(ttl) = ttl {
+                builder = builder.ttl(ttl);
+            }
+            let result = builder.send().await.map(|_| ());
+            results.insert(name, result);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{error_response, mock_ssm_client, parameter_response};
+
+    #[tokio::test]
+    async fn warm_from_manifest_reports_each_name() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("missing") {
+            true => error_response("ParameterNotFound"),
+            false => (200, parameter_response("service/parameter", "value")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let path = std::env::temp_dir().join(format!("manifest-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"["service/parameter", {"name": "service/short", "ttl_seconds": 0}, "missing"]"#,
+        )
+        .unwrap();
+
+        let results = parameter_cache.warm_from_manifest(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(results["service/parameter"].is_ok());
+        assert!(results["service/short"].is_ok());
+        assert!(results["missing"].as_ref().unwrap_err().is_not_found());
+        assert!(parameter_cache
+            .get_parameter_age("service/parameter")
+            .is_some());
+        // cached with its zero TTL, so already expired
+        assert!(parameter_cache.get_parameter_age("service/short").is_none());
+        assert_eq!(parameter_cache.cache.len(), 2);
+        assert!(matches!(
+            parameter_cache.warm_from_manifest(&path).await,
+            Err(CacheError::Manifest { .. })
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_sdk_ssm::types::error::ParameterNotFound;
+
+use super::error::CacheError;
+use super::shared::ParameterProvider;
+
+/// An in-memory ParameterProvider for testing code that uses the cache.
+///
+/// Serves the values it was created with and never calls AWS. Getting a parameter it doesn't
+/// hold returns CacheError::NotFound, as the real cache would.
+#[derive(Clone, Debug, Default)]
+pub struct MockParameterCache {
+    parameters: HashMap<String, String>,
+}
+
+impl MockParameterCache {
+    /// Returns a mock cache serving the given parameter values, keyed by name.
+    pub fn new(parameters: HashMap<String, String>) -> Self {
+        MockParameterCache { parameters }
+    }
+
+    /// Returns the mock cache with another parameter value added.
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parameters.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for MockParameterCache {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        MockParameterCache::new(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+#[async_trait]
+impl ParameterProvider for MockParameterCache {
+    async fn get_parameter(&self, parameter_name: &str) -> Result<String, CacheError> {
+        match self.parameters.get(parameter_name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(CacheError::NotFound {
+                name: parameter_name.to_string(),
+                source: Box::new(aws_sdk_ssm::Error::ParameterNotFound(
+                    ParameterNotFound::builder()
+                        .message(format!(
+                            "Parameter {} not found in the mock",
+                            parameter_name
+                        ))
+                        .build(),
+                )),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_parameter_cache() {
+        let provider: Box<dyn ParameterProvider> = Box::new(
+            [("service/parameter", "value")]
+                .into_iter()
+                .collect::<MockParameterCache>()
+                .with_parameter("other", "other value"),
+        );
+
+        assert_eq!(
+            provider.get_parameter("service/parameter").await.unwrap(),
+            "value"
+        );
+        assert_eq!(
+            provider.get_parameter("other").await.unwrap(),
+            "other value"
+        );
+        assert!(provider
+            .get_parameter("missing")
+            .await
+            .unwrap_err()
+            .is_not_found());
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use super::error::CacheError;
+
+const TRUTHY: [&str; 5] = ["true", "t", "yes", "y", "on"];
+const FALSY: [&str; 5] = ["false", "f", "no", "n", "off"];
+
+/// Parses a parameter value into a bool.
+///
+/// Leading and trailing whitespace is ignored and matching is case-insensitive.
+/// - true: "true", "t", "yes", "y", "on", "1"
+/// - false: "false", "f", "no", "n", "off", "0"
+pub(crate) fn parse_bool(parameter_name: &str, value: &str) -> Result<bool, CacheError> {
+    let value = value.trim();
+    if value == "1" || TRUTHY.iter().any(|s| value.eq_ignore_ascii_case(s)) {
+        Ok(true)
+    } else if value == "0" || FALSY.iter().any(|s| value.eq_ignore_ascii_case(s)) {
+        Ok(false)
+    } else {
+        Err(CacheError::Parse {
+            name: parameter_name.to_string(),
+            message: "value is not a recognized boolean".to_string(),
+        })
+    }
+}
+
+/// Splits a parameter value into lines.
+///
+/// Lines are split on "\n" with any trailing "\r" removed, so "\r\n" line endings are accepted.
+/// Empty lines are kept unless skip_empty is set.
+pub(crate) fn parse_lines(value: &str, skip_empty: bool) -> Vec<String> {
+    value
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| !(skip_empty && line.is_empty()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Splits a StringList parameter value into a set of its elements.
+///
+/// Elements are split on "," and trimmed of surrounding whitespace; empty elements are left out.
+pub(crate) fn parse_string_set(value: &str) -> HashSet<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|element| !element.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Splits a StringList parameter value into its elements, in order.
+///
+/// Elements are split on "," and kept as they are; an empty value has no elements.
+pub(crate) fn parse_string_list(value: &str) -> Vec<String> {
+    match value.is_empty() {
+        true => Vec::new(),
+        false => value.split(',').map(str::to_string).collect(),
+    }
+}
+
+/// Parses a dotenv-style parameter value of KEY=VALUE lines into a map.
+///
+/// Keys and values are trimmed of surrounding whitespace and quotes are kept as they are. Blank
+/// lines and lines starting with "#" are skipped; any other line without "=" or with an empty
+/// key is an error. A repeated key keeps its last value.
+pub(crate) fn parse_env_map(
+    parameter_name: &str,
+    value: &str,
+) -> Result<HashMap<String, String>, CacheError> {
+    let mut map = HashMap::new();
+    for (index, line) in parse_lines(value, false).iter().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                map.insert(key.trim().to_string(), value.trim().to_string());
+            }
+            _ => {
+                return Err(CacheError::Parse {
+                    name: parameter_name.to_string(),
+                    message: format!("line {} is not KEY=VALUE", index + 1),
+                })
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Parses a humantime-style duration such as "30s", "5m" or "1h30m" into a Duration.
+///
+/// A duration is one or more whole numbers, each followed by a unit: "ns", "us", "ms", "s",
+/// "m", "h" or "d". Whitespace around and between the parts is ignored.
+pub(crate) fn parse_duration(parameter_name: &str, value: &str) -> Result<Duration, CacheError> {
+    let invalid = |reason: &str| CacheError::Parse {
+        name: parameter_name.to_string(),
+        message: format!("value is not a duration: {}", reason),
+    };
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err(invalid("it is empty"));
+    }
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| invalid("expected a number"))?;
+        rest = &rest[digits..];
+        let letters = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..letters] {
+            "ns" => Duration::from_nanos(1),
+            "us" => Duration::from_micros(1),
+            "ms" => Duration::from_millis(1),
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(3600),
+            "d" => Duration::from_secs(86400),
+            "" => return Err(invalid("missing a unit")),
+            _ => return Err(invalid("unknown unit")),
+        };
+        total = u32::try_from(amount)
+            .ok()
+            .and_then(|amount| unit.checked_mul(amount))
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| invalid("too large"))?;
+        rest = rest[letters..].trim_start();
+    }
+    Ok(total)
+}
+
+/// Returns the SHA-256 of a parameter value as lowercase hex.
+#[cfg(feature = "checksum")]
+pub(crate) fn sha256_hex(value: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Decodes a base64 (standard alphabet, padded) parameter value into bytes.
+#[cfg(feature = "base64")]
+pub(crate) fn parse_base64(parameter_name: &str, value: &str) -> Result<Vec<u8>, CacheError> {
+    use base64::Engine;
+
+    base64::engine::general_purpose::STANDARD
+        .decode(value.trim())
+        .map_err(|e| CacheError::Parse {
+            name: parameter_name.to_string(),
+            message: format!("value is not valid base64: {}", e),
+        })
+}
+
+/// Deserializes a JSON parameter value.
+#[cfg(feature = "serde")]
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(
+    parameter_name: &str,
+    value: &str,
+) -> Result<T, CacheError> {
+    serde_json::from_str(value).map_err(|e| CacheError::Parse {
+        name: parameter_name.to_string(),
+        message: format!("value is not valid JSON: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bool_accepted_spellings() {
+        for value in ["true", "TRUE", "Yes", "y", "on", "1", " t "] {
+            assert!(parse_bool("flag", value).unwrap(), "{}", value);
+        }
+        for value in ["false", "False", "NO", "n", "off", "0", "f\n"] {
+            assert!(!parse_bool("flag", value).unwrap(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn parse_bool_rejects_unknown_values() {
+        for value in ["", "2", "enabled", "tru"] {
+            match parse_bool("flag", value) {
+                Err(CacheError::Parse { name, .. }) => assert_eq!(name, "flag"),
+                other => panic!("unexpected result for {:?}: {:?}", value, other),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_lines_values() {
+        assert_eq!(parse_lines("a\r\nb\n\nc", false), vec!["a", "b", "", "c"]);
+        assert_eq!(parse_lines("a\r\nb\n\nc\n", true), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn parse_string_set_values() {
+        let expected: HashSet<String> = ["a", "b"].into_iter().map(str::to_string).collect();
+        assert_eq!(parse_string_set(" a,b , a,,"), expected);
+        assert!(parse_string_set("").is_empty());
+    }
+
+    #[test]
+    fn parse_string_list_values() {
+        assert_eq!(parse_string_list("a,b,,a"), vec!["a", "b", "", "a"]);
+        assert!(parse_string_list("").is_empty());
+    }
+
+    #[test]
+    fn parse_env_map_values() {
+        let map = parse_env_map("env", "# settings\nHOST = db\n\nPORT=5432\r\nURL=a=b\n").unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["HOST"], "db");
+        assert_eq!(map["PORT"], "5432");
+        assert_eq!(map["URL"], "a=b");
+        for value in ["HOST=db\nnot a pair", "=value"] {
+            assert!(matches!(
+                parse_env_map("env", value),
+                Err(CacheError::Parse { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_duration_values() {
+        assert_eq!(
+            parse_duration("timeout", "30s").unwrap(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            parse_duration("timeout", " 1h 30m ").unwrap(),
+            Duration::from_secs(5400)
+        );
+        assert_eq!(
+            parse_duration("timeout", "250ms").unwrap(),
+            Duration::from_millis(250)
+        );
+        for value in ["", "30", "s", "5 minutes", "1.5h", "-1s"] {
+            assert!(
+                matches!(
+                    parse_duration("timeout", value),
+                    Err(CacheError::Parse { .. })
+                ),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn sha256_hex_values() {
+        assert_eq!(
+            sha256_hex("hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn parse_base64_values() {
+        assert_eq!(parse_base64("blob", "aGVsbG8=").unwrap(), b"hello");
+        assert!(matches!(
+            parse_base64("blob", "not base64!"),
+            Err(CacheError::Parse { .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_json_values() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Database {
+            host: String,
+            port: u16,
+        }
+
+        assert_eq!(
+            parse_json::<Database>("db", r#"{"host":"db.internal","port":5432}"#).unwrap(),
+            Database {
+                host: "db.internal".to_string(),
+                port: 5432
+            }
+        );
+        assert!(matches!(
+            parse_json::<Database>("db", r#"{"host":"db.internal"}"#),
+            Err(CacheError::Parse { .. })
+        ));
+    }
+}
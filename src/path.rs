@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+use aws_sdk_ssm::types::ParameterStringFilter;
+
+/// A builder for the get_parameters_by_path method.
+pub struct GetParametersByPathBuilder<'a, 'b, S> {
+    parameter_cache: &'a mut ParameterCache<S>,
+    path: &'b str,
+    recursive: bool,
+    strip_prefix: bool,
+    with_decryption: bool,
+    filters: Vec<ParameterStringFilter>,
+    label: Option<String>,
+}
+
+impl<'a, 'b, S: CacheStore<CacheKey, CacheItem<String>>> GetParametersByPathBuilder<'a, 'b, S> {
+    pub fn new(parameter_cache: &'a mut ParameterCache<S>, path: &'b str) -> Self {
+        let with_decryption = parameter_cache.config.default_with_decryption;
+        GetParametersByPathBuilder {
+            parameter_cache,
+            path,
+            recursive: false,
+            strip_prefix: false,
+            with_decryption,
+            filters: Vec::new(),
+            label: None,
+        }
+    }
+
+    /// Also returns parameters in the levels below the path, not just those directly under it.
+    pub fn recursive(mut self) -> Self {
+        self.recursive = true;
+        self
+    }
+
+    /// Removes the queried path from the names in the returned map.
+    ///
+    /// For a path of "/myapp/db/", "/myapp/db/host" is returned as "host". Values are still
+    /// cached under their full names.
+    pub fn strip_prefix(mut self, strip_prefix: bool) -> Self {
+        self.strip_prefix = strip_prefix;
+        self
+    }
+
+    /// Sets whether SecureString values are fetched decrypted.
+    ///
+    /// Overrides default_with_decryption from the CacheConfig for this call.
+    pub fn with_decryption(mut self, with_decryption: bool) -> Self {
+        self.with_decryption = with_decryption;
+        self
+    }
+
+    /// Only returns parameters matching a filter, applied by AWS SSM.
+    ///
+    /// The key, option and values are those of the SDK's ParameterStringFilter, for example
+    /// `.filter("Type", "Equals", ["SecureString"])`. GetParametersByPath supports the Type,
+    /// KeyId, Label and tag:<key> filter keys. Calling filter more than once requires
+    /// parameters to match every filter.
+    pub fn filter<V: Into<String>>(
+        mut self,
+        key: impl Into<String>,
+        option: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let filter = ParameterStringFilter::builder()
+            .key(key)
+            .option(option)
+            .set_values(Some(values.into_iter().map(Into::into).collect()))
+            .build()
+            .expect("ParameterStringFilter key is set");
+        self.filters.push(filter);
+        self
+    }
+
+    /// Pins the results to the parameter versions with a label, e.g. for a blue/green rollout.
+    ///
+    /// Labels belong to individual parameter versions, not to the path: AWS SSM returns the
+    /// labeled version of each parameter under the path, and leaves out parameters with no
+    /// version carrying the label. Values are cached under the name with the label selector
+    /// appended (e.g. "/myapp/db/host:blue"), as get_parameter("/myapp/db/host:blue") would cache
+    /// them, while the returned map is keyed by the plain names.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        let label = label.into();
+        self.label = Some(label.clone());
+        self.filter("Label", "Equals", [label])
+    }
+
+    /// Fetches the parameters under the path from AWS SSM, keyed by name.
+    ///
+    /// Which parameters exist under a path can change at any time, so this always calls AWS,
+    /// fetching all pages of GetParametersByPath results. Each value found is stored in the cache
+    /// under its full name (with any label selector) with the cache_item_ttl from the CacheConfig.
+    pub async fn send(self) -> Result<HashMap<String, String>, CacheError> {
+        if let Some(e) = self
+            .parameter_cache
+            .dry_run_refusal(self.path, "GetParametersByPath")
+        {
+            return Err(e);
+        }
+        let mut parameters = HashMap::new();
+        let mut next_token = None;
+        loop {
+            self.parameter_cache.fetch_rate.acquire().await;
+            let started = Instant::now();
+            let output = self
+                .parameter_cache
+                .client
+                .get_parameters_by_path()
+                .path(self.path)
+                .recursive(self.recursive)
+                .with_decryption(self.with_decryption)
+                .set_parameter_filters((!self.filters.is_empty()).then(|| self.filters.clone()))
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| CacheError::from_sdk_error(self.path, e));
+            self.parameter_cache
+                .record_fetch(&[self.path], started, &output);
+            let output = output?;
+
+            let ttl = self.parameter_cache.config.cache_item_ttl;
+            for mut parameter in output.parameters.unwrap_or_default() {
+                if let (Some(name), Some(value)) = (parameter.name.clone(), parameter.value.take())
+                {
+                    let cached_name = match &self.label {
+                        Some(label) => format!("{}:{}", name, label),
+                        None => name.clone(),
+                    };
+                    let mut cache_item = CacheItem::new_with_clock(
+                        value.clone(),
+                        ttl,
+                        self.parameter_cache.clock.clone(),
+                    );
+                    cache_item.set_metadata(&parameter);
+                    self.parameter_cache
+                        .insert(CacheKey::new(cached_name, self.with_decryption), cache_item);
+                    parameters.insert(self.returned_name(name), value);
+                }
+            }
+            match output.next_token {
+                Some(token) if !token.is_empty() => next_token = Some(token),
+                _ => return Ok(parameters),
+            }
+        }
+    }
+
+    fn returned_name(&self, name: String) -> String {
+        match name.strip_prefix(self.path) {
+            Some(relative) if self.strip_prefix => relative.trim_start_matches('/').to_string(),
+            _ => name,
+        }
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Fetches every parameter under a path as a flat configuration map with dotted keys.
+    ///
+    /// The path is fetched recursively, as with get_parameters_by_path, and each name relative
+    /// to the path has its slashes replaced with dots: under "/myapp/", "/myapp/db/host" becomes
+    /// "db.host". That is the key format of layered configuration crates, so the map can be
+    /// merged into them, e.g. with config's `ConfigBuilder::set_override` for each entry or as a
+    /// figment `Serialized` provider.
+    pub async fn get_config_map(
+        &mut self,
+        path: &str,
+    ) -> Result<HashMap<String, String>, CacheError> {
+        let parameters = self
+            .get_parameters_by_path(path)
+            .recursive()
+            .strip_prefix(true)
+            .send()
+            .await?;
+        Ok(parameters
+            .into_iter()
+            .map(|(name, value)| (name.trim_matches('/').replace('/', "."), value))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_ssm_client;
+
+    #[tokio::test]
+    async fn get_parameters_by_path_strip_prefix() {
+        let mock_ssm_client = mock_ssm_client(|operation, body| {
+            assert_eq!(operation, "GetParametersByPath");
+            let response = if body.contains("page-2") {
+                r#"{"Parameters":[{"Name":"/myapp/db/port","Type":"String","Value":"5432"}]}"#
+            } else {
+                r#"{"Parameters":[{"Name":"/myapp/db/host","Type":"String","Value":"localhost"}],
+                    "NextToken":"page-2"}"#
+            };
+            (200, response.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let parameters = parameter_cache
+            .get_parameters_by_path("/myapp/db/")
+            .strip_prefix(true)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            parameters.get("host").map(String::as_str),
+            Some("localhost")
+        );
+        assert_eq!(parameters.get("port").map(String::as_str), Some("5432"));
+        // the cache holds the full names
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("/myapp/db/host", false))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn get_parameters_by_path_label() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(
+                body.contains(r#"{"Key":"Label","Option":"Equals","Values":["blue"]}"#),
+                "{}",
+                body
+            );
+            let response =
+                r#"{"Parameters":[{"Name":"/myapp/db/host","Type":"String","Value":"blue-host"}]}"#;
+            (200, response.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let parameters = parameter_cache
+            .get_parameters_by_path("/myapp/db/")
+            .label("blue")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            parameters.get("/myapp/db/host").map(String::as_str),
+            Some("blue-host")
+        );
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("/myapp/db/host:blue", false))
+            .is_some());
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("/myapp/db/host", false))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn get_config_map_dotted_keys() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(body.contains(r#""Recursive":true"#), "{}", body);
+            let response = r#"{"Parameters":[
+                {"Name":"/myapp/db/host","Type":"String","Value":"localhost"},
+                {"Name":"/myapp/log_level","Type":"String","Value":"debug"}]}"#;
+            (200, response.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let config = parameter_cache.get_config_map("/myapp").await.unwrap();
+
+        assert_eq!(config.get("db.host").map(String::as_str), Some("localhost"));
+        assert_eq!(config.get("log_level").map(String::as_str), Some("debug"));
+        assert_eq!(config.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_parameters_by_path_filter() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(
+                body.contains(
+                    r#""ParameterFilters":[{"Key":"Type","Option":"Equals","Values":["SecureString"]}]"#
+                ),
+                "{}",
+                body
+            );
+            (200, r#"{"Parameters":[]}"#.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let parameters = parameter_cache
+            .get_parameters_by_path("/myapp/")
+            .filter("Type", "Equals", ["SecureString"])
+            .send()
+            .await
+            .unwrap();
+        assert!(parameters.is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::SystemTime;
+
+use aws_sdk_ssm::primitives::{DateTime, DateTimeFormat};
+
+/// A period of whole days or hours, used by the notification policies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyPeriod {
+    /// A number of days.
+    Days(u32),
+    /// A number of hours.
+    Hours(u32),
+}
+
+impl PolicyPeriod {
+    // The amount and unit attributes of the period
+    fn attributes(self) -> (u32, &'static str) {
+        match self {
+            PolicyPeriod::Days(days) => (days, "Days"),
+            PolicyPeriod::Hours(hours) => (hours, "Hours"),
+        }
+    }
+}
+
+/// A policy attached to an Advanced parameter when it is written with PutParameterBuilder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterPolicy {
+    /// AWS SSM deletes the parameter at this time, e.g. to force rotation.
+    Expiration(SystemTime),
+
+    /// AWS SSM sends an EventBridge notification this long before the parameter expires.
+    ExpirationNotification(PolicyPeriod),
+
+    /// AWS SSM sends an EventBridge notification if the parameter hasn't changed for this long.
+    NoChangeNotification(PolicyPeriod),
+}
+
+impl ParameterPolicy {
+    /// Returns the policy in the JSON form AWS SSM expects.
+    pub fn to_json(&self) -> String {
+        let (policy_type, attributes) = match self {
+            ParameterPolicy::Expiration(at) => {
+                let timestamp = DateTime::from(*at)
+                    .fmt(DateTimeFormat::DateTime)
+                    .unwrap_or_default();
+                ("Expiration", format!(r#"{{"Timestamp":"{}"}}"#, timestamp))
+            }
+            ParameterPolicy::ExpirationNotification(period) => {
+                let (before, unit) = period.attributes();
+                (
+                    "ExpirationNotification",
+                    format!(r#"{{"Before":"{}","Unit":"{}"}}"#, before, unit),
+                )
+            }
+            ParameterPolicy::NoChangeNotification(period) => {
+                let (after, unit) = period.attributes();
+                (
+                    "NoChangeNotification",
+                    format!(r#"{{"After":"{}","Unit":"{}"}}"#, after, unit),
+                )
+            }
+        };
+        format!(
+            r#"{{"Type":"{}","Version":"1.0","Attributes":{}}}"#,
+            policy_type, attributes
+        )
+    }
+}
+
+// The Policies request value for a list of policies: a JSON array of their JSON forms
+pub(crate) fn policies_json(policies: &[ParameterPolicy]) -> String {
+    let policies: Vec<String> = policies.iter().map(ParameterPolicy::to_json).collect();
+    format!("[{}]", policies.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn policies_json_forms() {
+        let expires = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            policies_json(&[
+                ParameterPolicy::Expiration(expires),
+                ParameterPolicy::NoChangeNotification(PolicyPeriod::Days(20)),
+            ]),
+            concat!(
+                r#"[{"Type":"Expiration","Version":"1.0","Attributes":{"Timestamp":"2023-11-14T22:13:20Z"}},"#,
+                r#"{"Type":"NoChangeNotification","Version":"1.0","Attributes":{"After":"20","Unit":"Days"}}]"#
+            )
+        );
+        assert_eq!(
+            ParameterPolicy::ExpirationNotification(PolicyPeriod::Hours(6)).to_json(),
+            r#"{"Type":"ExpirationNotification","Version":"1.0","Attributes":{"Before":"6","Unit":"Hours"}}"#
+        );
+    }
+}
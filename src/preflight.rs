@@ -0,0 +1,205 @@
+use std::fmt;
+
+use super::access::AccessResult;
+use super::builder::validate_config;
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::put::{MAX_ADVANCED_VALUE_BYTES, MAX_STANDARD_VALUE_BYTES};
+use super::store::CacheStore;
+
+// The service setting holding the tier AWS SSM gives parameters written without one
+const DEFAULT_TIER_SETTING: &str = "/ssm/parameter-store/default-parameter-tier";
+
+/// A problem found by ParameterCache::preflight.
+#[derive(Debug)]
+pub enum PreflightIssue {
+    /// The Cache Configuration can't work as set, or doesn't fit AWS SSM's limits.
+    Config(String),
+
+    /// AWS SSM couldn't be reached, or failed a request for another reason.
+    Unreachable(CacheError),
+
+    /// The caller isn't allowed to make a request, named by the operation or parameter.
+    Denied(String),
+
+    /// A parameter the service needs doesn't exist.
+    NotFound(String),
+}
+
+impl fmt::Display for PreflightIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreflightIssue::Config(message) => write!(f, "Invalid configuration: {}", message),
+            PreflightIssue::Unreachable(e) => write!(f, "AWS SSM request failed: {}", e),
+            PreflightIssue::Denied(target) => write!(f, "Access denied to {}", target),
+            PreflightIssue::NotFound(name) => write!(f, "Parameter '{}' not found", name),
+        }
+    }
+}
+
+/// The problems found by ParameterCache::preflight, of which there is at least one.
+#[derive(Debug)]
+pub struct PreflightError {
+    /// Every problem found, in the order they were checked.
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl fmt::Display for PreflightError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Preflight found {} issue(s)", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "; {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PreflightError {}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Checks the cache is ready to serve, e.g. for a readiness probe before taking traffic.
+    ///
+    /// Runs every check and reports all the problems found together:
+    /// - the Cache Configuration is checked as ParameterCacheBuilder::build checks it, and
+    ///   max_value_bytes against the largest values AWS SSM stores;
+    /// - AWS SSM is reached with a DescribeParameters call, which also checks that the caller
+    ///   may list parameters;
+    /// - if max_value_bytes allows values over 4 KB, the account's default parameter tier is
+    ///   read, since values that large need the Advanced tier (skipped if the caller may not
+    ///   read service settings);
+    /// - each named parameter is read with check_access.
+    ///
+    /// Nothing is stored in the cache.
+    pub async fn preflight(&self, names: &[&str]) -> Result<(), PreflightError> {
+        let mut issues = Vec::new();
+        if let Err(CacheError::InvalidConfig { message }) = validate_config(&self.config) {
+            issues.push(PreflightIssue::Config(message));
+        }
+        let max_value_bytes = self.config.max_value_bytes.unwrap_or_default();
+        if max_value_bytes > MAX_ADVANCED_VALUE_BYTES {
+            issues.push(PreflightIssue::Config(format!(
+                "max_value_bytes of {} is above the {} bytes AWS SSM stores",
+                max_value_bytes, MAX_ADVANCED_VALUE_BYTES
+            )));
+        }
+
+        // AWS isn't called in dry run mode, so can't be checked
+        if let Some(e) = self.dry_run_refusal("DescribeParameters", "DescribeParameters") {
+            issues.push(PreflightIssue::Unreachable(e));
+            return Err(PreflightError { issues });
+        }
+
+        self.fetch_rate.acquire().await;
+        let listed = self
+            .client
+            .describe_parameters()
+            .max_results(1)
+            .send()
+            .await;
+        match listed.map_err(|e| CacheError::from_sdk_error("DescribeParameters", e)) {
+            Ok(_) => {}
+            Err(CacheError::AccessDenied { .. }) => {
+                issues.push(PreflightIssue::Denied("DescribeParameters".to_string()))
+            }
+            Err(e) => issues.push(PreflightIssue::Unreachable(e)),
+        }
+
+        if max_value_bytes > MAX_STANDARD_VALUE_BYTES {
+            self.fetch_rate.acquire().await;
+            let setting = self
+                .client
+                .get_service_setting()
+                .setting_id(DEFAULT_TIER_SETTING)
+                .send()
+                .await;
+            match setting.map_err(|e| CacheError::from_sdk_error(DEFAULT_TIER_SETTING, e)) {
+                Ok(output) => {
+                    let tier = output
+                        .service_setting
+                        .and_then(|setting| setting.setting_value);
+                    if tier.as_deref() == Some("Standard") {
+                        issues.push(PreflightIssue::Config(format!(
+                            "max_value_bytes of {} allows values over 4 KB, but the account's \
+                             default parameter tier is Standard",
+                            max_value_bytes
+                        )));
+                    }
+                }
+                Err(CacheError::AccessDenied { .. }) => {}
+                Err(e) => issues.push(PreflightIssue::Unreachable(e)),
+            }
+        }
+
+        for (name, access) in self.check_access(names).await {
+            match access {
+                AccessResult::Ok => {}
+                AccessResult::Denied => issues.push(PreflightIssue::Denied(name)),
+                AccessResult::NotFound => issues.push(PreflightIssue::NotFound(name)),
+                AccessResult::Failed(e) => issues.push(PreflightIssue::Unreachable(e)),
+            }
+        }
+
+        match issues.is_empty() {
+            true => Ok(()),
+            false => Err(PreflightError { issues }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{error_response, mock_ssm_client, parameter_response};
+
+    #[tokio::test]
+    async fn preflight_passes() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| match operation {
+            "DescribeParameters" => (200, r#"{"Parameters":[]}"#.to_string()),
+            _ => (200, parameter_response("service/parameter", "value")),
+        });
+        let parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        parameter_cache
+            .preflight(&["service/parameter"])
+            .await
+            .unwrap();
+        assert_eq!(parameter_cache.cache.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn preflight_reports_every_issue() {
+        let mock_ssm_client = mock_ssm_client(|operation, body| match operation {
+            "DescribeParameters" => error_response("AccessDeniedException"),
+            "GetServiceSetting" => (
+                200,
+                r#"{"ServiceSetting":{"SettingValue":"Standard"}}"#.to_string(),
+            ),
+            _ if body.contains("missing") => error_response("ParameterNotFound"),
+            _ => error_response("AccessDeniedException"),
+        });
+        let config = CacheConfig::new().max_value_bytes(6000);
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+        // as a cache built with new_with_config isn't checked
+        parameter_cache.config.max_cache_size = 0;
+
+        let error = parameter_cache
+            .preflight(&["missing", "secret"])
+            .await
+            .unwrap_err();
+        let issues: Vec<String> = error.issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            [
+                "Invalid configuration: max_cache_size must be at least 1",
+                "Access denied to DescribeParameters",
+                "Invalid configuration: max_value_bytes of 6000 allows values over 4 KB, but the \
+                 account's default parameter tier is Standard",
+                "Parameter 'missing' not found",
+                "Access denied to secret",
+            ]
+        );
+    }
+}
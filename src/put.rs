@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use tokio::task::JoinSet;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::{validate_name, CacheKey};
+use super::policy::{policies_json, ParameterPolicy};
+use super::store::CacheStore;
+use super::trace::Instrument;
+use aws_sdk_ssm::types::{ParameterTier, ParameterType};
+
+// The largest values AWS SSM accepts for Standard parameters and for the other tiers
+pub(crate) const MAX_STANDARD_VALUE_BYTES: usize = 4096;
+pub(crate) const MAX_ADVANCED_VALUE_BYTES: usize = 8192;
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Returns a builder for writing a single parameter to AWS SSM, with options such as the
+    /// tier.
+    ///
+    /// Write the parameter with send().
+    pub fn put_parameter<'a>(
+        &'a mut self,
+        name: &str,
+        value: &str,
+        parameter_type: ParameterType,
+    ) -> PutParameterBuilder<'a, S> {
+        PutParameterBuilder {
+            parameter_cache: self,
+            name: name.to_string(),
+            value: value.to_string(),
+            parameter_type,
+            tier: None,
+            policies: Vec::new(),
+        }
+    }
+
+    /// Writes several parameters to AWS SSM, overwriting any existing values.
+    ///
+    /// Each entry is a parameter name, value and type. AWS SSM has no batch PutParameter, so
+    /// the parameters are written with concurrent PutParameter calls, at most
+    /// max_concurrent_requests at a time. Each successful write also stores the value in the
+    /// cache with the cache_item_ttl from the CacheConfig, replacing any cached value;
+    /// SecureString values are cached as decrypted values.
+    ///
+    /// Names that break the AWS SSM naming rules (see validate_name) aren't sent.
+    ///
+    /// Returns the result for each name: the new parameter version, or the error writing it.
+    pub async fn put_parameters(
+        &mut self,
+        entries: &[(&str, &str, ParameterType)],
+    ) -> HashMap<String, Result<i64, CacheError>> {
+        let mut results = HashMap::new();
+        let mut writes = JoinSet::new();
+        for &(name, value, ref parameter_type) in entries {
+            if let Err(e) = validate_name(name) {
+                results.insert(name.to_string(), Err(e));
+                continue;
+            }
+            if let Some(e) = self.dry_run_refusal(name, "PutParameter") {
+                results.insert(name.to_string(), Err(e));
+                continue;
+            }
+            let client = self.client.clone();
+            let semaphore = self.request_limit.clone();
+            let fetch_rate = self.fetch_rate.clone();
+            let (name, value, parameter_type) =
+                (name.to_string(), value.to_string(), parameter_type.clone());
+            writes.spawn(
+                async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    fetch_rate.acquire().await;
+                    let result = client
+                        .put_parameter()
+                        .name(&name)
+                        .value(&value)
+                        .r#type(parameter_type.clone())
+                        .overwrite(true)
+                        .send()
+                        .await
+                        .map_err(|e| CacheError::from_sdk_error(&name, e));
+                    (name, value, parameter_type, result)
+                }
+                .in_current_span(),
+            );
+        }
+
+        while let Some(write) = writes.join_next().await {
+            let (name, value, parameter_type, result) = write.expect("put_parameter task panicked");
+            let result = result.map(|output| {
+                self.cache_written(&name, value, &parameter_type, output.version, output.tier);
+                output.version
+            });
+            results.insert(name, result);
+        }
+        results
+    }
+
+    // Caches a value just written to AWS SSM, replacing any cached value
+    fn cache_written(
+        &mut self,
+        name: &str,
+        value: String,
+        parameter_type: &ParameterType,
+        version: i64,
+        tier: Option<ParameterTier>,
+    ) {
+        let with_decryption = match parameter_type {
+            ParameterType::SecureString => true,
+            _ => self.config.default_with_decryption,
+        };
+        // the value fetched with the other decryption setting is now out of date
+        self.remove(&CacheKey::new(name, !with_decryption));
+        let ttl = self.config.cache_item_ttl;
+        let mut cache_item = CacheItem::new(value, ttl);
+        cache_item.version = Some(version);
+        cache_item.tier = tier;
+        self.insert(CacheKey::new(name, with_decryption), cache_item);
+    }
+}
+
+/// A builder for writing a single parameter, returned by ParameterCache::put_parameter.
+pub struct PutParameterBuilder<'a, S> {
+    parameter_cache: &'a mut ParameterCache<S>,
+    name: String,
+    value: String,
+    parameter_type: ParameterType,
+    tier: Option<ParameterTier>,
+    policies: Vec<ParameterPolicy>,
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> PutParameterBuilder<'_, S> {
+    /// Sets the tier of the parameter: Standard, Advanced or Intelligent-Tiering.
+    ///
+    /// Without a tier AWS SSM uses the account's default tier. Values larger than 4 KB need
+    /// the Advanced or Intelligent-Tiering tier, and no tier accepts values larger than 8 KB.
+    pub fn tier(mut self, tier: ParameterTier) -> Self {
+        self.tier = Some(tier);
+        self
+    }
+
+    /// Attaches policies to the parameter, such as an expiration that forces rotation.
+    ///
+    /// Policies need the Advanced or Intelligent-Tiering tier to be set with tier, and are
+    /// rejected with a CacheError::InvalidPolicy before AWS is called otherwise. They replace
+    /// any policies already attached.
+    pub fn policies(mut self, policies: &[ParameterPolicy]) -> Self {
+        self.policies = policies.to_vec();
+        self
+    }
+
+    /// Writes the parameter to AWS SSM, overwriting any existing value, and caches the value
+    /// with the tier AWS SSM reports, as put_parameters does.
+    ///
+    /// A value too large for the requested tier is rejected with a CacheError::ValueTooLarge
+    /// before AWS is called. Returns the new parameter version.
+    pub async fn send(self) -> Result<i64, CacheError> {
+        validate_name(&self.name)?;
+        let max_bytes = match self.tier {
+            Some(ParameterTier::Standard) => MAX_STANDARD_VALUE_BYTES,
+            _ => MAX_ADVANCED_VALUE_BYTES,
+        };
+        if self.value.len() > max_bytes {
+            return Err(CacheError::ValueTooLarge {
+                name: self.name,
+                bytes: self.value.len(),
+                max_bytes,
+            });
+        }
+        let policies = match (&self.policies[..], &self.tier) {
+            ([], _) => None,
+            (_, Some(ParameterTier::Advanced | ParameterTier::IntelligentTiering)) => {
+                Some(policies_json(&self.policies))
+            }
+            _ => {
+                return Err(CacheError::InvalidPolicy {
+                    name: self.name,
+                    message: "policies need the Advanced or Intelligent-Tiering tier".to_string(),
+                })
+            }
+        };
+        let parameter_cache = self.parameter_cache;
+        if let Some(e) = parameter_cache.dry_run_refusal(&self.name, "PutParameter") {
+            return Err(e);
+        }
+        parameter_cache.fetch_rate.acquire().await;
+        let output = parameter_cache
+            .client
+            .put_parameter()
+            .name(&self.name)
+            .value(&self.value)
+            .r#type(self.parameter_type.clone())
+            .set_tier(self.tier)
+            .set_policies(policies)
+            .overwrite(true)
+            .send()
+            .await
+            .map_err(|e| CacheError::from_sdk_error(&self.name, e))?;
+        parameter_cache.cache_written(
+            &self.name,
+            self.value,
+            &self.parameter_type,
+            output.version,
+            output.tier,
+        );
+        Ok(output.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::policy::PolicyPeriod;
+    use crate::test_util::{error_response, mock_ssm_client};
+
+    #[tokio::test]
+    async fn put_parameters_refused_in_dry_run() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| panic!("called {}", operation));
+        let config = CacheConfig::new()
+            .dry_run(true)
+            .dry_run_placeholder("placeholder");
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let result = parameter_cache
+            .put_parameter("a", "value", ParameterType::String)
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::DryRun { .. })));
+        let results = parameter_cache
+            .put_parameters(&[("b", "value", ParameterType::String)])
+            .await;
+        assert!(matches!(results["b"], Err(CacheError::DryRun { .. })));
+        assert!(parameter_cache.cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn put_parameter_with_tier() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(body.contains(r#""Tier":"Intelligent-Tiering""#));
+            (200, r#"{"Version":2,"Tier":"Advanced"}"#.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let version = parameter_cache
+            .put_parameter("large", &"x".repeat(5000), ParameterType::String)
+            .tier(ParameterTier::IntelligentTiering)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(
+            parameter_cache.get_parameter_tier("large"),
+            Some(ParameterTier::Advanced)
+        );
+
+        let result = parameter_cache
+            .put_parameter("large", &"x".repeat(5000), ParameterType::String)
+            .tier(ParameterTier::Standard)
+            .send()
+            .await;
+        assert!(matches!(
+            result,
+            Err(CacheError::ValueTooLarge {
+                max_bytes: 4096,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_parameter_with_policies() {
+        let mock_ssm_client = mock_ssm_client(|_, body| {
+            assert!(body.contains(r#""Policies":"[{\"Type\":\"NoChangeNotification\""#));
+            (200, r#"{"Version":1,"Tier":"Advanced"}"#.to_string())
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        let policies = [ParameterPolicy::NoChangeNotification(PolicyPeriod::Days(
+            30,
+        ))];
+
+        let version = parameter_cache
+            .put_parameter("rotated", "value", ParameterType::SecureString)
+            .tier(ParameterTier::Advanced)
+            .policies(&policies)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(version, 1);
+
+        let result = parameter_cache
+            .put_parameter("rotated", "value", ParameterType::SecureString)
+            .policies(&policies)
+            .send()
+            .await;
+        assert!(matches!(result, Err(CacheError::InvalidPolicy { .. })));
+    }
+
+    #[tokio::test]
+    async fn put_parameters_writes_through() {
+        let mock_ssm_client = mock_ssm_client(|operation, body| {
+            assert_eq!(operation, "PutParameter");
+            match body.contains("denied") {
+                true => error_response("AccessDeniedException"),
+                false => (200, r#"{"Version":3,"Tier":"Standard"}"#.to_string()),
+            }
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let results = parameter_cache
+            .put_parameters(&[
+                ("plain", "value", ParameterType::String),
+                ("secret", "hunter2", ParameterType::SecureString),
+                ("denied", "value", ParameterType::String),
+            ])
+            .await;
+
+        assert_eq!(results["plain"].as_ref().ok(), Some(&3));
+        assert_eq!(results["secret"].as_ref().ok(), Some(&3));
+        assert!(matches!(
+            results["denied"],
+            Err(CacheError::AccessDenied { .. })
+        ));
+        assert_eq!(
+            parameter_cache.get_parameter("plain").send().await.unwrap(),
+            "value"
+        );
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("secret", true))
+            .is_some());
+        assert_eq!(parameter_cache.cache.len(), 2);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// A token bucket capping the sustained rate of requests to AWS SSM.
+///
+/// The bucket holds up to a second's worth of requests (at least one), so a burst after an
+/// idle period is let through at once and later requests are spaced out to the rate.
+pub(crate) struct RateLimiter {
+    per_second: Option<f64>,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Returns a limiter allowing per_second requests a second, or any number if None.
+    pub(crate) fn new(per_second: Option<f64>) -> Self {
+        let per_second = per_second.filter(|rate| rate.is_finite() && *rate > 0.0);
+        RateLimiter {
+            per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: per_second.map_or(0.0, burst),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until another request can be made within the rate.
+    pub(crate) async fn acquire(&self) {
+        let Some(per_second) = self.per_second else {
+            return;
+        };
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let refill = now.duration_since(bucket.updated).as_secs_f64() * per_second;
+                bucket.tokens = (bucket.tokens + refill).min(burst(per_second));
+                bucket.updated = now;
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / per_second)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+// The most requests let through at once
+fn burst(per_second: f64) -> f64 {
+    per_second.max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limiter_spaces_out_requests() {
+        let limiter = RateLimiter::new(Some(2.0));
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(1500));
+        assert!(start.elapsed() < Duration::from_millis(1600));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limiter_unlimited() {
+        let limiter = RateLimiter::new(None);
+        let start = Instant::now();
+
+        for _ in 0..100 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
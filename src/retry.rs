@@ -0,0 +1,70 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aws_sdk_ssm::error::ProvideErrorMetadata;
+
+use super::cache::JitterSource;
+use super::error::CacheError;
+use super::rate::RateLimiter;
+use super::trace;
+
+// The most a request waits in total between retries while it is throttled
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(20);
+
+/// How a request to AWS SSM is retried when AWS SSM, or KMS decrypting a value, throttles it.
+///
+/// Every fetch path sends its requests through this, so get_parameter, get_parameters and the
+/// warm methods retry KMS throttling the same way.
+#[derive(Clone)]
+pub(crate) struct RetryPolicy {
+    pub(crate) max_retries: u32,
+    pub(crate) base_backoff: Duration,
+    pub(crate) jitter_source: JitterSource,
+    pub(crate) fetch_rate: Arc<RateLimiter>,
+}
+
+impl RetryPolicy {
+    /// Sends a request until it isn't throttled, max_retries retries have been made or the waits
+    /// between them add up to 20 seconds, waiting for the fetch rate before each attempt.
+    ///
+    /// The wait before each retry starts at base_backoff and doubles, and is jittered to between
+    /// half and all of that, so callers throttled together don't retry in lockstep. The last wait
+    /// is cut short to keep within the total.
+    pub(crate) async fn send<T, E, F, Fut>(&self, send: F) -> Result<T, E>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: ProvideErrorMetadata,
+    {
+        let mut retries = 0;
+        let mut waited = Duration::ZERO;
+        loop {
+            self.fetch_rate.acquire().await;
+            match send().await {
+                Err(e)
+                    if retries < self.max_retries
+                        && waited < MAX_RETRY_WAIT
+                        && CacheError::is_throttling(&e) =>
+                {
+                    let backoff = self.backoff(retries).min(MAX_RETRY_WAIT - waited);
+                    trace::debug!(retries, ?backoff, "throttled, retrying");
+                    tokio::time::sleep(backoff).await;
+                    waited += backoff;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // The jittered wait before a retry, after this many retries
+    fn backoff(&self, retries: u32) -> Duration {
+        let backoff = self
+            .base_backoff
+            .saturating_mul(1 << retries.min(16))
+            .min(MAX_RETRY_WAIT);
+        let jitter = (self.jitter_source)().clamp(0.0, 1.0);
+        backoff.mul_f64(0.5 + jitter / 2.0)
+    }
+}
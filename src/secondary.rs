@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::key::CacheKey;
+use super::store::CacheStore;
+
+/// A second-level cache consulted when a parameter isn't in the in-process cache, before
+/// fetching it from AWS SSM.
+///
+/// Implement this over a store shared between processes, such as Redis or a file, so that a
+/// warm cache can be shared across the processes on a host. A store that fails should log the
+/// failure and behave as a miss; it never fails a request.
+#[async_trait]
+pub trait SecondaryStore: Send + Sync {
+    /// Returns the value stored for a key, if any and it hasn't expired.
+    async fn get(&self, key: &CacheKey) -> Option<String>;
+
+    /// Stores a value for a key, to expire after the TTL.
+    async fn put(&self, key: &CacheKey, value: &str, ttl: Duration);
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Adds a second-level cache below the in-process cache.
+    ///
+    /// On a miss, get_parameter looks the parameter up in the secondary store before fetching
+    /// it from AWS SSM, and caches a value found there in-process. Values fetched from AWS SSM
+    /// are stored in both. The secondary store isn't consulted for Consistency::Fresh, nor when
+    /// min_version or max_age is set, since it doesn't record versions or ages, nor in dry run
+    /// mode or with caching disabled.
+    pub fn with_secondary_store(mut self, secondary: impl SecondaryStore + 'static) -> Self {
+        self.secondary = Some(Arc::new(secondary));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::ValueSource;
+    use crate::test_util::{mock_ssm_client, parameter_response};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct MapSecondary(Arc<Mutex<HashMap<String, String>>>);
+
+    #[async_trait]
+    impl SecondaryStore for MapSecondary {
+        async fn get(&self, key: &CacheKey) -> Option<String> {
+            self.0.lock().unwrap().get(&key.name).cloned()
+        }
+
+        async fn put(&self, key: &CacheKey, value: &str, _ttl: Duration) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(key.name.clone(), value.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn secondary_store_consulted_before_aws() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("fetched", "value"))
+        });
+        let secondary = MapSecondary::default();
+        secondary
+            .0
+            .lock()
+            .unwrap()
+            .insert("shared".to_string(), "shared value".to_string());
+        let mut parameter_cache =
+            ParameterCache::new(mock_ssm_client).with_secondary_store(secondary.clone());
+
+        let (value, source) = parameter_cache
+            .get_parameter("shared")
+            .send_with_source()
+            .await
+            .unwrap();
+        assert_eq!(
+            (value.as_str(), source),
+            ("shared value", ValueSource::Secondary)
+        );
+        assert!(parameter_cache
+            .cache
+            .peek(&CacheKey::new("shared", false))
+            .is_some());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let value = parameter_cache.get_parameter("fetched").send().await;
+        assert_eq!(value.unwrap(), "value");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(secondary.0.lock().unwrap()["fetched"], "value");
+    }
+}
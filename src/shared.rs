@@ -0,0 +1,1159 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lru::LruCache;
+use tokio::sync::{broadcast, watch, Notify, RwLock, RwLockWriteGuard};
+use tokio::task::JoinHandle;
+
+use super::cache::{lookup, Consistency, ParameterCache};
+use super::cache_item::CacheItem;
+use super::config::InflightOverflow;
+use super::error::CacheError;
+use super::events::CacheEvent;
+use super::key::{validate_name, CacheKey};
+use super::store::CacheStore;
+use super::trace::{self, Instrument};
+
+/// A source of parameter values that can be used behind dynamic dispatch.
+///
+/// Storing a `Box<dyn ParameterProvider>` lets an application swap the real cache for a
+/// fake in tests without being generic over the cache type.
+#[async_trait]
+pub trait ParameterProvider: Send + Sync {
+    /// Gets the value of a parameter.
+    async fn get_parameter(&self, parameter_name: &str) -> Result<String, CacheError>;
+}
+
+/// A ParameterCache that can be cloned and shared between tasks.
+///
+/// Clones share the same cache, so each observes the others' inserts, invalidations and stats.
+/// Requests are serialized by an async lock; use lock() to access the full ParameterCache API.
+/// With update_recency_on_hit unset in the CacheConfig, since hits then leave the cache as it
+/// is, ParameterProvider::get_parameter serves hits under a shared read lock whenever nothing
+/// holds the cache exclusively, so they proceed together rather than one at a time.
+///
+/// Concurrent ParameterProvider::get_parameter calls for the same parameter are coalesced
+/// into a single request (single-flight): while one call is fetching, other callers wait for
+/// and share its value. With a cache_item_ttl of zero every call still fetches from AWS,
+/// except those made while a fetch of the parameter is already in flight. If the shared
+/// fetch fails, each waiting caller makes its own request. Forced refreshes made with
+/// force_refresh, and those made with refresh_resilient, are each coalesced in the same way,
+/// separately from reads. The max_inflight_groups of the CacheConfig bounds how many distinct
+/// parameters are coalesced at once.
+///
+/// By default a fetch holds the cache until it completes, so other requests wait for it. With
+/// per_key_refresh_locks only the fetched parameter is locked during a fetch.
+pub struct SharedParameterCache<S = LruCache<CacheKey, CacheItem<String>>> {
+    inner: Arc<RwLock<ParameterCache<S>>>,
+    per_key_locks: Option<PerKeyLocks>,
+    in_flight: InFlight,
+    refreshing: InFlight,
+    refreshing_resilient: InFlight,
+    refresh_ahead_in_background: bool,
+    work: Arc<Work>,
+}
+
+// How callers are served while a per-key locked fetch of their parameter is in flight
+#[derive(Clone, Copy)]
+struct PerKeyLocks {
+    serve_stale: bool,
+}
+
+type InFlight = Arc<Flights>;
+
+// The in-flight calls, by parameter name, publishing their value on success
+#[derive(Default)]
+struct Flights {
+    groups: std::sync::Mutex<HashMap<String, watch::Receiver<Option<String>>>>,
+    limit: Option<(usize, InflightOverflow)>,
+    slot_freed: Notify,
+}
+
+// The work running apart from the requests holding or waiting for the cache: spawned refreshes
+// and per-key locked fetches, which flush waits for
+#[derive(Default)]
+struct Work {
+    running: AtomicUsize,
+    done: Notify,
+}
+
+impl Work {
+    fn start(self: &Arc<Self>) -> WorkGuard {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        WorkGuard { work: self.clone() }
+    }
+
+    async fn wait(&self) {
+        loop {
+            let done = self.done.notified();
+            if self.running.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            done.await;
+        }
+    }
+}
+
+// Counts as running work until dropped, even if its task panics or is cancelled
+struct WorkGuard {
+    work: Arc<Work>,
+}
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        let running = self.work.running.fetch_sub(1, Ordering::SeqCst);
+        if running == 1 {
+            self.work.done.notify_waiters();
+        }
+    }
+}
+
+impl<S> SharedParameterCache<S> {
+    /// Returns a shared cache wrapping a ParameterCache.
+    pub fn new(parameter_cache: ParameterCache<S>) -> Self {
+        let config = &parameter_cache.config;
+        let in_flight = Flights {
+            limit: config
+                .max_inflight_groups
+                .map(|limit| (limit, config.inflight_overflow)),
+            ..Flights::default()
+        };
+        let refresh_ahead_in_background = config.refresh_ahead_in_background;
+        SharedParameterCache {
+            inner: Arc::new(RwLock::new(parameter_cache)),
+            per_key_locks: None,
+            in_flight: Arc::new(in_flight),
+            refreshing: InFlight::default(),
+            refreshing_resilient: InFlight::default(),
+            refresh_ahead_in_background,
+            work: Arc::default(),
+        }
+    }
+
+    /// Makes ParameterProvider::get_parameter lock only the parameter it fetches, rather than
+    /// the whole cache, so that hits and fetches of other parameters proceed during a fetch.
+    ///
+    /// The cache is held only to look up the parameter and to store the fetched value. While a
+    /// parameter is being fetched, other callers for it share the fetch; with serve_stale they
+    /// are instead answered at once with the expired cached value, if there is one. When
+    /// nothing is cached for the parameter they always wait for the fetch. Requests made with
+    /// lock(), force_refresh and refresh_resilient still hold the whole cache.
+    ///
+    /// A fetch made this way is made as warm_within makes it, with the default decryption
+    /// setting: it honors max_retries, the circuit breaker, error_cache_ttl, negative_ttl,
+    /// empty_as_not_found and evict_after_failed_refreshes. With dry_run set, caching disabled
+    /// or a secondary store, it is made holding the cache as usual.
+    pub fn per_key_refresh_locks(mut self, serve_stale: bool) -> Self {
+        self.per_key_locks = Some(PerKeyLocks { serve_stale });
+        self
+    }
+
+    /// Waits for exclusive access to the underlying ParameterCache.
+    pub async fn lock(&self) -> RwLockWriteGuard<'_, ParameterCache<S>> {
+        self.inner.write().await
+    }
+
+    /// Waits for work already started on the cache to complete, such as before a Lambda
+    /// function is frozen or the process exits.
+    ///
+    /// Returns once every operation that was holding or waiting for the cache when flush was
+    /// called has finished: a scheduled refresh in progress, get_parameter calls (including
+    /// those coalesced into a single fetch or made with per_key_refresh_locks), refreshes
+    /// spawned by refresh_ahead and get_fresh_or_background, and operations started with
+    /// lock(), such as put_parameters. Writes aren't buffered, so there is nothing else to wait
+    /// for; with nothing in progress this returns immediately. Scheduled refreshes continue
+    /// afterwards.
+    pub async fn flush(&self) {
+        self.work.wait().await;
+        // the lock is fair, so this waits for everything queued before it
+        drop(self.inner.write().await);
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> SharedParameterCache<S> {
+    /// Borrows a cached parameter value without cloning it, holding the cache until the guard
+    /// is dropped.
+    ///
+    /// Returns None if the parameter isn't cached with the default decryption setting or has
+    /// expired; this never calls AWS. The guard holds the cache's lock, so every other request
+    /// to the cache waits until it is dropped. Keep it short-lived, and don't call other
+    /// methods of this cache (or await anything that does) while holding it, or the task
+    /// deadlocks.
+    pub async fn get_ref(&self, parameter_name: &str) -> Option<CacheValueGuard<'_, S>> {
+        let mut guard = self.lock().await;
+        let parameter_name = guard.normalized_name(parameter_name).into_owned();
+        let key = CacheKey::new(parameter_name, guard.config.default_with_decryption);
+        let update_recency_on_hit = guard.config.update_recency_on_hit;
+        let hit = matches!(lookup(&mut guard.cache, update_recency_on_hit, &key),
+            Some(cache_item) if !cache_item.is_expired());
+        guard.record_lookup(&key.name, hit);
+        hit.then_some(CacheValueGuard { guard, key })
+    }
+
+    /// Returns a builder to refresh a parameter from AWS SSM, bypassing any cached value.
+    ///
+    /// Concurrent forced refreshes of the same parameter are coalesced into a single fetch
+    /// whose value every caller shares, e.g. when many tasks react to the same rotation. Use
+    /// no_coalesce on the builder for a fetch of its own.
+    pub fn force_refresh<'a>(&'a self, parameter_name: &'a str) -> SharedRefreshBuilder<'a, S> {
+        SharedRefreshBuilder {
+            shared: self,
+            parameter_name,
+            coalesce: true,
+        }
+    }
+
+    /// Waits for the cached value of a parameter to change and returns the new value.
+    ///
+    /// Resolves the next time a fetch of the parameter stores a value different from the one
+    /// cached (with the default decryption setting) when this was called; fetches that return
+    /// the same value are ignored. Reads only fetch once the value has expired, so this needs
+    /// something refreshing the parameter, such as schedule_refresh, or it may never resolve.
+    pub async fn changed(&self, parameter_name: &str) -> String {
+        let (mut events, key, mut previous) = {
+            let guard = self.lock().await;
+            let parameter_name = guard.normalized_name(parameter_name).into_owned();
+            let key = CacheKey::new(parameter_name, guard.config.default_with_decryption);
+            let previous = guard.cache.peek(&key).map(|item| item.value.clone());
+            (guard.events(), key, previous)
+        };
+        loop {
+            let fetched = match events.recv().await {
+                Ok(CacheEvent::Fetched { name, .. }) => name == key.name,
+                Ok(_) => false,
+                // events were missed, so check the value in case it changed meanwhile
+                Err(broadcast::error::RecvError::Lagged(_)) => true,
+                Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+            };
+            if !fetched {
+                continue;
+            }
+            let current = self
+                .lock()
+                .await
+                .cache
+                .peek(&key)
+                .map(|item| item.value.clone());
+            match current {
+                Some(value) if previous.as_ref() != Some(&value) => return value,
+                current => previous = current,
+            }
+        }
+    }
+
+    /// Refreshes a parameter from AWS SSM, falling back to its stale cached value if the
+    /// refresh fails, e.g. to pick up a rotated secret without failing while SSM is unavailable.
+    ///
+    /// Like force_refresh this always fetches rather than serve a cached value, and concurrent
+    /// calls for the same parameter share a single fetch. Unlike force_refresh, a failed fetch
+    /// returns the value cached for the parameter, even if expired, as force_refresh_or_stale
+    /// does; the error is only returned if nothing is cached. Callers sharing a fetch may
+    /// therefore all receive the stale value.
+    pub async fn refresh_resilient(&self, parameter_name: &str) -> Result<String, CacheError> {
+        let fetch = || async {
+            self.lock()
+                .await
+                .get_parameter(parameter_name)
+                .force_refresh_or_stale()
+                .send()
+                .await
+        };
+        single_flight(&self.refreshing_resilient, parameter_name, fetch).await
+    }
+}
+
+/// A forced refresh of a parameter, started with SharedParameterCache::force_refresh.
+pub struct SharedRefreshBuilder<'a, S = LruCache<CacheKey, CacheItem<String>>> {
+    shared: &'a SharedParameterCache<S>,
+    parameter_name: &'a str,
+    coalesce: bool,
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> SharedRefreshBuilder<'_, S> {
+    /// Makes this refresh fetch from AWS itself rather than share a concurrent refresh of the
+    /// same parameter.
+    pub fn no_coalesce(mut self) -> Self {
+        self.coalesce = false;
+        self
+    }
+
+    /// Fetches the parameter value from AWS SSM and stores it in the cache.
+    pub async fn send(self) -> Result<String, CacheError> {
+        let fetch = || async {
+            self.shared
+                .lock()
+                .await
+                .get_parameter(self.parameter_name)
+                .consistency(Consistency::Fresh)
+                .send()
+                .await
+        };
+        match self.coalesce {
+            true => single_flight(&self.shared.refreshing, self.parameter_name, fetch).await,
+            false => fetch().await,
+        }
+    }
+}
+
+/// A cached parameter value borrowed with SharedParameterCache::get_ref.
+///
+/// Dereferences to the value. The cache is locked until the guard is dropped.
+pub struct CacheValueGuard<'a, S = LruCache<CacheKey, CacheItem<String>>> {
+    guard: RwLockWriteGuard<'a, ParameterCache<S>>,
+    key: CacheKey,
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> Deref for CacheValueGuard<'_, S> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        // the entry can't be removed while the guard holds the lock
+        let cache_item = self
+            .guard
+            .cache
+            .peek(&self.key)
+            .expect("guarded entry is cached");
+        &cache_item.value
+    }
+}
+
+impl<S> SharedParameterCache<S>
+where
+    S: CacheStore<CacheKey, CacheItem<String>> + Send + Sync + 'static,
+{
+    /// Refreshes a parameter from AWS SSM every interval, whether or not it is being read.
+    ///
+    /// Spawns a task on the current Tokio runtime that force refreshes the parameter after each
+    /// interval, starting one interval from now. Failed refreshes are logged and retried at the
+    /// next interval. The schedule runs until cancelled with the returned ScheduledRefresh.
+    pub fn schedule_refresh(
+        &self,
+        parameter_name: impl Into<String>,
+        interval: Duration,
+    ) -> ScheduledRefresh {
+        let shared = self.clone();
+        let parameter_name = parameter_name.into();
+        let handle = tokio::spawn(
+            async move {
+                let start = tokio::time::Instant::now() + interval;
+                let mut ticks = tokio::time::interval_at(start, interval);
+                loop {
+                    ticks.tick().await;
+                    let result = shared
+                        .lock()
+                        .await
+                        .get_parameter(&parameter_name)
+                        .consistency(Consistency::Fresh)
+                        .send()
+                        .await;
+                    if let Err(e) = result {
+                        trace::warn!(%parameter_name, error = %e, "scheduled refresh failed");
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+        ScheduledRefresh { handle }
+    }
+
+    /// Gets a parameter value, serving a cached value fetched more than max_age ago, or expired,
+    /// while a background task refreshes it (stale-while-revalidate).
+    ///
+    /// A value fetched within max_age and unexpired is returned as is. An older value is
+    /// returned at once too, and a refresh is spawned on the current Tokio runtime, coalesced
+    /// with other forced refreshes of the parameter; a failed refresh is logged and the value
+    /// stays cached. Only when nothing is cached does the call wait for a fetch, as
+    /// get_parameter does, and return its error. Apart from that cold miss it never waits on
+    /// AWS, though like every request it waits for the cache while something else holds it,
+    /// e.g. a fetch made without per_key_refresh_locks.
+    pub async fn get_fresh_or_background(
+        &self,
+        parameter_name: &str,
+        max_age: Duration,
+    ) -> Result<String, CacheError> {
+        let cached = {
+            let mut guard = self.lock().await;
+            let parameter_name = guard.normalized_name(parameter_name).into_owned();
+            let key = CacheKey::new(parameter_name, guard.config.default_with_decryption);
+            let update_recency_on_hit = guard.config.update_recency_on_hit;
+            let cached = lookup(&mut guard.cache, update_recency_on_hit, &key).map(|cache_item| {
+                let expired = cache_item.is_expired();
+                let fresh = !expired && cache_item.age() <= max_age;
+                (cache_item.value.clone(), fresh, expired)
+            });
+            // a cold miss is counted by the get_parameter call that fetches it
+            if let Some((_, fresh, expired)) = cached {
+                guard.record_lookup(&key.name, fresh);
+                if expired {
+                    guard.record_expired(&key.name);
+                }
+                if !fresh {
+                    guard.record_stale_served(&key.name);
+                }
+            }
+            cached.map(|(value, fresh, _)| (value, fresh))
+        };
+        match cached {
+            Some((value, true)) => Ok(value),
+            Some((value, false)) => {
+                let shared = self.clone();
+                let parameter_name = parameter_name.to_string();
+                let work = self.work.start();
+                tokio::spawn(
+                    async move {
+                        let _work = work;
+                        // boxed, as the refresh future is too deeply nested to lay out inline
+                        let refresh = Box::pin(shared.force_refresh(&parameter_name).send());
+                        if let Err(e) = refresh.await {
+                            trace::warn!(%parameter_name, error = %e, "background refresh failed");
+                        }
+                    }
+                    .in_current_span(),
+                );
+                Ok(value)
+            }
+            None => self.get_parameter(parameter_name).await,
+        }
+    }
+}
+
+impl<S> SharedParameterCache<S>
+where
+    S: CacheStore<CacheKey, CacheItem<String>> + Send + Sync + 'static,
+{
+    // Spawns a refresh of a parameter in its refresh_ahead window, leading the coalesced
+    // forced refresh so that no other refresh of it starts while it is in flight
+    async fn refresh_ahead(&self, parameter_name: &str) {
+        if !self.lock().await.is_stale(parameter_name) {
+            return;
+        }
+        let Flight::Leader(leader) = join_flight(&self.refreshing, parameter_name) else {
+            return;
+        };
+        let shared = self.clone();
+        let parameter_name = parameter_name.to_string();
+        let work = self.work.start();
+        tokio::spawn(
+            async move {
+                let _work = work;
+                // boxed, as the refresh future is too deeply nested to lay out inline
+                let refresh = Box::pin(async {
+                    let mut guard = shared.lock().await;
+                    let refresh = guard.get_parameter(&parameter_name);
+                    refresh.consistency(Consistency::Fresh).send().await
+                });
+                match refresh.await {
+                    Ok(value) => {
+                        leader.sender.send_replace(Some(value));
+                    }
+                    Err(e) => {
+                        trace::warn!(%parameter_name, error = %e, "refresh ahead failed")
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+/// A periodic refresh started by SharedParameterCache::schedule_refresh.
+///
+/// Dropping this doesn't stop the schedule; call cancel() to stop it.
+pub struct ScheduledRefresh {
+    handle: JoinHandle<()>,
+}
+
+impl ScheduledRefresh {
+    /// Stops the periodic refresh.
+    pub fn cancel(self) {
+        self.handle.abort();
+    }
+}
+
+impl<S> Clone for SharedParameterCache<S> {
+    fn clone(&self) -> Self {
+        SharedParameterCache {
+            inner: self.inner.clone(),
+            per_key_locks: self.per_key_locks,
+            in_flight: self.in_flight.clone(),
+            refreshing: self.refreshing.clone(),
+            refreshing_resilient: self.refreshing_resilient.clone(),
+            refresh_ahead_in_background: self.refresh_ahead_in_background,
+            work: self.work.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> ParameterProvider for SharedParameterCache<S>
+where
+    S: CacheStore<CacheKey, CacheItem<String>> + Send + Sync + 'static,
+{
+    async fn get_parameter(&self, parameter_name: &str) -> Result<String, CacheError> {
+        // a caller that would wait for the cache instead joins a fetch in flight, as below
+        let hit = match self.inner.try_read() {
+            Ok(parameter_cache) => parameter_cache.shared_hit(parameter_name),
+            Err(_) => None,
+        };
+        let value = match (hit, self.per_key_locks) {
+            (Some(value), _) => Ok(value),
+            (None, Some(per_key_locks)) => {
+                self.get_per_key_locked(parameter_name, per_key_locks).await
+            }
+            (None, None) => {
+                let fetch =
+                    || async { self.lock().await.get_parameter(parameter_name).send().await };
+                single_flight(&self.in_flight, parameter_name, fetch).await
+            }
+        }?;
+        if self.refresh_ahead_in_background {
+            self.refresh_ahead(parameter_name).await;
+        }
+        Ok(value)
+    }
+}
+
+impl<S> SharedParameterCache<S>
+where
+    S: CacheStore<CacheKey, CacheItem<String>> + Send + Sync,
+{
+    // Gets a parameter holding the cache only to look it up and store it
+    async fn get_per_key_locked(
+        &self,
+        parameter_name: &str,
+        per_key_locks: PerKeyLocks,
+    ) -> Result<String, CacheError> {
+        loop {
+            let mut guard = self.lock().await;
+            if guard.config.dry_run || !guard.config.caching_enabled || guard.secondary.is_some() {
+                return guard.get_parameter(parameter_name).send().await;
+            }
+            let parameter_name = guard.normalized_name(parameter_name).into_owned();
+            validate_name(&parameter_name)?;
+            let key = CacheKey::new(parameter_name, guard.config.default_with_decryption);
+            let update_recency_on_hit = guard.config.update_recency_on_hit;
+            let cached = lookup(&mut guard.cache, update_recency_on_hit, &key)
+                .map(|cache_item| (cache_item.value.clone(), cache_item.is_expired()));
+            guard.record_lookup(&key.name, matches!(cached, Some((_, false))));
+            if matches!(cached, Some((_, true))) {
+                guard.record_expired(&key.name);
+            }
+            let stale = match cached {
+                Some((value, false)) => return Ok(value),
+                Some((value, true)) => Some(value),
+                None => None,
+            };
+
+            let slot_freed = self.in_flight.slot_freed.notified();
+            match join_flight(&self.in_flight, &key.name) {
+                Flight::Leader(leader) => {
+                    let _work = self.work.start();
+                    let fetch = guard.fetch_unlocked(key.name);
+                    drop(guard);
+                    let fetched = fetch.await;
+                    let (_, result) = self.lock().await.store_fetched(fetched);
+                    if let Ok(value) = &result {
+                        leader.sender.send_replace(Some(value.clone()));
+                    }
+                    return result;
+                }
+                Flight::Follower(mut receiver) => {
+                    if let Some(stale) = stale.filter(|_| per_key_locks.serve_stale) {
+                        guard.record_stale_served(&key.name);
+                        return Ok(stale);
+                    }
+                    drop(guard);
+                    return match wait_for_leader(&mut receiver).await {
+                        Some(value) => Ok(value),
+                        None => self.lock().await.get_parameter(&key.name).send().await,
+                    };
+                }
+                Flight::Overflow(InflightOverflow::Uncoalesced) => {
+                    let _work = self.work.start();
+                    let fetch = guard.fetch_unlocked(key.name);
+                    drop(guard);
+                    let fetched = fetch.await;
+                    return self.lock().await.store_fetched(fetched).1;
+                }
+                // waits without holding the cache, then looks the parameter up again
+                Flight::Overflow(InflightOverflow::Wait) => {
+                    drop(guard);
+                    slot_freed.await;
+                }
+            }
+        }
+    }
+}
+
+// Fetches a parameter, or shares the value of the fetch of it already in flight
+async fn single_flight<F, Fut>(
+    in_flight: &InFlight,
+    parameter_name: &str,
+    fetch: F,
+) -> Result<String, CacheError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<String, CacheError>>,
+{
+    loop {
+        let slot_freed = in_flight.slot_freed.notified();
+        match join_flight(in_flight, parameter_name) {
+            Flight::Leader(leader) => {
+                let result = fetch().await;
+                if let Ok(value) = &result {
+                    leader.sender.send_replace(Some(value.clone()));
+                }
+                return result;
+            }
+            Flight::Follower(mut receiver) => {
+                return match wait_for_leader(&mut receiver).await {
+                    Some(value) => Ok(value),
+                    None => fetch().await,
+                };
+            }
+            Flight::Overflow(InflightOverflow::Uncoalesced) => return fetch().await,
+            Flight::Overflow(InflightOverflow::Wait) => slot_freed.await,
+        }
+    }
+}
+
+enum Flight {
+    Leader(FlightLeader),
+    Follower(watch::Receiver<Option<String>>),
+    // max_inflight_groups parameters are already being fetched
+    Overflow(InflightOverflow),
+}
+
+// Removes its in-flight entry when the leading call completes or is cancelled
+struct FlightLeader {
+    in_flight: InFlight,
+    parameter_name: String,
+    sender: watch::Sender<Option<String>>,
+}
+
+impl Drop for FlightLeader {
+    fn drop(&mut self) {
+        if let Ok(mut groups) = self.in_flight.groups.lock() {
+            groups.remove(&self.parameter_name);
+        }
+        self.in_flight.slot_freed.notify_waiters();
+    }
+}
+
+// Leads the fetch of a parameter, follows the call already fetching it, or overflows when
+// max_inflight_groups parameters are already being fetched
+fn join_flight(in_flight: &InFlight, parameter_name: &str) -> Flight {
+    let mut flights = in_flight.groups.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(receiver) = flights.get(parameter_name) {
+        return Flight::Follower(receiver.clone());
+    }
+    if let Some((limit, overflow)) = in_flight.limit {
+        if flights.len() >= limit {
+            return Flight::Overflow(overflow);
+        }
+    }
+    let (sender, receiver) = watch::channel(None);
+    flights.insert(parameter_name.to_string(), receiver);
+    Flight::Leader(FlightLeader {
+        in_flight: in_flight.clone(),
+        parameter_name: parameter_name.to_string(),
+        sender,
+    })
+}
+
+// The leader's value, or None if its fetch failed or was cancelled
+async fn wait_for_leader(receiver: &mut watch::Receiver<Option<String>>) -> Option<String> {
+    loop {
+        if let Some(value) = receiver.borrow_and_update().clone() {
+            return Some(value);
+        }
+        if receiver.changed().await.is_err() {
+            return receiver.borrow().clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{
+        error_response, mock_ssm_client, never_responding_ssm_client, parameter_response,
+        ManualClock,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FakeProvider;
+
+    #[async_trait]
+    impl ParameterProvider for FakeProvider {
+        async fn get_parameter(&self, parameter_name: &str) -> Result<String, CacheError> {
+            Ok(format!("fake {}", parameter_name))
+        }
+    }
+
+    #[tokio::test]
+    async fn get_ref_holds_the_lock() {
+        let mut parameter_cache =
+            ParameterCache::new(mock_ssm_client(|_, _| panic!("get_ref never calls AWS")));
+        parameter_cache.insert(
+            CacheKey::new("service/parameter", false),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+        let shared = SharedParameterCache::new(parameter_cache);
+
+        let value = shared.get_ref("service/parameter").await.unwrap();
+        assert_eq!(&*value, "value");
+        assert!(shared.inner.try_write().is_err());
+        drop(value);
+
+        assert!(shared.get_ref("missing").await.is_none());
+        assert!(shared.inner.try_write().is_ok());
+    }
+
+    #[tokio::test]
+    async fn parameter_provider_dynamic_dispatch() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+        let providers: Vec<Box<dyn ParameterProvider>> =
+            vec![Box::new(shared.clone()), Box::new(FakeProvider)];
+
+        let mut values = Vec::new();
+        for provider in &providers {
+            values.push(provider.get_parameter("service/parameter").await.unwrap());
+        }
+
+        assert_eq!(values, vec!["value", "fake service/parameter"]);
+        assert_eq!(shared.lock().await.cache.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_zero_ttl_reads_single_flight() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let config = CacheConfig::new().cache_item_ttl(0);
+        let shared =
+            SharedParameterCache::new(ParameterCache::new_with_config(mock_ssm_client, config));
+
+        // hold the cache so the reads are all waiting at once
+        let guard = shared.lock().await;
+        let reads: Vec<_> = (0..3)
+            .map(|_| {
+                let shared = shared.clone();
+                tokio::spawn(async move { shared.get_parameter("service/parameter").await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(guard);
+        for read in reads {
+            assert_eq!(read.await.unwrap().unwrap(), "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // the value was stored expired, so the next read fetches again
+        shared.get_parameter("service/parameter").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(shared.lock().await.cache.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_inflight_groups_uncoalesced_overflow() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let config = CacheConfig::new().cache_item_ttl(0).max_inflight_groups(1);
+        let shared =
+            SharedParameterCache::new(ParameterCache::new_with_config(mock_ssm_client, config));
+
+        // "a" takes the only slot while the cache is held, so both reads of "b" fetch alone
+        let guard = shared.lock().await;
+        let mut reads = Vec::new();
+        for name in ["a", "b", "b"] {
+            let shared = shared.clone();
+            reads.push(tokio::spawn(
+                async move { shared.get_parameter(name).await },
+            ));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(shared.in_flight.groups.lock().unwrap().len(), 1);
+        drop(guard);
+        for read in reads {
+            assert_eq!(read.await.unwrap().unwrap(), "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_inflight_groups_wait_overflow() {
+        let in_flight = Arc::new(Flights {
+            limit: Some((1, InflightOverflow::Wait)),
+            ..Flights::default()
+        });
+        let Flight::Leader(leader) = join_flight(&in_flight, "a") else {
+            panic!("the first flight leads");
+        };
+        assert!(matches!(join_flight(&in_flight, "a"), Flight::Follower(_)));
+        assert!(matches!(
+            join_flight(&in_flight, "b"),
+            Flight::Overflow(InflightOverflow::Wait)
+        ));
+
+        let waiting = in_flight.clone();
+        let read = tokio::spawn(async move {
+            single_flight(&waiting, "b", || async { Ok("value".to_string()) }).await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!read.is_finished());
+        drop(leader);
+        assert_eq!(read.await.unwrap().unwrap(), "value");
+    }
+
+    #[tokio::test]
+    async fn get_fresh_or_background_serves_stale() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            (200, parameter_response("p", &format!("value-{}", call)))
+        });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+        let max_age = Duration::from_secs(60);
+
+        // a cold miss waits for the fetch, then the fresh value is served from the cache
+        assert_eq!(
+            shared.get_fresh_or_background("p", max_age).await.unwrap(),
+            "value-1"
+        );
+        assert_eq!(
+            shared.get_fresh_or_background("p", max_age).await.unwrap(),
+            "value-1"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let key = CacheKey::new("p", false);
+        shared
+            .lock()
+            .await
+            .insert(key.clone(), CacheItem::new("old".to_string(), 0));
+        assert_eq!(
+            shared.get_fresh_or_background("p", max_age).await.unwrap(),
+            "old"
+        );
+        let stats = shared.lock().await.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+        assert_eq!((stats.expirations, stats.stale_served), (1, 1));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while shared.lock().await.cache.peek(&key).unwrap().value != "value-2" {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn refresh_ahead_in_background_serves_stale() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            (200, parameter_response("p", &format!("value-{}", call)))
+        });
+        let config = CacheConfig::new()
+            .refresh_ahead(Duration::from_secs(60))
+            .refresh_ahead_in_background(true);
+        let shared =
+            SharedParameterCache::new(ParameterCache::new_with_config(mock_ssm_client, config));
+
+        let key = CacheKey::new("p", false);
+        let ttl = Duration::from_secs(30).as_nanos();
+        shared
+            .lock()
+            .await
+            .insert(key.clone(), CacheItem::new("old".to_string(), ttl));
+        assert_eq!(shared.get_parameter("p").await.unwrap(), "old");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while shared.lock().await.cache.peek(&key).unwrap().value != "value-1" {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_force_refreshes_single_flight() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+
+        for coalesce in [true, false] {
+            calls.store(0, Ordering::SeqCst);
+            // hold the cache so the refreshes are all waiting at once
+            let guard = shared.lock().await;
+            let refreshes: Vec<_> = (0..3)
+                .map(|_| {
+                    let shared = shared.clone();
+                    tokio::spawn(async move {
+                        let refresh = shared.force_refresh("service/parameter");
+                        match coalesce {
+                            true => refresh.send().await,
+                            false => refresh.no_coalesce().send().await,
+                        }
+                    })
+                })
+                .collect();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(guard);
+            for refresh in refreshes {
+                assert_eq!(refresh.await.unwrap().unwrap(), "value");
+            }
+            let expected = if coalesce { 1 } else { 3 };
+            assert_eq!(calls.load(Ordering::SeqCst), expected);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refresh_resilient_coalesces_and_falls_back_to_stale() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, parameter_response("service/parameter", "value")),
+                _ => error_response("InternalServerError"),
+            });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+
+        // hold the cache so the refreshes are all waiting at once
+        let guard = shared.lock().await;
+        let refreshes: Vec<_> = (0..3)
+            .map(|_| {
+                let shared = shared.clone();
+                tokio::spawn(async move { shared.refresh_resilient("service/parameter").await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(guard);
+        for refresh in refreshes {
+            assert_eq!(refresh.await.unwrap().unwrap(), "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // the refresh fails, so the cached value is served
+        assert_eq!(
+            shared.refresh_resilient("service/parameter").await.unwrap(),
+            "value"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(shared
+            .force_refresh("service/parameter")
+            .send()
+            .await
+            .is_err());
+        assert!(shared.refresh_resilient("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn per_key_refresh_locks_serve_hits_during_a_fetch() {
+        let mut parameter_cache = ParameterCache::new(never_responding_ssm_client());
+        for (name, ttl) in [("stale", 0), ("fresh", 3600000000000)] {
+            parameter_cache.insert(
+                CacheKey::new(name, false),
+                CacheItem::new(format!("{} value", name), ttl),
+            );
+        }
+        let shared = SharedParameterCache::new(parameter_cache).per_key_refresh_locks(true);
+
+        let refresher = shared.clone();
+        let refresh = tokio::spawn(async move { refresher.get_parameter("stale").await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let within = |read| tokio::time::timeout(Duration::from_secs(1), read);
+        let fresh = within(shared.get_parameter("fresh")).await.unwrap();
+        assert_eq!(fresh.unwrap(), "fresh value");
+        let stale = within(shared.get_parameter("stale")).await.unwrap();
+        assert_eq!(stale.unwrap(), "stale value");
+        assert_eq!(shared.lock().await.stats().stale_served, 1);
+        assert!(!refresh.is_finished());
+        refresh.abort();
+    }
+
+    #[tokio::test]
+    async fn per_key_refresh_locks_store_fetched_values() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client))
+            .per_key_refresh_locks(false);
+
+        for _ in 0..2 {
+            let value = shared.get_parameter("service/parameter").await;
+            assert_eq!(value.unwrap(), "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(shared.lock().await.stats().hits, 1);
+    }
+
+    #[tokio::test]
+    async fn hits_share_a_read_lock_without_recency_updates() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let config = CacheConfig::new().update_recency_on_hit(false);
+        let shared =
+            SharedParameterCache::new(ParameterCache::new_with_config(mock_ssm_client, config));
+        shared.get_parameter("service/parameter").await.unwrap();
+
+        let reader = shared.inner.read().await;
+        let hit = tokio::time::timeout(
+            Duration::from_secs(5),
+            shared.get_parameter("service/parameter"),
+        );
+        assert_eq!(hit.await.unwrap().unwrap(), "value");
+        drop(reader);
+        assert_eq!(shared.lock().await.stats().hits, 1);
+    }
+
+    #[tokio::test]
+    async fn per_key_refresh_locks_evict_after_failed_refreshes() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            error_response("AccessDeniedException")
+        });
+        let clock = ManualClock::new();
+        let config = CacheConfig::new()
+            .evict_after_failed_refreshes(1)
+            .error_cache_ttl(Duration::from_secs(60));
+        let parameter_cache = ParameterCache::builder(mock_ssm_client)
+            .config(config)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let key = CacheKey::new("service/parameter", false);
+        let shared = SharedParameterCache::new(parameter_cache).per_key_refresh_locks(false);
+        let stale = CacheItem::new("stale".to_string(), 0);
+        shared.lock().await.insert(key.clone(), stale);
+        clock.advance(Duration::from_millis(10));
+
+        for _ in 0..2 {
+            assert!(shared.get_parameter("service/parameter").await.is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(shared.lock().await.cache.peek(&key).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn changed_resolves_on_a_new_value() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            let value = match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => "old",
+                _ => "new",
+            };
+            (200, parameter_response("service/parameter", value))
+        });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+        shared.get_parameter("service/parameter").await.unwrap();
+
+        let waiter = shared.clone();
+        let changed = tokio::spawn(async move { waiter.changed("service/parameter").await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        shared
+            .force_refresh("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!changed.is_finished());
+
+        shared
+            .force_refresh("service/parameter")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(changed.await.unwrap(), "new");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_waits_for_work_in_progress() {
+        let mock_ssm_client =
+            mock_ssm_client(|_, _| (200, parameter_response("service/parameter", "value")));
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+        shared.flush().await;
+
+        let writer = shared.clone();
+        tokio::spawn(async move {
+            let mut parameter_cache = writer.lock().await;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            parameter_cache
+                .get_parameter("service/parameter")
+                .send()
+                .await
+                .unwrap();
+        });
+        tokio::task::yield_now().await;
+
+        shared.flush().await;
+        assert_eq!(shared.lock().await.cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn flush_waits_for_refresh_ahead() {
+        let mock_ssm_client = mock_ssm_client(|_, _| (200, parameter_response("p", "new")));
+        let config = CacheConfig::new()
+            .refresh_ahead(Duration::from_secs(60))
+            .refresh_ahead_in_background(true);
+        let shared =
+            SharedParameterCache::new(ParameterCache::new_with_config(mock_ssm_client, config));
+        let key = CacheKey::new("p", false);
+        let ttl = Duration::from_secs(30).as_nanos();
+        let cache_item = CacheItem::new("old".to_string(), ttl);
+        shared.lock().await.insert(key.clone(), cache_item);
+
+        assert_eq!(shared.get_parameter("p").await.unwrap(), "old");
+        shared.flush().await;
+        assert_eq!(shared.lock().await.cache.peek(&key).unwrap().value, "new");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn schedule_refresh_until_cancelled() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+
+        let schedule = shared.schedule_refresh("service/parameter", Duration::from_secs(60));
+        tokio::time::sleep(Duration::from_secs(150)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        schedule.cancel();
+        tokio::time::sleep(Duration::from_secs(150)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,104 @@
+use std::io;
+
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::JoinHandle;
+
+use super::cache_item::CacheItem;
+use super::key::CacheKey;
+use super::shared::SharedParameterCache;
+use super::store::CacheStore;
+use super::trace::{self, Instrument};
+
+impl<S> SharedParameterCache<S>
+where
+    S: CacheStore<CacheKey, CacheItem<String>> + Send + Sync + 'static,
+{
+    /// Refreshes parameters from AWS SSM each time the process receives SIGHUP, the usual
+    /// signal to reload configuration.
+    ///
+    /// Spawns a task on the current Tokio runtime that force refreshes each of the parameters,
+    /// in order, on every SIGHUP. Failed refreshes are logged and the rest still go ahead. The
+    /// task runs until cancelled with the returned SignalReload. Returns an error if the signal
+    /// handler can't be installed.
+    pub fn reload_on_sighup<I>(&self, parameter_names: I) -> io::Result<SignalReload>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut hangups = signal(SignalKind::hangup())?;
+        let shared = self.clone();
+        let parameter_names: Vec<String> = parameter_names.into_iter().map(Into::into).collect();
+        let handle = tokio::spawn(
+            async move {
+                while hangups.recv().await.is_some() {
+                    trace::info!(
+                        count = parameter_names.len(),
+                        "SIGHUP: reloading parameters"
+                    );
+                    for parameter_name in &parameter_names {
+                        // boxed, as the refresh future is too deeply nested to lay out inline
+                        let refresh = Box::pin(shared.force_refresh(parameter_name).send());
+                        if let Err(e) = refresh.await {
+                            trace::warn!(%parameter_name, error = %e, "reload on SIGHUP failed");
+                        }
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+        Ok(SignalReload { handle })
+    }
+}
+
+/// A reload on SIGHUP started by SharedParameterCache::reload_on_sighup.
+///
+/// Dropping this doesn't stop listening; call cancel() to stop it.
+pub struct SignalReload {
+    handle: JoinHandle<()>,
+}
+
+impl SignalReload {
+    /// Stops reloading on SIGHUP.
+    pub fn cancel(self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::cache::ParameterCache;
+    use crate::test_util::{mock_ssm_client, parameter_response};
+
+    #[tokio::test]
+    async fn reload_on_sighup_refreshes() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, parameter_response("service/parameter", "value"))
+        });
+        let shared = SharedParameterCache::new(ParameterCache::new(mock_ssm_client));
+        let reload = shared
+            .reload_on_sighup(["service/parameter", "service/other"])
+            .unwrap();
+
+        let status = std::process::Command::new("kill")
+            .args(["-HUP", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while calls.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        reload.cancel();
+    }
+}
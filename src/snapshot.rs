@@ -0,0 +1,152 @@
+use std::time::SystemTime;
+
+use aws_sdk_ssm::Client as SSMClient;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::config::CacheConfig;
+use super::key::CacheKey;
+use super::store::CacheStore;
+
+/// A point-in-time copy of a ParameterCache's entries and configuration.
+///
+/// Useful for diagnostics and for persisting a cache across restarts. With the serde feature
+/// enabled it can be serialized and deserialized.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheSnapshot {
+    /// The cache's configuration.
+    pub config: CacheConfig,
+
+    /// The cached entries, most recently used first.
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// A cached parameter value in a CacheSnapshot.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotEntry {
+    /// The parameter name, including any version or label selector.
+    pub name: String,
+
+    /// Whether the value was fetched with decryption, the flag that is part of the cache key.
+    ///
+    /// For a SecureString parameter an entry with this unset holds the ciphertext, e.g. when a
+    /// consumer unexpectedly got an encrypted value; for other types both forms are the same.
+    pub with_decryption: bool,
+
+    /// The registered account client the value was fetched with, or None for the default client.
+    pub account: Option<String>,
+
+    /// The tenant the value was requested for, or None if it wasn't requested for a tenant.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tenant: Option<String>,
+
+    /// The cached value, or None if it was redacted.
+    pub value: Option<String>,
+
+    /// The version of the parameter, if known.
+    pub version: Option<i64>,
+
+    /// When the value was fetched.
+    pub inserted_at: SystemTime,
+
+    /// When the value expires.
+    pub expires_at: SystemTime,
+}
+
+impl ParameterCache {
+    /// Returns a new ParameterCache with the configuration and entries of a snapshot.
+    ///
+    /// Entries keep the expiry they were snapshotted with, so those that have expired since
+    /// are refreshed on their next request. Redacted entries have no value and are skipped.
+    pub fn from_snapshot(client: SSMClient, snapshot: CacheSnapshot) -> Self {
+        let mut parameter_cache = ParameterCache::new_with_config(client, snapshot.config);
+        // least recently used first, so the restored cache has the same recency order
+        for entry in snapshot.entries.into_iter().rev() {
+            if let Some(value) = entry.value {
+                let mut cache_item =
+                    CacheItem::restored(value, entry.inserted_at, entry.expires_at);
+                cache_item.version = entry.version;
+                let key = CacheKey::new(entry.name, entry.with_decryption)
+                    .with_account(entry.account)
+                    .with_tenant(entry.tenant);
+                parameter_cache.insert(key, cache_item);
+            }
+        }
+        parameter_cache
+    }
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Returns a snapshot of the cache's configuration and entries, including expired entries.
+    ///
+    /// If redact_decrypted is set, values fetched with decryption, which may be SecureString
+    /// plaintext, are left out of the snapshot; their other fields are kept. Taking a
+    /// snapshot doesn't affect recency.
+    pub fn to_snapshot(&self, redact_decrypted: bool) -> CacheSnapshot {
+        let entries = self
+            .cache
+            .iter()
+            .map(|(key, cache_item)| SnapshotEntry {
+                name: key.name.clone(),
+                with_decryption: key.with_decryption,
+                account: key.account.clone(),
+                tenant: key.tenant.clone(),
+                value: (!(redact_decrypted && key.with_decryption))
+                    .then(|| cache_item.value.clone()),
+                version: cache_item.version,
+                inserted_at: cache_item.inserted_at(),
+                expires_at: cache_item.expires_at(),
+            })
+            .collect();
+        CacheSnapshot {
+            config: self.config.clone(),
+            entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_ssm_client;
+
+    #[test]
+    fn snapshot_round_trip_redacted() {
+        let client = mock_ssm_client(|_, _| panic!("restoring a snapshot never calls AWS"));
+        let config = CacheConfig::new().max_cache_size(10);
+        let mut parameter_cache = ParameterCache::new_with_config(client.clone(), config);
+        for (name, with_decryption) in [("plain", false), ("secret", true)] {
+            parameter_cache.insert(
+                CacheKey::new(name, with_decryption),
+                CacheItem::new(name.to_string(), 3600000000000),
+            );
+        }
+
+        let snapshot = parameter_cache.to_snapshot(true);
+        assert_eq!(snapshot.config.max_cache_size, 10);
+        let values: Vec<(&str, Option<&str>)> = snapshot
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.value.as_deref()))
+            .collect();
+        assert_eq!(values, vec![("secret", None), ("plain", Some("plain"))]);
+
+        let restored = ParameterCache::from_snapshot(client, snapshot).to_snapshot(false);
+        assert_eq!(restored.config.max_cache_size, 10);
+        let original = &parameter_cache.to_snapshot(false).entries[1];
+        assert_eq!(restored.entries.len(), 1);
+        assert_eq!(restored.entries[0].value.as_deref(), Some("plain"));
+        assert_eq!(restored.entries[0].expires_at, original.expires_at);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot_serializes() {
+        let parameter_cache = ParameterCache::new(mock_ssm_client(|_, _| unreachable!()));
+        let json = serde_json::to_string(&parameter_cache.to_snapshot(false)).unwrap();
+        let snapshot: CacheSnapshot = serde_json::from_str(&json).unwrap();
+        assert!(snapshot.entries.is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use super::breaker::BreakerState;
+
+// The upper bounds of the latency histogram buckets, in milliseconds; a last bucket holds the rest
+const BUCKET_BOUNDS_MS: [u64; 12] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Counts of requests to the cache and fetches from AWS SSM.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Requests for a parameter answered from the cache.
+    pub hits: u64,
+
+    /// Requests for a parameter that weren't answered from the cache, because it wasn't
+    /// cached, had expired, a refresh was forced or caching is disabled.
+    pub misses: u64,
+
+    /// Requests made to AWS SSM.
+    pub fetches: u64,
+
+    /// Misses for a parameter whose cached value had expired.
+    pub expirations: u64,
+
+    /// Misses because a refresh was forced with Consistency::Fresh.
+    pub forced_refreshes: u64,
+
+    /// Entries evicted to make room, for the memory budget or after failed refreshes.
+    /// Invalidated entries aren't counted.
+    pub evictions: u64,
+
+    /// Requests answered with a stale cached value: after a failed fetch by
+    /// force_refresh_or_stale, or by a SharedParameterCache serving an expired or over-age value
+    /// while it refreshes. A rising count means the cache is operating degraded.
+    pub stale_served: u64,
+
+    /// Fetches answered by the fallback_name parameter because the requested one wasn't found,
+    /// another sign of degraded configuration.
+    pub fallback_used: u64,
+
+    /// Requests made with the synthetic option, which are not included in the other counts.
+    pub synthetic: u64,
+
+    /// How long requests to AWS SSM took, successful or not, to detect degradation.
+    ///
+    /// Only kept for the totals from ParameterCache::stats; always empty in stats_by_name.
+    pub fetch_latency: LatencyHistogram,
+
+    /// The state of the circuit breaker set up with breaker_threshold.
+    ///
+    /// Only kept for the totals from ParameterCache::stats; always Closed in stats_by_name.
+    pub breaker: BreakerState,
+}
+
+impl CacheStats {
+    pub(crate) fn record_lookup(&mut self, hit: bool) {
+        match hit {
+            true => self.hits += 1,
+            false => self.misses += 1,
+        }
+    }
+
+    pub(crate) fn record_fetch(&mut self) {
+        self.fetches += 1;
+    }
+
+    pub(crate) fn record_expired(&mut self) {
+        self.expirations += 1;
+    }
+
+    pub(crate) fn record_forced_refresh(&mut self) {
+        self.forced_refreshes += 1;
+    }
+
+    pub(crate) fn record_eviction(&mut self) {
+        self.evictions += 1;
+    }
+
+    pub(crate) fn record_stale_served(&mut self) {
+        self.stale_served += 1;
+    }
+
+    pub(crate) fn record_fallback_used(&mut self) {
+        self.fallback_used += 1;
+    }
+
+    pub(crate) fn record_synthetic(&mut self) {
+        self.synthetic += 1;
+    }
+}
+
+/// A histogram of request latencies in fixed buckets, from 1ms to over 5s.
+///
+/// Percentiles are estimated as the upper bound of the bucket they fall in, capped at the
+/// slowest latency recorded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// Returns the number of latencies recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the fastest latency recorded, or None if there are none.
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// Returns the slowest latency recorded, or None if there are none.
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Returns the mean latency, or None if there are none.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count).unwrap_or(u32::MAX);
+        (self.count > 0).then(|| self.total / count)
+    }
+
+    /// Returns an estimate of the latency below which the given fraction of requests completed,
+    /// e.g. 0.95 for the 95th percentile, or None if there are none.
+    pub fn percentile(&self, fraction: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((fraction.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = BUCKET_BOUNDS_MS
+                    .get(bucket)
+                    .map(|&ms| Duration::from_millis(ms));
+                return Some(bound.map_or(self.max, |bound| bound.min(self.max)));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Returns the number of latencies in each bucket, by the bucket's upper bound; None is the
+    /// bound of the last bucket, which holds latencies over 5s.
+    pub fn buckets(&self) -> Vec<(Option<Duration>, u64)> {
+        let bounds = BUCKET_BOUNDS_MS
+            .iter()
+            .map(|&ms| Some(Duration::from_millis(ms)))
+            .chain([None]);
+        bounds.zip(self.buckets).collect()
+    }
+
+    pub(crate) fn record(&mut self, latency: Duration) {
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|&ms| latency <= Duration::from_millis(ms))
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[bucket] += 1;
+        self.min = match self.count {
+            0 => latency,
+            _ => self.min.min(latency),
+        };
+        self.max = self.max.max(latency);
+        self.total += latency;
+        self.count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_histogram_summaries() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(0.5), None);
+        for ms in [3, 4, 8, 40, 7000] {
+            histogram.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.min(), Some(Duration::from_millis(3)));
+        assert_eq!(histogram.max(), Some(Duration::from_secs(7)));
+        assert_eq!(histogram.mean(), Some(Duration::from_millis(1411)));
+        assert_eq!(histogram.percentile(0.5), Some(Duration::from_millis(10)));
+        assert_eq!(histogram.percentile(0.95), Some(Duration::from_secs(7)));
+        assert_eq!(histogram.buckets()[2], (Some(Duration::from_millis(5)), 2));
+    }
+}
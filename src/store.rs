@@ -0,0 +1,156 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+use lru::LruCache;
+
+/// The storage backing a ParameterCache.
+///
+/// The default store is an LruCache bounded by the max_cache_size from the CacheConfig.
+/// Implement this trait to back the cache with a different store, for example a map with
+/// its own eviction policy. Expiry is handled by the ParameterCache, so a store only needs
+/// to hold entries until they are replaced, removed or evicted.
+pub trait CacheStore<K, V> {
+    /// Returns the value stored for a key, marking the entry as recently used.
+    fn get(&mut self, key: &K) -> Option<&V>;
+
+    /// Returns the value stored for a key without affecting which entry is evicted next.
+    fn peek(&self, key: &K) -> Option<&V>;
+
+    /// Stores a value for a key.
+    ///
+    /// Returns the entry displaced by the insert, if any: either the previous value
+    /// stored for the key, or an entry evicted to make room.
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)>;
+
+    /// Removes the entry for a key, returning its value.
+    fn pop(&mut self, key: &K) -> Option<V>;
+
+    /// Removes and returns the entry that should be evicted next, if the store keeps a usage order.
+    ///
+    /// Used to enforce the max_memory_bytes budget. The default returns None, in which case
+    /// the store's entries are never evicted to stay within the budget.
+    fn pop_lru(&mut self) -> Option<(K, V)> {
+        None
+    }
+
+    /// Returns the entry that should be evicted next without removing it or affecting recency.
+    ///
+    /// The default returns None, for stores that don't keep a usage order.
+    fn peek_lru(&self) -> Option<(&K, &V)> {
+        None
+    }
+
+    /// Changes the number of entries the store holds, returning any entries evicted to fit.
+    ///
+    /// Called when the max_cache_size of a running cache changes. The default ignores the new
+    /// capacity, for stores that manage their own.
+    fn resize(&mut self, capacity: NonZeroUsize) -> Vec<(K, V)> {
+        let _ = capacity;
+        Vec::new()
+    }
+
+    /// Returns the most entries the store holds before evicting, if it has a fixed capacity.
+    ///
+    /// The default returns None, for stores that manage their own capacity.
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the number of entries in the store.
+    fn len(&self) -> usize;
+
+    /// Returns true if the store holds no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the entries in the store, without affecting recency.
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>;
+}
+
+impl<K: Hash + Eq, V> CacheStore<K, V> for LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        LruCache::get(self, key)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        LruCache::peek(self, key)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        LruCache::push(self, key, value)
+    }
+
+    fn pop(&mut self, key: &K) -> Option<V> {
+        LruCache::pop(self, key)
+    }
+
+    fn pop_lru(&mut self) -> Option<(K, V)> {
+        LruCache::pop_lru(self)
+    }
+
+    fn peek_lru(&self) -> Option<(&K, &V)> {
+        LruCache::peek_lru(self)
+    }
+
+    fn resize(&mut self, capacity: NonZeroUsize) -> Vec<(K, V)> {
+        let mut evicted = Vec::new();
+        while LruCache::len(self) > capacity.get() {
+            evicted.extend(LruCache::pop_lru(self));
+        }
+        LruCache::resize(self, capacity);
+        evicted
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(LruCache::cap(self).get())
+    }
+
+    fn len(&self) -> usize {
+        LruCache::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a> {
+        Box::new(LruCache::iter(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_store_put_reports_displaced_entries() {
+        let mut store = LruCache::new(NonZeroUsize::new(2).unwrap());
+
+        assert!(CacheStore::put(&mut store, "a", 1).is_none());
+        assert_eq!(CacheStore::put(&mut store, "a", 2), Some(("a", 1)));
+        assert!(CacheStore::put(&mut store, "b", 3).is_none());
+
+        // "a" is the least recently used entry
+        assert_eq!(CacheStore::put(&mut store, "c", 4), Some(("a", 2)));
+        assert_eq!(CacheStore::len(&store), 2);
+    }
+
+    #[test]
+    fn lru_store_peek_does_not_affect_recency() {
+        let mut store = LruCache::new(NonZeroUsize::new(2).unwrap());
+        CacheStore::put(&mut store, "a", 1);
+        CacheStore::put(&mut store, "b", 2);
+
+        assert_eq!(CacheStore::peek(&store, &"a"), Some(&1));
+        assert_eq!(CacheStore::put(&mut store, "c", 3), Some(("a", 1)));
+    }
+
+    #[test]
+    fn lru_store_resize_returns_evicted_entries() {
+        let mut store = LruCache::new(NonZeroUsize::new(3).unwrap());
+        CacheStore::put(&mut store, "a", 1);
+        CacheStore::put(&mut store, "b", 2);
+        CacheStore::put(&mut store, "c", 3);
+
+        let evicted = CacheStore::resize(&mut store, NonZeroUsize::new(1).unwrap());
+        assert_eq!(evicted, vec![("a", 1), ("b", 2)]);
+        assert_eq!(store.cap().get(), 1);
+    }
+}
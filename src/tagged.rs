@@ -0,0 +1,86 @@
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::CacheKey;
+use super::store::CacheStore;
+
+/// A parameter value with a hint of how its content is formatted, as returned by get_tagged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaggedValue {
+    /// The parameter value.
+    pub value: String,
+
+    /// How the value is formatted, e.g. "json" or "pem", if known.
+    pub content_hint: Option<String>,
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Gets a parameter value along with a hint of its content type, e.g. for a generic config
+    /// loader that picks a parser per parameter.
+    ///
+    /// The hint is the lowercased extension of the last segment of the name, so
+    /// "/svc/tls/cert.pem" is hinted "pem". Failing that, it is the SSM data type of the
+    /// parameter, such as "aws:ec2:image", when that is known and not plain "text". The raw
+    /// string value is what gets cached.
+    pub async fn get_tagged(&mut self, parameter_name: &str) -> Result<TaggedValue, CacheError> {
+        let value = self.get_parameter(parameter_name).send().await?;
+        let parameter_name = self.normalized_name(parameter_name).into_owned();
+        let content_hint = name_hint(&parameter_name).or_else(|| {
+            self.peek_unexpired(&parameter_name)
+                .and_then(|cache_item| cache_item.data_type.clone())
+                .filter(|data_type| data_type != "text")
+        });
+        Ok(TaggedValue {
+            value,
+            content_hint,
+        })
+    }
+}
+
+// The extension of the last segment of a parameter name, if it has one
+fn name_hint(parameter_name: &str) -> Option<String> {
+    let segment = parameter_name.rsplit('/').next()?;
+    let (stem, extension) = segment.rsplit_once('.')?;
+    (!stem.is_empty() && !extension.is_empty()).then(|| extension.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_ssm_client, parameter_response};
+
+    #[test]
+    fn name_hints() {
+        assert_eq!(name_hint("/svc/tls/cert.PEM").as_deref(), Some("pem"));
+        assert_eq!(name_hint("/svc/settings.json").as_deref(), Some("json"));
+        assert_eq!(name_hint("/svc.v2/settings"), None);
+        assert_eq!(name_hint("/svc/.hidden"), None);
+        assert_eq!(name_hint("/svc/trailing."), None);
+    }
+
+    #[tokio::test]
+    async fn get_tagged_hints_by_name_or_data_type() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("ami") {
+            true => (
+                200,
+                r#"{"Parameter":{"Name":"/svc/ami","Type":"String","Value":"ami-0123",
+                    "Version":1,"DataType":"aws:ec2:image"}}"#
+                    .to_string(),
+            ),
+            false => (200, parameter_response("/svc/settings.json", "{}")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let tagged = parameter_cache
+            .get_tagged("/svc/settings.json")
+            .await
+            .unwrap();
+        assert_eq!(tagged.value, "{}");
+        assert_eq!(tagged.content_hint.as_deref(), Some("json"));
+        for _ in 0..2 {
+            let tagged = parameter_cache.get_tagged("/svc/ami").await.unwrap();
+            assert_eq!(tagged.value, "ami-0123");
+            assert_eq!(tagged.content_hint.as_deref(), Some("aws:ec2:image"));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Helpers for unit tests that need an SSM client without network access.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_ssm::config::retry::RetryConfig;
+use aws_sdk_ssm::config::{Credentials, HttpClient, Region};
+use aws_sdk_ssm::{Client as SSMClient, Config};
+use aws_smithy_runtime::client::http::test_util::{infallible_client_fn, NeverClient};
+
+/// Returns a client whose requests are answered by `handler` instead of AWS.
+///
+/// The handler receives the operation name (e.g. "GetParameter") and the JSON request body,
+/// and returns the HTTP status code and JSON response body. Retries are disabled.
+pub(crate) fn mock_ssm_client<F>(handler: F) -> SSMClient
+where
+    F: Fn(&str, &str) -> (u16, String) + Send + Sync + 'static,
+{
+    let http_client = infallible_client_fn(move |request: http::Request<_>| {
+        let operation = request
+            .headers()
+            .get("x-amz-target")
+            .and_then(|target| target.to_str().ok())
+            .and_then(|target| target.strip_prefix("AmazonSSM."))
+            .unwrap_or_default()
+            .to_string();
+        let body = request
+            .body()
+            .bytes()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+            .unwrap_or_default();
+        let (status, response) = handler(&operation, &body);
+        http::Response::builder()
+            .status(status)
+            .body(response)
+            .unwrap()
+    });
+
+    client_with_http_client(http_client)
+}
+
+/// Returns a client whose requests never receive a response.
+pub(crate) fn never_responding_ssm_client() -> SSMClient {
+    client_with_http_client(NeverClient::new())
+}
+
+/// Returns a client whose requests are sent to `http_client`.
+pub(crate) fn client_with_http_client(http_client: impl HttpClient + 'static) -> SSMClient {
+    let conf = Config::builder()
+        .region(Region::new("ap-southeast-2"))
+        .credentials_provider(Credentials::new("asdf", "asdf", None, None, "test"))
+        .retry_config(RetryConfig::disabled())
+        .http_client(http_client)
+        .build();
+
+    SSMClient::from_conf(conf)
+}
+
+/// A GetParameter response body for a String parameter.
+pub(crate) fn parameter_response(name: &str, value: &str) -> String {
+    format!(
+        r#"{{"Parameter":{{"Name":"{}","Type":"String","Value":"{}","Version":1}}}}"#,
+        name, value
+    )
+}
+
+/// A GetParameters response body for a request body, with a value of "value-<name>" for each
+/// requested name, except names containing "missing", which are invalid.
+///
+/// Panics if the request has more names than a GetParameters call accepts.
+pub(crate) fn batch_response(body: &str) -> String {
+    let names = body
+        .split_once(r#""Names":["#)
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(names, _)| names)
+        .unwrap();
+    let names: Vec<&str> = names
+        .split(',')
+        .map(|name| name.trim_matches('"'))
+        .collect();
+    assert!(names.len() <= 10);
+    let parameters: Vec<String> = names
+        .iter()
+        .filter(|name| !name.contains("missing"))
+        .map(|name| {
+            format!(
+                r#"{{"Name":"{0}","Type":"String","Value":"value-{0}","Version":1}}"#,
+                name
+            )
+        })
+        .collect();
+    let invalid: Vec<String> = names
+        .iter()
+        .filter(|name| name.contains("missing"))
+        .map(|name| format!(r#""{}""#, name))
+        .collect();
+    format!(
+        r#"{{"Parameters":[{}],"InvalidParameters":[{}]}}"#,
+        parameters.join(","),
+        invalid.join(",")
+    )
+}
+
+/// A clock that only moves when advanced, to test expiry without sleeping.
+#[derive(Clone)]
+pub(crate) struct ManualClock(Arc<Mutex<SystemTime>>);
+
+impl ManualClock {
+    /// Returns a clock stopped at the current system time.
+    pub(crate) fn new() -> Self {
+        ManualClock(Arc::new(Mutex::new(SystemTime::now())))
+    }
+
+    /// Moves the clock forward.
+    pub(crate) fn advance(&self, by: Duration) {
+        *self.0.lock().unwrap() += by;
+    }
+}
+
+impl crate::clock::Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// An error response in the AWS JSON protocol format, as a status code and body.
+pub(crate) fn error_response(error_type: &str) -> (u16, String) {
+    (
+        400,
+        format!(r#"{{"__type":"{}","message":"mock error"}}"#, error_type),
+    )
+}
@@ -0,0 +1,75 @@
+//! Logging and spans through tracing when the tracing feature is enabled.
+//!
+//! Without the feature the macros and Instrument below do nothing, so the cache logs
+//! through the same calls either way. The disabled macros still borrow the fields they are
+//! given, so values only logged don't become unused.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, debug_span, info, warn, Instrument};
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use disabled::{debug, debug_span, info, warn, Instrument};
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) mod disabled {
+    // Borrows each field and format argument of an event that isn't logged
+    macro_rules! fields {
+        () => {};
+        ($message:literal $(, $arg:expr)* $(,)?) => { $(let _ = &$arg;)* };
+        (%$value:expr $(, $($rest:tt)*)?) => {
+            let _ = &$value;
+            $($crate::trace::disabled::fields!($($rest)*);)?
+        };
+        (?$value:expr $(, $($rest:tt)*)?) => {
+            let _ = &$value;
+            $($crate::trace::disabled::fields!($($rest)*);)?
+        };
+        ($field:ident = %$value:expr $(, $($rest:tt)*)?) => {
+            let _ = &$value;
+            $($crate::trace::disabled::fields!($($rest)*);)?
+        };
+        ($field:ident = ?$value:expr $(, $($rest:tt)*)?) => {
+            let _ = &$value;
+            $($crate::trace::disabled::fields!($($rest)*);)?
+        };
+        ($field:ident = $value:expr $(, $($rest:tt)*)?) => {
+            let _ = &$value;
+            $($crate::trace::disabled::fields!($($rest)*);)?
+        };
+        ($field:ident $(, $($rest:tt)*)?) => {
+            let _ = &$field;
+            $($crate::trace::disabled::fields!($($rest)*);)?
+        };
+    }
+
+    macro_rules! event {
+        ($($fields:tt)*) => {{
+            $crate::trace::disabled::fields!($($fields)*);
+        }};
+    }
+
+    macro_rules! debug_span {
+        ($name:literal $(, $($fields:tt)*)?) => {{
+            $($crate::trace::disabled::fields!($($fields)*);)?
+            $crate::trace::disabled::Span
+        }};
+    }
+
+    pub(crate) use {debug_span, event as debug, event as info, event as warn, fields};
+
+    // A span that isn't recorded
+    pub(crate) struct Span;
+
+    // Instruments nothing, leaving futures as they are
+    pub(crate) trait Instrument: Sized {
+        fn instrument(self, _span: Span) -> Self {
+            self
+        }
+
+        fn in_current_span(self) -> Self {
+            self
+        }
+    }
+
+    impl<T> Instrument for T {}
+}
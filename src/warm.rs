@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::future::Future;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+#[cfg(feature = "stream")]
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use aws_sdk_ssm::operation::get_parameter::GetParameterOutput;
+use tokio::task::JoinSet;
+
+use super::cache::ParameterCache;
+use super::cache_item::CacheItem;
+use super::error::CacheError;
+use super::key::{validate_name, CacheKey};
+use super::store::CacheStore;
+use super::trace::{self, Instrument};
+
+/// The outcome of warming the cache with warm_within.
+#[derive(Debug, Default)]
+pub struct WarmResult {
+    /// The result of fetching each parameter that finished before the deadline.
+    pub completed: HashMap<String, Result<(), CacheError>>,
+
+    /// The parameters that were still being fetched when the deadline passed.
+    pub unfinished: Vec<String>,
+}
+
+impl<S: CacheStore<CacheKey, CacheItem<String>>> ParameterCache<S> {
+    /// Fetches the named parameters into the cache with as few GetParameters calls as
+    /// possible, e.g. to warm it at startup.
+    ///
+    /// Each distinct name is fetched once, names with an unexpired cached value are skipped,
+    /// and the rest are fetched in calls of at most 10 names, as get_parameters does, decrypted
+    /// if default_with_decryption is set in the CacheConfig. Returns the names AWS SSM reported
+    /// as missing or invalid, which aren't cached. If a call fails its error is returned,
+    /// though values from the calls that succeeded are still cached.
+    pub async fn warm(&mut self, names: &[&str]) -> Result<Vec<String>, CacheError> {
+        let result = self.get_parameters(names).send().await?;
+        Ok(result.invalid_parameters)
+    }
+
+    /// Fetches as many of the named parameters into the cache as possible before a deadline,
+    /// e.g. to warm it within a startup budget.
+    ///
+    /// The parameters are fetched with concurrent GetParameter calls, at most
+    /// max_concurrent_requests at a time, decrypted if default_with_decryption is set in the
+    /// CacheConfig. Each value fetched is stored with the cache_item_ttl from the CacheConfig.
+    /// Each fetch runs as its own task, so a failed fetch is reported for its name alone and
+    /// never cancels the others. Fetches still running at the deadline are cancelled and their
+    /// names reported as unfinished; they can be fetched on first use as usual.
+    pub async fn warm_within(&mut self, names: &[&str], deadline: Instant) -> WarmResult {
+        let WarmFetches {
+            mut fetches,
+            mut pending,
+            rejected,
+        } = self.spawn_warm_fetches(names);
+        let mut result = WarmResult::default();
+        for (name, e) in rejected {
+            result.completed.insert(name, Err(e));
+        }
+
+        let deadline = tokio::time::Instant::from_std(deadline);
+        while let Ok(Some(fetch)) = tokio::time::timeout_at(deadline, fetches.join_next()).await {
+            let (name, stored) = self.store_fetched(fetch.expect("warm task panicked"));
+            pending.retain(|pending_name| *pending_name != name);
+            result.completed.insert(name, stored.map(|_| ()));
+        }
+        result.unfinished = pending;
+        result
+    }
+
+    /// Fetches the named parameters into the cache, yielding each name and its value or error
+    /// as soon as its fetch completes, e.g. to report progress or use early results.
+    ///
+    /// The parameters are fetched as with warm_within, at most max_concurrent_requests at a
+    /// time. Each value is stored in the cache when the stream yields it, so poll the stream
+    /// to the end to warm every parameter. Dropping the stream cancels the fetches that
+    /// haven't been yielded.
+    #[cfg(feature = "stream")]
+    pub fn warm_stream(&mut self, names: &[&str]) -> WarmStream<'_, S> {
+        let WarmFetches {
+            fetches, rejected, ..
+        } = self.spawn_warm_fetches(names);
+        WarmStream {
+            parameter_cache: self,
+            rejected: rejected.into_iter(),
+            fetches,
+        }
+    }
+
+    // Starts concurrent fetches of the distinct names, rejecting those SSM wouldn't accept
+    fn spawn_warm_fetches(&self, names: &[&str]) -> WarmFetches {
+        let mut warm = WarmFetches {
+            fetches: JoinSet::new(),
+            pending: Vec::new(),
+            rejected: Vec::new(),
+        };
+        for &name in names {
+            if warm.pending.iter().any(|pending_name| pending_name == name) {
+                continue;
+            }
+            if let Err(e) = validate_name(name) {
+                warm.rejected.push((name.to_string(), e));
+                continue;
+            }
+            warm.pending.push(name.to_string());
+            warm.fetches.spawn(self.fetch_unlocked(name.to_string()));
+        }
+        warm
+    }
+
+    /// Returns a fetch of a parameter that doesn't borrow the cache, so it can run while the
+    /// cache is used for other requests. Pass its result to store_fetched.
+    ///
+    /// The fetch is made as get_parameter makes it: it honors max_concurrent_requests,
+    /// max_fetch_rate, max_retries, the circuit breaker, error_cache_ttl, negative_ttl and
+    /// dry_run, and decrypts if default_with_decryption is set.
+    pub(crate) fn fetch_unlocked(
+        &self,
+        name: String,
+    ) -> impl Future<Output = UnlockedFetch> + Send + 'static {
+        let with_decryption = self.config.default_with_decryption;
+        let answered = match self.config.dry_run {
+            true => Some(self.dry_run_output(&name, with_decryption, None)),
+            false => self
+                .fetch_refused(&CacheKey::new(&*name, with_decryption), false)
+                .map(Err),
+        };
+        let client = self.client.clone();
+        let semaphore = self.request_limit.clone();
+        let retry = self.retry_policy();
+        let span = trace::debug_span!("fetch_parameter", parameter_name = &*name);
+        async move {
+            if let Some(output) = answered {
+                return UnlockedFetch {
+                    name,
+                    started: None,
+                    output,
+                };
+            }
+            let _permit = semaphore.acquire_owned().await;
+            let started = Instant::now();
+            let request = client
+                .get_parameter()
+                .name(&name)
+                .with_decryption(with_decryption);
+            let output = retry
+                .send(|| request.clone().send())
+                .await
+                .map_err(|e| CacheError::from_sdk_error(&name, e));
+            UnlockedFetch {
+                name,
+                started: Some(started),
+                output,
+            }
+        }
+        .instrument(span)
+    }
+
+    /// Records a fetch made with fetch_unlocked and caches its value, or counts the failure
+    /// toward evict_after_failed_refreshes.
+    pub(crate) fn store_fetched(
+        &mut self,
+        fetch: UnlockedFetch,
+    ) -> (String, Result<String, CacheError>) {
+        let UnlockedFetch {
+            name,
+            started,
+            output,
+        } = fetch;
+        let key = CacheKey::new(&*name, self.config.default_with_decryption);
+        if let Some(started) = started {
+            self.record_get_parameter(&key, started, &output, false);
+        }
+        let stored = output.and_then(|output| self.take_value(&name, output));
+        if stored.is_err() {
+            self.record_failed_refresh(&key);
+        }
+        let stored = stored.map(|(value, parameter)| {
+            let mut cache_item = CacheItem::new_with_clock(
+                value.clone(),
+                self.config.cache_item_ttl,
+                self.clock.clone(),
+            );
+            cache_item.set_metadata(&parameter);
+            self.insert(key, cache_item);
+            value
+        });
+        (name, stored)
+    }
+}
+
+/// A fetch made with fetch_unlocked.
+pub(crate) struct UnlockedFetch {
+    pub(crate) name: String,
+    // When the request was sent, or None if the fetch was answered without calling AWS
+    pub(crate) started: Option<Instant>,
+    pub(crate) output: Result<GetParameterOutput, CacheError>,
+}
+
+// The fetches started to warm the cache, and the names rejected without fetching
+struct WarmFetches {
+    fetches: JoinSet<UnlockedFetch>,
+    pending: Vec<String>,
+    rejected: Vec<(String, CacheError)>,
+}
+
+/// The results of warming the cache, returned by ParameterCache::warm_stream.
+///
+/// Yields each parameter name with its value or the error fetching it, in the order the
+/// fetches complete.
+#[cfg(feature = "stream")]
+pub struct WarmStream<'a, S> {
+    parameter_cache: &'a mut ParameterCache<S>,
+    rejected: std::vec::IntoIter<(String, CacheError)>,
+    fetches: JoinSet<UnlockedFetch>,
+}
+
+#[cfg(feature = "stream")]
+impl<S: CacheStore<CacheKey, CacheItem<String>>> futures_core::Stream for WarmStream<'_, S> {
+    type Item = (String, Result<String, CacheError>);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some((name, e)) = this.rejected.next() {
+            return Poll::Ready(Some((name, Err(e))));
+        }
+        this.fetches.poll_join_next(cx).map(|fetch| {
+            fetch.map(|fetch| {
+                this.parameter_cache
+                    .store_fetched(fetch.expect("warm task panicked"))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::test_util::{
+        batch_response, error_response, mock_ssm_client, never_responding_ssm_client,
+        parameter_response,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn warm_dedupes_and_chunks() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |operation, body| {
+            assert_eq!(operation, "GetParameters");
+            assert!(!body.contains("cached"), "{}", body);
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, batch_response(body))
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+        parameter_cache.insert(
+            CacheKey::new("cached", false),
+            CacheItem::new("value".to_string(), 3600000000000),
+        );
+
+        // 21 distinct names to fetch, each given twice, plus a fresh cached one
+        let mut names: Vec<String> = (0..20).map(|i| format!("p{}", i)).collect();
+        names.extend(["missing".to_string(), "cached".to_string()]);
+        names.extend(names.clone());
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        let invalid = parameter_cache.warm(&names).await.unwrap();
+        assert_eq!(invalid, vec!["missing"]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(parameter_cache.cache.len(), 21);
+    }
+
+    #[tokio::test]
+    async fn warm_within_completes_before_deadline() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("missing") {
+            true => error_response("ParameterNotFound"),
+            false => (200, parameter_response("service/parameter", "value")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = parameter_cache
+            .warm_within(&["a", "b", "missing"], deadline)
+            .await;
+
+        assert!(result.unfinished.is_empty());
+        assert!(result.completed["a"].is_ok() && result.completed["b"].is_ok());
+        assert!(result.completed["missing"]
+            .as_ref()
+            .unwrap_err()
+            .is_not_found());
+        assert_eq!(parameter_cache.cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn warm_within_retries_kms_throttling() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client =
+            mock_ssm_client(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => error_response("KMSThrottlingException"),
+                _ => (200, parameter_response("service/parameter", "value")),
+            });
+        let config = CacheConfig::new()
+            .max_retries(1)
+            .base_backoff(Duration::from_millis(1));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = parameter_cache.warm_within(&["a"], deadline).await;
+        assert!(result.completed["a"].is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn warm_within_honors_dry_run() {
+        let mock_ssm_client = mock_ssm_client(|operation, _| panic!("called {}", operation));
+        let config = CacheConfig::new()
+            .dry_run(true)
+            .dry_run_placeholder("placeholder");
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = parameter_cache.warm_within(&["a"], deadline).await;
+        assert!(result.completed["a"].is_ok());
+        let key = CacheKey::new("a", false);
+        assert_eq!(
+            parameter_cache.cache.get(&key).unwrap().value,
+            "placeholder"
+        );
+    }
+
+    #[tokio::test]
+    async fn warm_within_remembers_failures_and_empty_values() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mock_ssm_client = mock_ssm_client(move |_, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match body {
+                body if body.contains("empty") => (200, parameter_response("empty", "")),
+                body if body.contains("missing") => error_response("ParameterNotFound"),
+                _ => error_response("AccessDeniedException"),
+            }
+        });
+        let config = CacheConfig::new()
+            .empty_as_not_found(true)
+            .error_cache_ttl(Duration::from_secs(60))
+            .negative_ttl(Duration::from_secs(60));
+        let mut parameter_cache = ParameterCache::new_with_config(mock_ssm_client, config);
+
+        let names = ["denied", "missing", "empty"];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = parameter_cache.warm_within(&names, deadline).await;
+        assert!(matches!(
+            result.completed["denied"],
+            Err(CacheError::AccessDenied { .. })
+        ));
+        for name in ["missing", "empty"] {
+            let completed = result.completed[name].as_ref();
+            assert!(completed.is_err_and(CacheError::is_not_found), "{}", name);
+        }
+        assert_eq!(parameter_cache.cache.len(), 0);
+
+        let result = parameter_cache.warm_within(&names[..2], deadline).await;
+        assert!(matches!(
+            result.completed["denied"],
+            Err(CacheError::RecentFailure { .. })
+        ));
+        assert!(result.completed["missing"]
+            .as_ref()
+            .is_err_and(CacheError::is_not_found));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn warm_within_isolates_failures() {
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("denied") {
+            true => error_response("AccessDeniedException"),
+            false => (200, parameter_response("service/parameter", "value")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let names = ["a", "denied", "b", "c"];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = parameter_cache.warm_within(&names, deadline).await;
+
+        assert!(result.unfinished.is_empty());
+        assert_eq!(result.completed.len(), 4);
+        assert!(matches!(
+            result.completed["denied"],
+            Err(CacheError::AccessDenied { .. })
+        ));
+        for name in ["a", "b", "c"] {
+            assert!(result.completed[name].is_ok(), "{}", name);
+        }
+        assert_eq!(parameter_cache.cache.len(), 3);
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn warm_stream_yields_each_result() {
+        use futures_core::Stream;
+
+        let mock_ssm_client = mock_ssm_client(|_, body| match body.contains("missing") {
+            true => error_response("ParameterNotFound"),
+            false => (200, parameter_response("service/parameter", "value")),
+        });
+        let mut parameter_cache = ParameterCache::new(mock_ssm_client);
+
+        let mut stream = parameter_cache.warm_stream(&["a", "missing", "bad name"]);
+        let mut results = HashMap::new();
+        while let Some((name, result)) =
+            std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+        {
+            results.insert(name, result);
+        }
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results["a"].as_ref().unwrap(), "value");
+        assert!(results["missing"].as_ref().unwrap_err().is_not_found());
+        assert!(matches!(
+            results["bad name"],
+            Err(CacheError::InvalidName { .. })
+        ));
+        assert_eq!(parameter_cache.cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn warm_within_reports_unfinished() {
+        let mut parameter_cache = ParameterCache::new(never_responding_ssm_client());
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let result = parameter_cache.warm_within(&["a", "b"], deadline).await;
+
+        assert!(result.completed.is_empty());
+        assert_eq!(result.unfinished, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(parameter_cache.cache.len(), 0);
+    }
+}